@@ -0,0 +1,706 @@
+// integers that grow instead of overflowing, stored as base 2^32 digits with the least significant first
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::TryFromIntError;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "cannot parse an integer from an empty string"),
+            ParseError::InvalidDigit(c) => write!(f, "invalid digit {:?}", c),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An unsigned integer of any size.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    // no trailing zero limbs, so zero is empty and equal values have equal limbs
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> BigUint {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 32)
+            .is_some_and(|limb| limb >> (i % 32) & 1 == 1)
+    }
+
+    // self * m + a, the step both parsing and long division take
+    fn mul_add_small(&self, m: u32, a: u32) -> BigUint {
+        let mut carry = a as u64;
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        for &limb in &self.limbs {
+            let t = limb as u64 * m as u64 + carry;
+            limbs.push(t as u32);
+            carry = t >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::from_limbs(limbs)
+    }
+
+    fn div_rem_small(&self, d: u32) -> (BigUint, u32) {
+        let mut rem = 0u64;
+        let mut limbs = vec![0; self.limbs.len()];
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let t = rem << 32 | limb as u64;
+            limbs[i] = (t / d as u64) as u32;
+            rem = t % d as u64;
+        }
+        (BigUint::from_limbs(limbs), rem as u32)
+    }
+
+    /// `None` when `other` is larger, where a primitive would overflow.
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+        let mut borrow = false;
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let (t, b1) = limb.overflowing_sub(other.limbs.get(i).copied().unwrap_or(0));
+            let (t, b2) = t.overflowing_sub(borrow as u32);
+            limbs.push(t);
+            borrow = b1 || b2;
+        }
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    /// Quotient and remainder, `None` when dividing by zero.
+    pub fn checked_div_rem(&self, other: &BigUint) -> Option<(BigUint, BigUint)> {
+        match other.limbs[..] {
+            [] => return None,
+            [d] => {
+                let (q, r) = self.div_rem_small(d);
+                return Some((q, BigUint::from(r)));
+            }
+            _ => {}
+        }
+        // shift and subtract, a bit at a time
+        let mut q = vec![0u32; self.limbs.len()];
+        let mut r = BigUint::zero();
+        for i in (0..self.bits()).rev() {
+            r = r.mul_add_small(2, self.bit(i) as u32);
+            if let Some(less) = r.checked_sub(other) {
+                r = less;
+                q[i / 32] |= 1 << (i % 32);
+            }
+        }
+        Some((BigUint::from_limbs(q), r))
+    }
+
+    /// Panics when `other` is zero, like `/` and `%` do.
+    pub fn div_rem(&self, other: &BigUint) -> (BigUint, BigUint) {
+        self.checked_div_rem(other)
+            .expect("attempt to divide by zero")
+    }
+
+    /// Parses digits in `radix`, which has to be between 2 and 36 like for the primitives.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigUint, ParseError> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix must be between 2 and 36, got {}",
+            radix
+        );
+        let digits = s.strip_prefix('+').unwrap_or(s);
+        if digits.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        digits.chars().try_fold(BigUint::zero(), |n, c| {
+            let digit = c.to_digit(radix).ok_or(ParseError::InvalidDigit(c))?;
+            Ok(n.mul_add_small(radix, digit))
+        })
+    }
+
+    fn to_decimal(&self) -> String {
+        // nine decimal digits at a time, the most a u32 holds
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.div_rem_small(1_000_000_000);
+            chunks.push(r);
+            n = q;
+        }
+        let mut s = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            s += &format!("{:09}", chunk);
+        }
+        s
+    }
+
+    fn to_hex(&self) -> String {
+        let mut limbs = self.limbs.iter().rev();
+        let mut s = format!("{:x}", limbs.next().unwrap_or(&0));
+        for limb in limbs {
+            s += &format!("{:08x}", limb);
+        }
+        s
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_decimal())
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex())
+    }
+}
+
+impl fmt::UpperHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex().to_uppercase())
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<BigUint, ParseError> {
+        BigUint::from_str_radix(s, 10)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut carry = false;
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        for (i, &limb) in long.limbs.iter().enumerate() {
+            let (t, c1) = limb.overflowing_add(short.limbs.get(i).copied().unwrap_or(0));
+            let (t, c2) = t.overflowing_add(carry as u32);
+            limbs.push(t);
+            carry = c1 || c2;
+        }
+        limbs.push(carry as u32);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let t = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Div<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).1
+    }
+}
+
+/// A signed integer of any size, a sign next to a `BigUint`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    // never true for zero, so there is only one zero
+    negative: bool,
+    magnitude: BigUint,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    fn new(negative: bool, magnitude: BigUint) -> BigInt {
+        BigInt {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    /// Rounds towards zero and gives the remainder the sign of `self`, as `/` and `%` on `i128` do.
+    pub fn checked_div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        let (q, r) = self.magnitude.checked_div_rem(&other.magnitude)?;
+        Some((
+            BigInt::new(self.negative != other.negative, q),
+            BigInt::new(self.negative, r),
+        ))
+    }
+
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        self.checked_div_rem(other)
+            .expect("attempt to divide by zero")
+    }
+
+    /// Like `BigUint::from_str_radix`, with an optional sign in front.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigInt, ParseError> {
+        match s.strip_prefix('-') {
+            // -+1 is one sign too many
+            Some(digits) if digits.starts_with('+') => Err(ParseError::InvalidDigit('+')),
+            Some(digits) => Ok(BigInt::new(true, BigUint::from_str_radix(digits, radix)?)),
+            None => Ok(BigInt::from(BigUint::from_str_radix(s, radix)?)),
+        }
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(magnitude: BigUint) -> BigInt {
+        BigInt::new(false, magnitude)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude.to_decimal())
+    }
+}
+
+// as for the primitives, with a minus sign instead of two's complement
+impl fmt::LowerHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "0x", &self.magnitude.to_hex())
+    }
+}
+
+impl fmt::UpperHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(
+            !self.negative,
+            "0x",
+            &self.magnitude.to_hex().to_uppercase(),
+        )
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<BigInt, ParseError> {
+        BigInt::from_str_radix(s, 10)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (negative, _) => other.negative.cmp(&negative),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude)
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, &self.magnitude + &other.magnitude);
+        }
+        // opposite signs, the larger magnitude wins
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => BigInt::new(other.negative, &other.magnitude - &self.magnitude),
+            _ => BigInt::new(self.negative, &self.magnitude - &other.magnitude),
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            &self.magnitude * &other.magnitude,
+        )
+    }
+}
+
+impl Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
+// the by-value forms borrow and call the ones above
+macro_rules! by_value {
+    ($big:ty: $($trait:ident $method:ident),*) => {$(
+        impl $trait for $big {
+            type Output = $big;
+
+            fn $method(self, other: $big) -> $big {
+                (&self).$method(&other)
+            }
+        }
+
+        impl $trait<&$big> for $big {
+            type Output = $big;
+
+            fn $method(self, other: &$big) -> $big {
+                (&self).$method(other)
+            }
+        }
+    )*};
+}
+
+by_value!(BigUint: Add add, Sub sub, Mul mul, Div div, Rem rem);
+by_value!(BigInt: Add add, Sub sub, Mul mul, Div div, Rem rem);
+
+// TryFromIntError can't be built directly, so make a conversion fail
+fn out_of_range() -> TryFromIntError {
+    u8::try_from(-1i8).unwrap_err()
+}
+
+macro_rules! from_unsigned {
+    ($($t:ty),*) => {$(
+        impl From<$t> for BigUint {
+            fn from(n: $t) -> BigUint {
+                let mut n = n as u128;
+                let mut limbs = Vec::new();
+                while n > 0 {
+                    limbs.push(n as u32);
+                    n >>= 32;
+                }
+                BigUint { limbs }
+            }
+        }
+
+        impl From<$t> for BigInt {
+            fn from(n: $t) -> BigInt {
+                BigInt::from(BigUint::from(n))
+            }
+        }
+
+        impl TryFrom<&BigUint> for $t {
+            type Error = TryFromIntError;
+
+            fn try_from(n: &BigUint) -> Result<$t, TryFromIntError> {
+                if n.limbs.len() > 4 {
+                    return Err(out_of_range());
+                }
+                let wide = n.limbs.iter().rev().fold(0u128, |acc, &limb| acc << 32 | limb as u128);
+                <$t>::try_from(wide).map_err(|_| out_of_range())
+            }
+        }
+    )*};
+}
+
+macro_rules! from_signed {
+    ($($t:ty),*) => {$(
+        impl From<$t> for BigInt {
+            fn from(n: $t) -> BigInt {
+                BigInt::new(n < 0, BigUint::from((n as i128).unsigned_abs()))
+            }
+        }
+
+        impl TryFrom<&BigInt> for $t {
+            type Error = TryFromIntError;
+
+            fn try_from(n: &BigInt) -> Result<$t, TryFromIntError> {
+                let magnitude = u128::try_from(&n.magnitude)?;
+                let wide = if n.negative {
+                    // i128::MIN has no positive counterpart, so subtract the magnitude from zero
+                    0i128.checked_sub_unsigned(magnitude)
+                } else {
+                    i128::try_from(magnitude).ok()
+                };
+                wide.and_then(|wide| <$t>::try_from(wide).ok()).ok_or_else(out_of_range)
+            }
+        }
+    )*};
+}
+
+from_unsigned!(u8, u16, u32, u64, u128, usize);
+from_signed!(i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const CASES: usize = 2000;
+
+    // the edges, then random values of random widths so small and large both come up
+    fn values(rng: &mut StdRng) -> impl Iterator<Item = i128> + '_ {
+        let edges = [
+            0,
+            1,
+            -1,
+            2,
+            u32::MAX as i128,
+            1 << 32,
+            i64::MIN as i128,
+            i128::MAX,
+            i128::MIN,
+        ];
+        edges.into_iter().chain((0..CASES).map(move |_| {
+            let shift = rng.gen_range(0..128);
+            rng.gen::<i128>() >> shift
+        }))
+    }
+
+    fn big(n: i128) -> BigInt {
+        BigInt::from(n)
+    }
+
+    fn check_signed(property: impl Fn(i128, i128)) {
+        let mut rng = StdRng::seed_from_u64(24);
+        let a: Vec<_> = values(&mut rng).collect();
+        let b: Vec<_> = values(&mut rng).collect();
+        for (&a, &b) in a.iter().zip(b.iter().rev()) {
+            property(a, b);
+        }
+    }
+
+    #[test]
+    fn signed_arithmetic_matches_i128() {
+        check_signed(|a, b| {
+            let (x, y) = (big(a), big(b));
+            if let Some(sum) = a.checked_add(b) {
+                assert_eq!(&x + &y, big(sum), "{} + {}", a, b);
+            }
+            if let Some(difference) = a.checked_sub(b) {
+                assert_eq!(&x - &y, big(difference), "{} - {}", a, b);
+            }
+            if let Some(product) = a.checked_mul(b) {
+                assert_eq!(&x * &y, big(product), "{} * {}", a, b);
+            }
+            if let (Some(q), Some(r)) = (a.checked_div(b), a.checked_rem(b)) {
+                assert_eq!(x.div_rem(&y), (big(q), big(r)), "{} divmod {}", a, b);
+            }
+            assert_eq!(x.cmp(&y), a.cmp(&b), "{} cmp {}", a, b);
+            // and past where i128 gives up
+            let p = &x * &y;
+            let s = &p + &y;
+            assert_eq!(&s - &y, p);
+            if b != 0 {
+                assert_eq!(
+                    s.div_rem(&y),
+                    (&x + &big(1), big(0)),
+                    "({} * {} + {}) / {}",
+                    a,
+                    b,
+                    b,
+                    b
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn unsigned_arithmetic_matches_u128() {
+        check_signed(|a, b| {
+            let (a, b) = (a as u128, b as u128);
+            let (x, y) = (BigUint::from(a), BigUint::from(b));
+            if let Some(sum) = a.checked_add(b) {
+                assert_eq!(&x + &y, BigUint::from(sum), "{} + {}", a, b);
+            }
+            assert_eq!(
+                x.checked_sub(&y),
+                a.checked_sub(b).map(BigUint::from),
+                "{} - {}",
+                a,
+                b
+            );
+            if let Some(product) = a.checked_mul(b) {
+                assert_eq!(&x * &y, BigUint::from(product), "{} * {}", a, b);
+            }
+            let divmod = a
+                .checked_div(b)
+                .map(|q| (BigUint::from(q), BigUint::from(a % b)));
+            assert_eq!(x.checked_div_rem(&y), divmod, "{} divmod {}", a, b);
+            assert_eq!(x.cmp(&y), a.cmp(&b), "{} cmp {}", a, b);
+            assert_eq!(u128::try_from(&(&x * &y)).ok(), a.checked_mul(b));
+        });
+    }
+
+    #[test]
+    fn text_matches_i128() {
+        check_signed(|a, _| {
+            let x = big(a);
+            let sign = if a < 0 { "-" } else { "" };
+            let hex = format!("{}{:x}", sign, a.unsigned_abs());
+            assert_eq!(x.to_string(), a.to_string());
+            assert_eq!(format!("{:x}", x), hex);
+            assert_eq!(format!("{:X}", x), hex.to_uppercase());
+            assert_eq!(format!("{:>50}", x), format!("{:>50}", a));
+            assert_eq!(format!("{:+}", x), format!("{:+}", a));
+            assert_eq!(a.to_string().parse(), Ok(x.clone()));
+            assert_eq!(BigInt::from_str_radix(&hex, 16), Ok(x.clone()));
+            assert_eq!(i128::try_from(&x), Ok(a));
+            let m = BigUint::from(a as u128);
+            assert_eq!(m.to_string(), (a as u128).to_string());
+            assert_eq!(format!("{:#x}", m), format!("{:#x}", a as u128));
+            assert_eq!(
+                BigUint::from_str_radix(&format!("{:X}", a as u128), 16),
+                Ok(m)
+            );
+        });
+    }
+
+    #[test]
+    fn text_edges() {
+        let googol = format!("1{}", "0".repeat(100));
+        let n: BigUint = googol.parse().unwrap();
+        assert_eq!(n.to_string(), googol);
+        let ten = BigUint::from(10u8);
+        assert_eq!(&(&n / &ten) * &ten, n);
+        assert_eq!(&n % &ten, BigUint::zero());
+        assert_eq!(BigInt::from_str_radix("-ff", 16), Ok(big(-255)));
+        assert_eq!(BigInt::from_str_radix("+FF", 16), Ok(big(255)));
+        assert_eq!("-0".parse(), Ok(BigInt::zero()));
+        assert!(!"-0".parse::<BigInt>().unwrap().is_negative());
+        assert_eq!("".parse::<BigUint>(), Err(ParseError::Empty));
+        assert_eq!("-".parse::<BigInt>(), Err(ParseError::Empty));
+        assert_eq!("--1".parse::<BigInt>(), Err(ParseError::InvalidDigit('-')));
+        assert_eq!("-+1".parse::<BigInt>(), Err(ParseError::InvalidDigit('+')));
+        assert_eq!("-1".parse::<BigUint>(), Err(ParseError::InvalidDigit('-')));
+        assert_eq!("12g".parse::<BigUint>(), Err(ParseError::InvalidDigit('g')));
+        assert!(u8::try_from(&n).is_err());
+        assert!(i128::try_from(&-BigInt::from(n)).is_err());
+        assert_eq!(i128::try_from(&big(i128::MIN)), Ok(i128::MIN));
+        assert!(i128::try_from(&-big(i128::MIN)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn dividing_by_zero_panics() {
+        let _ = big(1) / big(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to subtract with overflow")]
+    fn unsigned_underflow_panics() {
+        let _ = BigUint::from(1u8) - BigUint::from(2u8);
+    }
+}
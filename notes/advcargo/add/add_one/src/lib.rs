@@ -2,8 +2,35 @@ pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
 
-pub fn add_one(x: i32) -> i32 {
-    x + 1
+pub mod big;
+
+pub use big::{BigInt, BigUint};
+
+use std::ops::Add;
+
+/// Numbers that have a 1 to add.
+pub trait One {
+    fn one() -> Self;
+}
+
+macro_rules! one {
+    ($($t:ty: $one:expr),*) => {$(
+        impl One for $t {
+            fn one() -> $t {
+                $one
+            }
+        }
+    )*};
+}
+
+one!(i8: 1, i16: 1, i32: 1, i64: 1, i128: 1, isize: 1);
+one!(u8: 1, u16: 1, u32: 1, u64: 1, u128: 1, usize: 1);
+one!(f32: 1.0, f64: 1.0);
+one!(BigUint: BigUint::from(1u8), BigInt: BigInt::from(1u8));
+
+// the primitives still overflow at their MAX, BigUint and BigInt just grow
+pub fn add_one<T: Add<Output = T> + One>(x: T) -> T {
+    x + T::one()
 }
 
 #[cfg(test)]
@@ -20,4 +47,16 @@ mod tests {
     fn it_works_too() {
         assert_eq!(3, add_one(2));
     }
+
+    #[test]
+    fn add_one_is_generic() {
+        assert_eq!(u8::MAX, add_one(254u8));
+        assert_eq!(1.5, add_one(0.5));
+        assert_eq!(
+            add_one(BigInt::from(i128::MAX)).to_string(),
+            "170141183460469231731687303715884105728"
+        );
+        assert_eq!(add_one(BigInt::from(-1)), BigInt::zero());
+        assert_eq!(add_one(BigUint::from(u32::MAX)), BigUint::from(1u64 << 32));
+    }
 }
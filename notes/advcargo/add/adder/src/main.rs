@@ -1,6 +1,81 @@
-use add_one;
+// a calculator REPL, `cargo run -p adder` and type `x = 10` then `++x`
+use calc::{Calculator, Mode};
+use std::io::{self, BufRead, Write};
+use std::process;
 
-fn main() {
+const HELP: &str = "\
+expressions use + - * / % ^ and parentheses, x = 1 assigns, ++x and x++ increment
+:int and :float switch modes, :vars lists the variables, :quit leaves";
+
+fn repl(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut calc = Calculator::new(Mode::Int);
     let num = 10;
-    println!("Hello, world! {num} plus one is {}!", add_one::add_one(num));
+    writeln!(
+        output,
+        "Hello, world! {num} plus one is {}! (:help for help)",
+        add_one::add_one(num)
+    )?;
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            // ctrl-d, end the prompt's line
+            return writeln!(output);
+        }
+        match line.trim() {
+            "" => {}
+            ":q" | ":quit" => return Ok(()),
+            ":help" => writeln!(output, "{}", HELP)?,
+            ":int" => calc.set_mode(Mode::Int),
+            ":float" => calc.set_mode(Mode::Float),
+            ":vars" => {
+                for (name, value) in calc.vars() {
+                    writeln!(output, "{} = {}", name, value)?;
+                }
+            }
+            command if command.starts_with(':') => {
+                writeln!(output, "unknown command {}, try :help", command)?
+            }
+            expr => match calc.eval(expr) {
+                Ok(value) => writeln!(output, "{}", value)?,
+                Err(e) => writeln!(output, "error: {}", e)?,
+            },
+        }
+    }
+}
+
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(e) = repl(stdin.lock(), stdout.lock()) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_session() {
+        let input = "x = 10\n++x\n:float\nx / 4\n\n:vars\n:nope\n1 +\n:quit\nnever read\n";
+        let mut output = Vec::new();
+        repl(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "> 10",
+                "> 11",
+                "> > 2.75",
+                "> > x = 11",
+                "> unknown command :nope, try :help",
+                "> error: column 4: expected a value",
+                "> ",
+            ]
+        );
+    }
 }
@@ -0,0 +1,274 @@
+// a calculator with variables, built from the other crates in the workspace: `++` is add_one
+use std::collections::HashMap;
+use std::fmt;
+
+pub mod parse;
+
+use parse::{Expr, Op};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Int(n) => write!(f, "{}", n),
+            Number::Float(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// What kind of number every value is turned into before it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Whole numbers, division rounds towards zero and overflow is an error.
+    #[default]
+    Int,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// `at` is the byte offset in the line.
+    Syntax {
+        at: usize,
+        message: String,
+    },
+    UnknownVariable(String),
+    DivisionByZero,
+    Overflow,
+    /// A float with a fraction used in integer mode.
+    NotAnInteger(f64),
+    NegativeExponent,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax { at, message } => write!(f, "column {}: {}", at + 1, message),
+            Error::UnknownVariable(name) => write!(f, "{} is not defined", name),
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow => write!(f, "the result is too large"),
+            Error::NotAnInteger(n) => write!(f, "{} is not an integer", n),
+            Error::NegativeExponent => write!(f, "integers can't be raised to a negative power"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Default)]
+pub struct Calculator {
+    mode: Mode,
+    vars: HashMap<String, Number>,
+}
+
+impl Calculator {
+    pub fn new(mode: Mode) -> Calculator {
+        Calculator {
+            mode,
+            vars: HashMap::new(),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    // variables keep what they hold, they are converted when they are next read
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    pub fn get(&self, name: &str) -> Option<Number> {
+        self.vars.get(name).copied()
+    }
+
+    /// Every variable, sorted by name.
+    pub fn vars(&self) -> Vec<(&str, Number)> {
+        let mut vars: Vec<_> = self.vars.iter().map(|(k, &v)| (k.as_str(), v)).collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+
+    /// Evaluates one line, assignments and increments in it stay for the next.
+    pub fn eval(&mut self, line: &str) -> Result<Number, Error> {
+        let expr = parse::parse(line)?;
+        self.eval_expr(&expr)
+    }
+
+    pub fn eval_expr(&mut self, expr: &Expr) -> Result<Number, Error> {
+        match expr {
+            Expr::Number(n) => self.convert(*n),
+            Expr::Var(name) => self.var(name),
+            Expr::Neg(inner) => match self.eval_expr(inner)? {
+                Number::Int(n) => n.checked_neg().map(Number::Int).ok_or(Error::Overflow),
+                Number::Float(n) => Ok(Number::Float(-n)),
+            },
+            Expr::Binary(op, left, right) => {
+                let left = self.eval_expr(left)?;
+                let right = self.eval_expr(right)?;
+                match (left, right) {
+                    (Number::Int(a), Number::Int(b)) => int_op(*op, a, b).map(Number::Int),
+                    (Number::Float(a), Number::Float(b)) => float_op(*op, a, b).map(Number::Float),
+                    // convert makes every value the mode's kind
+                    _ => unreachable!("mixed {:?} and {:?}", left, right),
+                }
+            }
+            Expr::Assign(name, value) => {
+                let value = self.eval_expr(value)?;
+                self.vars.insert(name.clone(), value);
+                Ok(value)
+            }
+            Expr::Increment { name, prefix } => {
+                let old = self.var(name)?;
+                let new = match old {
+                    // add_one would overflow at the top, the same as `+`
+                    Number::Int(i64::MAX) => return Err(Error::Overflow),
+                    Number::Int(n) => Number::Int(add_one::add_one(n)),
+                    Number::Float(n) => Number::Float(add_one::add_one(n)),
+                };
+                self.vars.insert(name.clone(), new);
+                Ok(if *prefix { new } else { old })
+            }
+        }
+    }
+
+    fn var(&self, name: &str) -> Result<Number, Error> {
+        let value = self
+            .get(name)
+            .ok_or_else(|| Error::UnknownVariable(name.to_string()))?;
+        self.convert(value)
+    }
+
+    fn convert(&self, n: Number) -> Result<Number, Error> {
+        match (self.mode, n) {
+            (Mode::Int, Number::Float(f)) => {
+                // 2.0 is fine, 2.5 isn't, and neither is anything past i64
+                if f.fract() != 0.0 || !f.is_finite() {
+                    Err(Error::NotAnInteger(f))
+                } else if f < -(2f64.powi(63)) || f >= 2f64.powi(63) {
+                    Err(Error::Overflow)
+                } else {
+                    Ok(Number::Int(f as i64))
+                }
+            }
+            (Mode::Float, Number::Int(i)) => Ok(Number::Float(i as f64)),
+            _ => Ok(n),
+        }
+    }
+}
+
+fn int_op(op: Op, a: i64, b: i64) -> Result<i64, Error> {
+    if b == 0 && matches!(op, Op::Div | Op::Rem) {
+        return Err(Error::DivisionByZero);
+    }
+    let result = match op {
+        Op::Add => a.checked_add(b),
+        Op::Sub => a.checked_sub(b),
+        Op::Mul => a.checked_mul(b),
+        Op::Div => a.checked_div(b),
+        Op::Rem => a.checked_rem(b),
+        Op::Pow => {
+            let b = u32::try_from(b).map_err(|_| {
+                if b < 0 {
+                    Error::NegativeExponent
+                } else {
+                    Error::Overflow
+                }
+            })?;
+            a.checked_pow(b)
+        }
+    };
+    result.ok_or(Error::Overflow)
+}
+
+fn float_op(op: Op, a: f64, b: f64) -> Result<f64, Error> {
+    if b == 0.0 && matches!(op, Op::Div | Op::Rem) {
+        return Err(Error::DivisionByZero);
+    }
+    let result = match op {
+        Op::Add => a + b,
+        Op::Sub => a - b,
+        Op::Mul => a * b,
+        Op::Div => a / b,
+        Op::Rem => a % b,
+        Op::Pow => a.powf(b),
+    };
+    // finite in, infinite out only happens past f64::MAX (or for 0 ^ -1)
+    if result.is_infinite() {
+        Err(Error::Overflow)
+    } else {
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(calc: &mut Calculator, line: &str) -> String {
+        match calc.eval(line) {
+            Ok(n) => n.to_string(),
+            Err(e) => format!("error: {}", e),
+        }
+    }
+
+    #[test]
+    fn integer_mode() {
+        let mut calc = Calculator::default();
+        assert_eq!(eval(&mut calc, "1 + 2 * 3"), "7");
+        assert_eq!(eval(&mut calc, "(1 + 2) * 3"), "9");
+        assert_eq!(eval(&mut calc, "7 / 2"), "3");
+        assert_eq!(eval(&mut calc, "-7 % 3"), "-1");
+        assert_eq!(eval(&mut calc, "2 ^ 3 ^ 2"), "512");
+        assert_eq!(eval(&mut calc, "-2 ^ 2"), "-4");
+        assert_eq!(eval(&mut calc, "4.0 / 2"), "2");
+        assert_eq!(eval(&mut calc, "1 / 0"), "error: division by zero");
+        assert_eq!(eval(&mut calc, "2 ^ 63"), "error: the result is too large");
+        assert_eq!(
+            eval(&mut calc, "2 ^ -1"),
+            "error: integers can't be raised to a negative power"
+        );
+        assert_eq!(eval(&mut calc, "1.5 + 1"), "error: 1.5 is not an integer");
+        assert_eq!(eval(&mut calc, "1 +"), "error: column 4: expected a value");
+    }
+
+    #[test]
+    fn float_mode() {
+        let mut calc = Calculator::new(Mode::Float);
+        assert_eq!(eval(&mut calc, "7 / 2"), "3.5");
+        assert_eq!(eval(&mut calc, "2 ^ 0.5 * 2 ^ 0.5"), "2.0000000000000004");
+        assert_eq!(eval(&mut calc, "1 / 0"), "error: division by zero");
+        assert_eq!(
+            eval(&mut calc, "10 ^ 400"),
+            "error: the result is too large"
+        );
+    }
+
+    #[test]
+    fn variables_and_increments() {
+        let mut calc = Calculator::default();
+        assert_eq!(eval(&mut calc, "x"), "error: x is not defined");
+        assert_eq!(eval(&mut calc, "x = y = 2"), "2");
+        assert_eq!(eval(&mut calc, "x++ * 10"), "20");
+        assert_eq!(eval(&mut calc, "++x * 10"), "40");
+        assert_eq!(eval(&mut calc, "x + y"), "6");
+        assert_eq!(calc.vars(), [("x", Number::Int(4)), ("y", Number::Int(2))]);
+
+        calc.eval("big = 9223372036854775807").unwrap();
+        assert_eq!(eval(&mut calc, "big++"), "error: the result is too large");
+        assert_eq!(calc.get("big"), Some(Number::Int(i64::MAX)));
+
+        // a variable keeps its value across modes and is converted when it is used
+        calc.set_mode(Mode::Float);
+        assert_eq!(eval(&mut calc, "half = x / 8"), "0.5");
+        assert_eq!(eval(&mut calc, "++half"), "1.5");
+        calc.set_mode(Mode::Int);
+        assert_eq!(eval(&mut calc, "half"), "error: 1.5 is not an integer");
+        assert_eq!(eval(&mut calc, "x"), "4");
+    }
+}
@@ -0,0 +1,328 @@
+// turns a line like `x = (1 + 2) * -y ^ 2` into an Expr, evaluating it is the Calculator's job
+use crate::{Error, Number};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(Number),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Assign(String, Box<Expr>),
+    /// `++x` gives the new value, `x++` the old one.
+    Increment {
+        name: String,
+        prefix: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Number),
+    Ident(String),
+    Op(&'static str),
+}
+
+// longest first, so `++` isn't read as two `+`
+const OPS: [&str; 10] = ["++", "+", "-", "*", "/", "%", "^", "(", ")", "="];
+
+fn syntax(at: usize, message: impl Into<String>) -> Error {
+    Error::Syntax {
+        at,
+        message: message.into(),
+    }
+}
+
+// every token with the byte offset it starts at, for pointing at mistakes
+fn tokenize(line: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = line.char_indices().peekable();
+    while let Some(&(at, c)) = rest.peek() {
+        if c.is_whitespace() {
+            rest.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = at;
+            while let Some(&(i, c)) = rest.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                rest.next();
+            }
+            let text = &line[at..end];
+            let number = if text.contains('.') {
+                text.parse().map(Number::Float).ok()
+            } else {
+                text.parse().map(Number::Int).ok()
+            };
+            let number = number.ok_or_else(|| syntax(at, format!("bad number {}", text)))?;
+            tokens.push((at, Token::Number(number)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = at;
+            while let Some(&(i, c)) = rest.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                rest.next();
+            }
+            tokens.push((at, Token::Ident(line[at..end].to_string())));
+        } else {
+            let op = OPS
+                .iter()
+                .find(|op| line[at..].starts_with(*op))
+                .ok_or_else(|| syntax(at, format!("unexpected {}", c)))?;
+            for _ in 0..op.len() {
+                rest.next();
+            }
+            tokens.push((at, Token::Op(op)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses one expression, the whole line has to be used up.
+pub fn parse(line: &str) -> Result<Expr, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(line)?,
+        pos: 0,
+        end: line.len(),
+    };
+    let expr = parser.expr()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some((at, Token::Op(")"))) => Err(syntax(*at, "unmatched )")),
+        Some((at, _)) => Err(syntax(*at, "expected an operator")),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn at(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |&(at, _)| at)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // lowest first: assignment, + -, * / %, unary minus, ^, then a single value
+    fn expr(&mut self) -> Result<Expr, Error> {
+        if let (Some(Token::Ident(name)), Some((_, Token::Op("=")))) =
+            (self.peek(), self.tokens.get(self.pos + 1))
+        {
+            let name = name.clone();
+            self.pos += 2;
+            return Ok(Expr::Assign(name, Box::new(self.expr()?)));
+        }
+        let mut left = self.term()?;
+        loop {
+            let op = if self.eat("+") {
+                Op::Add
+            } else if self.eat("-") {
+                Op::Sub
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, Error> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                Op::Mul
+            } else if self.eat("/") {
+                Op::Div
+            } else if self.eat("%") {
+                Op::Rem
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    // -2 ^ 2 is -(2 ^ 2), as on paper
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat("-") {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat("+") {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    // right associative, 2 ^ 3 ^ 2 is 2 ^ 9
+    fn power(&mut self) -> Result<Expr, Error> {
+        let base = self.postfix()?;
+        if self.eat("^") {
+            Ok(Expr::Binary(
+                Op::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, Error> {
+        let at = self.at();
+        let value = self.primary()?;
+        if !self.eat("++") {
+            return Ok(value);
+        }
+        match value {
+            Expr::Var(name) => Ok(Expr::Increment {
+                name,
+                prefix: false,
+            }),
+            _ => Err(syntax(at, "only a variable can be incremented")),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let at = self.at();
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Ident(name)) => Ok(Expr::Var(name)),
+            Some(Token::Op("(")) => {
+                let inner = self.expr()?;
+                if self.eat(")") {
+                    Ok(inner)
+                } else {
+                    Err(syntax(self.at(), "expected )"))
+                }
+            }
+            Some(Token::Op("++")) => match self.peek().cloned() {
+                Some(Token::Ident(name)) => {
+                    self.pos += 1;
+                    Ok(Expr::Increment { name, prefix: true })
+                }
+                _ => Err(syntax(self.at(), "only a variable can be incremented")),
+            },
+            Some(Token::Op(op)) => Err(syntax(at, format!("unexpected {}", op))),
+            None => Err(syntax(at, "expected a value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: i64) -> Box<Expr> {
+        Box::new(Expr::Number(Number::Int(n)))
+    }
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var(name.to_string()))
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        use Op::*;
+        assert_eq!(
+            parse("1 + 2 * 3").unwrap(),
+            Expr::Binary(Add, num(1), Box::new(Expr::Binary(Mul, num(2), num(3))))
+        );
+        assert_eq!(
+            parse("(1 + 2) * 3").unwrap(),
+            Expr::Binary(Mul, Box::new(Expr::Binary(Add, num(1), num(2))), num(3))
+        );
+        assert_eq!(
+            parse("8 - 4 - 2").unwrap(),
+            Expr::Binary(Sub, Box::new(Expr::Binary(Sub, num(8), num(4))), num(2))
+        );
+        assert_eq!(
+            parse("2 ^ 3 ^ 2").unwrap(),
+            Expr::Binary(Pow, num(2), Box::new(Expr::Binary(Pow, num(3), num(2))))
+        );
+        assert_eq!(
+            parse("-2 ^ 2").unwrap(),
+            Expr::Neg(Box::new(Expr::Binary(Pow, num(2), num(2))))
+        );
+        assert_eq!(
+            parse("x = y = 1.5").unwrap(),
+            Expr::Assign(
+                "x".to_string(),
+                Box::new(Expr::Assign(
+                    "y".to_string(),
+                    Box::new(Expr::Number(Number::Float(1.5)))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn increments() {
+        assert_eq!(
+            parse("++x").unwrap(),
+            Expr::Increment {
+                name: "x".to_string(),
+                prefix: true
+            }
+        );
+        assert_eq!(
+            parse("x++ * 2").unwrap(),
+            Expr::Binary(
+                Op::Mul,
+                Box::new(Expr::Increment {
+                    name: "x".to_string(),
+                    prefix: false
+                }),
+                num(2)
+            )
+        );
+        assert_eq!(
+            parse("x + +y").unwrap(),
+            Expr::Binary(Op::Add, var("x"), var("y"))
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_mistake() {
+        let at = |line| match parse(line) {
+            Err(Error::Syntax { at, .. }) => at,
+            other => panic!("{} parsed as {:?}", line, other),
+        };
+        assert_eq!(at("1 +"), 3);
+        assert_eq!(at("(1 + 2"), 6);
+        assert_eq!(at("1 + 2)"), 5);
+        assert_eq!(at("1 2"), 2);
+        assert_eq!(at("3 $ 4"), 2);
+        assert_eq!(at("1.2.3"), 0);
+        assert_eq!(at("99999999999999999999"), 0);
+        assert_eq!(at("(1)++"), 0);
+        assert_eq!(at("++1"), 2);
+        assert_eq!(at("*"), 0);
+    }
+}
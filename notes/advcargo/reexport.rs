@@ -9,6 +9,7 @@ pub use self::utils::mix;
 
 pub mod kinds {
     /// The primary colors according to the RYB color model.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum PrimaryColor {
         Red,
         Yellow,
@@ -16,20 +17,228 @@ pub mod kinds {
     }
 
     /// The secondary colors according to the RYB color model.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum SecondaryColor {
         Orange,
         Green,
         Purple,
     }
+
+    /// The tertiary colors according to the RYB color model, each
+    /// between a primary and a secondary color.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TertiaryColor {
+        RedOrange,
+        YellowOrange,
+        YellowGreen,
+        BlueGreen,
+        BluePurple,
+        RedPurple,
+    }
+
+    /// Any of the twelve named colors on the RYB color wheel.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Color {
+        Primary(PrimaryColor),
+        Secondary(SecondaryColor),
+        Tertiary(TertiaryColor),
+    }
 }
 
 pub mod utils {
     use crate::kinds::*;
 
-    /// Combines two primary colors in equal amounts to create
+    // the wheel in order, a step is a twelfth of the way round and the primaries are four steps apart
+    const WHEEL: [Color; 12] = [
+        Color::Primary(PrimaryColor::Red),
+        Color::Tertiary(TertiaryColor::RedOrange),
+        Color::Secondary(SecondaryColor::Orange),
+        Color::Tertiary(TertiaryColor::YellowOrange),
+        Color::Primary(PrimaryColor::Yellow),
+        Color::Tertiary(TertiaryColor::YellowGreen),
+        Color::Secondary(SecondaryColor::Green),
+        Color::Tertiary(TertiaryColor::BlueGreen),
+        Color::Primary(PrimaryColor::Blue),
+        Color::Tertiary(TertiaryColor::BluePurple),
+        Color::Secondary(SecondaryColor::Purple),
+        Color::Tertiary(TertiaryColor::RedPurple),
+    ];
+
+    /// Combines two different primary colors in equal amounts to create
     /// a secondary color.
-    pub fn mix(c1: PrimaryColor, c2: PrimaryColor) -> SecondaryColor {
-        // --snip--
-        unimplemented!();
+    ///
+    /// Returns `None` for a color mixed with itself, which stays that
+    /// primary color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use art::{mix, PrimaryColor, SecondaryColor};
+    ///
+    /// assert_eq!(mix(PrimaryColor::Blue, PrimaryColor::Yellow), Some(SecondaryColor::Green));
+    /// assert_eq!(mix(PrimaryColor::Red, PrimaryColor::Red), None);
+    /// ```
+    pub fn mix(c1: PrimaryColor, c2: PrimaryColor) -> Option<SecondaryColor> {
+        use PrimaryColor::*;
+        match (c1, c2) {
+            (Red, Yellow) | (Yellow, Red) => Some(SecondaryColor::Orange),
+            (Yellow, Blue) | (Blue, Yellow) => Some(SecondaryColor::Green),
+            (Red, Blue) | (Blue, Red) => Some(SecondaryColor::Purple),
+            (Red, Red) | (Yellow, Yellow) | (Blue, Blue) => None,
+        }
+    }
+
+    /// Combines a primary color with a secondary color next to it on the
+    /// wheel in equal amounts to create a tertiary color.
+    ///
+    /// Returns `None` for a primary and the secondary color opposite it, like
+    /// red and green, which mix to a brown that is not on the wheel.
+    pub fn mix_tertiary(c1: PrimaryColor, c2: SecondaryColor) -> Option<TertiaryColor> {
+        use PrimaryColor::*;
+        use SecondaryColor::*;
+        match (c1, c2) {
+            (Red, Orange) => Some(TertiaryColor::RedOrange),
+            (Yellow, Orange) => Some(TertiaryColor::YellowOrange),
+            (Yellow, Green) => Some(TertiaryColor::YellowGreen),
+            (Blue, Green) => Some(TertiaryColor::BlueGreen),
+            (Blue, Purple) => Some(TertiaryColor::BluePurple),
+            (Red, Purple) => Some(TertiaryColor::RedPurple),
+            (Red, Green) | (Yellow, Purple) | (Blue, Orange) => None,
+        }
+    }
+
+    /// Combines primary colors in the given parts and returns the named
+    /// color closest to the result.
+    ///
+    /// Equal parts of all three primaries are a neutral brown, so that much
+    /// of each is taken out first and only the rest decides the hue. Returns
+    /// `None` when nothing is left, so for no paint at all or a perfectly
+    /// neutral mix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use art::kinds::{Color, PrimaryColor, TertiaryColor};
+    /// use art::utils::mix_weighted;
+    ///
+    /// let mixed = mix_weighted(&[(PrimaryColor::Red, 3), (PrimaryColor::Yellow, 1)]);
+    /// assert_eq!(mixed, Some(Color::Tertiary(TertiaryColor::RedOrange)));
+    /// ```
+    pub fn mix_weighted(parts: &[(PrimaryColor, u32)]) -> Option<Color> {
+        // red, yellow, blue
+        let mut amounts = [0u64; 3];
+        for &(color, amount) in parts {
+            amounts[color as usize] += amount as u64;
+        }
+        let grey = amounts.iter().copied().min().unwrap_or(0);
+        for amount in &mut amounts {
+            *amount -= grey;
+        }
+
+        // at most two primaries are left, find where the hue sits on the arc between them
+        let (from, to) = match amounts {
+            [0, 0, 0] => return None,
+            [_, _, 0] => (0, 1),
+            [0, _, _] => (1, 2),
+            _ => (2, 0),
+        };
+        let total = amounts[from] + amounts[to];
+        // rounded to the nearest step, halves going towards `to`
+        let steps = (8 * amounts[to] + total) / (2 * total);
+        Some(WHEEL[(from * 4 + steps as usize) % 12])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::kinds::*;
+    use super::utils::*;
+    use PrimaryColor::*;
+
+    #[test]
+    fn primary_pairs() {
+        let pairs = [
+            (Red, Yellow, SecondaryColor::Orange),
+            (Yellow, Blue, SecondaryColor::Green),
+            (Blue, Red, SecondaryColor::Purple),
+        ];
+        for (c1, c2, secondary) in pairs {
+            assert_eq!(mix(c1, c2), Some(secondary));
+            assert_eq!(mix(c2, c1), Some(secondary));
+            assert_eq!(
+                mix_weighted(&[(c1, 1), (c2, 1)]),
+                Some(Color::Secondary(secondary))
+            );
+        }
+    }
+
+    #[test]
+    fn a_color_mixed_with_itself_stays_the_same() {
+        for color in [Red, Yellow, Blue] {
+            assert_eq!(mix(color, color), None);
+            assert_eq!(
+                mix_weighted(&[(color, 1), (color, 1)]),
+                Some(Color::Primary(color))
+            );
+        }
+    }
+
+    #[test]
+    fn tertiary_colors_sit_between_their_parents() {
+        use SecondaryColor::*;
+        for (primary, secondary) in [
+            (Red, Orange),
+            (Yellow, Orange),
+            (Yellow, Green),
+            (Blue, Green),
+            (Blue, Purple),
+            (Red, Purple),
+        ] {
+            let tertiary = mix_tertiary(primary, secondary).unwrap();
+            // a secondary is one part of each of its primaries
+            let other = [Red, Yellow, Blue]
+                .into_iter()
+                .find(|&p| mix(primary, p) == Some(secondary))
+                .unwrap();
+            assert_eq!(
+                mix_weighted(&[(primary, 3), (other, 1)]),
+                Some(Color::Tertiary(tertiary))
+            );
+        }
+        assert_eq!(mix_tertiary(Red, Green), None);
+        assert_eq!(mix_tertiary(Yellow, Purple), None);
+        assert_eq!(mix_tertiary(Blue, Orange), None);
+    }
+
+    #[test]
+    fn weighted_mixes() {
+        let mixed = |parts: &[(PrimaryColor, u32)]| mix_weighted(parts);
+        assert_eq!(mixed(&[]), None);
+        assert_eq!(mixed(&[(Red, 0)]), None);
+        assert_eq!(mixed(&[(Red, 2), (Yellow, 2), (Blue, 2)]), None);
+        assert_eq!(mixed(&[(Blue, 5)]), Some(Color::Primary(Blue)));
+        // repeated colors add up
+        assert_eq!(
+            mixed(&[(Blue, 1), (Red, 1), (Blue, 2)]),
+            Some(Color::Tertiary(TertiaryColor::BluePurple))
+        );
+        // a little red in a lot of yellow is still yellow
+        assert_eq!(
+            mixed(&[(Red, 1), (Yellow, 20)]),
+            Some(Color::Primary(Yellow))
+        );
+        // the grey part doesn't change the hue
+        assert_eq!(
+            mixed(&[(Red, 10), (Yellow, 7), (Blue, 7)]),
+            Some(Color::Primary(Red))
+        );
+        assert_eq!(
+            mixed(&[(Yellow, 9), (Blue, 5), (Red, 4)]),
+            Some(Color::Tertiary(TertiaryColor::YellowGreen))
+        );
+        assert_eq!(
+            mixed(&[(Blue, u32::MAX), (Red, u32::MAX)]),
+            Some(Color::Secondary(SecondaryColor::Purple))
+        );
     }
 }
@@ -0,0 +1,152 @@
+// wins are appended to a plain text file, one tab separated line per record
+// every append takes an exclusive lock on the file and writes the line in one go,
+// so two games finishing at the same moment can't interleave their lines
+// lines that don't parse are skipped, a half written line never hides the rest of the board
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Default file name, relative to where the game is run.
+pub const DEFAULT_FILE: &str = "guessing-game-leaderboard.tsv";
+
+/// How many records a board shows.
+pub const TOP: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    /// `Difficulty::key` of the game.
+    pub difficulty: String,
+}
+
+impl Record {
+    // tabs and newlines would break the line format, a name can live without them
+    fn to_line(&self) -> String {
+        let clean = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+        format!(
+            "{}\t{}\t{}\t{}\n",
+            clean(&self.difficulty),
+            clean(&self.name),
+            self.attempts,
+            self.elapsed.as_millis()
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Record> {
+        let mut fields = line.split('\t');
+        let record = Record {
+            difficulty: fields.next()?.to_string(),
+            name: fields.next()?.to_string(),
+            attempts: fields.next()?.parse().ok()?,
+            elapsed: Duration::from_millis(fields.next()?.parse().ok()?),
+        };
+        match fields.next() {
+            None => Some(record),
+            Some(_) => None,
+        }
+    }
+}
+
+pub struct Leaderboard {
+    path: PathBuf,
+}
+
+impl Leaderboard {
+    /// The file is created by the first record.
+    pub fn new(path: impl AsRef<Path>) -> Leaderboard {
+        Leaderboard {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, record: &Record) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.lock()?;
+        // one write for the whole line, the lock is released when the file closes
+        file.write_all(record.to_line().as_bytes())?;
+        file.flush()
+    }
+
+    /// Every readable record in the file, oldest first.
+    pub fn records(&self) -> io::Result<Vec<Record>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        file.lock_shared()?;
+        let mut records = Vec::new();
+        for line in BufReader::new(&file).lines() {
+            if let Some(record) = Record::from_line(&line?) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// The best `TOP` records for `difficulty`, fewest attempts first, then fastest.
+    ///
+    /// Ties keep the older record ahead.
+    pub fn top(&self, difficulty: &str) -> io::Result<Board> {
+        let mut records: Vec<Record> = self
+            .records()?
+            .into_iter()
+            .filter(|r| r.difficulty == difficulty)
+            .collect();
+        records.sort_by_key(|r| (r.attempts, r.elapsed));
+        records.truncate(TOP);
+        Ok(Board {
+            difficulty: difficulty.to_string(),
+            records,
+        })
+    }
+
+    /// One board per difficulty that has any records, in name order.
+    pub fn boards(&self) -> io::Result<Vec<Board>> {
+        let mut difficulties: Vec<String> =
+            self.records()?.into_iter().map(|r| r.difficulty).collect();
+        difficulties.sort();
+        difficulties.dedup();
+        difficulties.iter().map(|d| self.top(d)).collect()
+    }
+}
+
+/// The top records for one difficulty, ready to print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub difficulty: String,
+    pub records: Vec<Record>,
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Top {} ({}):", TOP, self.difficulty)?;
+        if self.records.is_empty() {
+            return writeln!(f, "  no wins yet");
+        }
+        for (place, record) in self.records.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}. {:<16} {:>3} attempts {:>7.1}s",
+                place + 1,
+                record.name,
+                record.attempts,
+                record.elapsed.as_secs_f64()
+            )?;
+        }
+        Ok(())
+    }
+}
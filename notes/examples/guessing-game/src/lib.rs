@@ -0,0 +1,323 @@
+// the game itself, kept apart from the terminal so tests can play it with scripted input
+// everything random comes from the rng passed in, a seeded rng replays the same game
+pub mod leaderboard;
+pub mod net;
+pub mod solver;
+
+use rand::Rng;
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+// the range lives in one value now, the guess checks against it and the secret is drawn from it
+// so the two can never disagree the way two hard-coded 1..=100 could
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuessRange {
+    min: i32,
+    max: i32,
+}
+
+impl GuessRange {
+    /// `None` unless `min <= max`.
+    pub fn new(min: i32, max: i32) -> Option<GuessRange> {
+        if min <= max {
+            Some(GuessRange { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn min(&self) -> i32 {
+        self.min
+    }
+
+    pub fn max(&self) -> i32 {
+        self.max
+    }
+
+    pub fn contains(&self, value: i32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Picks the secret, it is a `Guess` too so it is in range by construction.
+    pub fn secret(&self, rng: &mut impl Rng) -> Guess {
+        Guess {
+            value: rng.gen_range(self.min..=self.max),
+            range: *self,
+        }
+    }
+}
+
+impl fmt::Display for GuessRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} and {}", self.min, self.max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    /// The line wasn't a whole number, holds what was typed.
+    NotANumber(String),
+    OutOfRange {
+        value: i64,
+        range: GuessRange,
+    },
+    /// Input ended, usually Ctrl-D.
+    Eof,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::NotANumber(input) if input.is_empty() => {
+                write!(f, "Please type a number!")
+            }
+            GuessError::NotANumber(input) => {
+                write!(f, "\"{}\" is not a number, please type a number!", input)
+            }
+            GuessError::OutOfRange { value, range } => {
+                write!(
+                    f,
+                    "{} is out of range, the number is between {}.",
+                    value, range
+                )
+            }
+            GuessError::Eof => write!(f, "No more input."),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guess {
+    value: i32,
+    range: GuessRange,
+}
+
+impl Guess {
+    pub fn new(value: i32, range: GuessRange) -> Guess {
+        if !range.contains(value) {
+            panic!("Guess value must be between {}, got {}.", range, value);
+        }
+
+        Guess { value, range }
+    }
+
+    /// Checks a line the player typed, surrounding whitespace is ignored.
+    pub fn try_new(input: &str, range: GuessRange) -> Result<Guess, GuessError> {
+        let input = input.trim();
+        // parsed wider than i32 so 99999999999 is out of range rather than not a number
+        let value: i64 = input
+            .parse()
+            .map_err(|_| GuessError::NotANumber(input.to_string()))?;
+        match i32::try_from(value) {
+            Ok(value) => Guess::in_range(value, range),
+            Err(_) => Err(GuessError::OutOfRange { value, range }),
+        }
+    }
+
+    /// Checks a number that is already an integer, like one read off the wire.
+    pub fn in_range(value: i32, range: GuessRange) -> Result<Guess, GuessError> {
+        if !range.contains(value) {
+            return Err(GuessError::OutOfRange {
+                value: value.into(),
+                range,
+            });
+        }
+        Ok(Guess { value, range })
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn range(&self) -> GuessRange {
+        self.range
+    }
+
+    /// The feedback a player gets, `Less` means the guess was too small.
+    pub fn compare(&self, secret: &Guess) -> Ordering {
+        self.value.cmp(&secret.value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Custom { range: GuessRange, attempts: u32 },
+}
+
+impl Difficulty {
+    pub fn range(&self) -> GuessRange {
+        match self {
+            Difficulty::Easy => GuessRange { min: 1, max: 10 },
+            Difficulty::Normal => GuessRange { min: 1, max: 100 },
+            Difficulty::Hard => GuessRange { min: 1, max: 1000 },
+            Difficulty::Custom { range, .. } => *range,
+        }
+    }
+
+    /// How many guesses the player gets before losing.
+    pub fn attempts(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 7,
+            Difficulty::Hard => 10,
+            Difficulty::Custom { attempts, .. } => *attempts,
+        }
+    }
+
+    /// Names the leaderboard a game belongs on, custom games only compete with the same settings.
+    pub fn key(&self) -> String {
+        match self {
+            Difficulty::Custom { range, attempts } => {
+                format!("custom {}..={} x{}", range.min, range.max, attempts)
+            }
+            preset => preset.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Normal => write!(f, "normal"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Custom { .. } => write!(f, "custom"),
+        }
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Guessed right on attempt number `attempts`.
+    Won {
+        attempts: u32,
+    },
+    Lost,
+    /// The input ended before the game did.
+    Quit,
+}
+
+/// Reads one line from `input` and checks it, `GuessError::Eof` once the input has ended.
+pub(crate) fn read_guess(
+    input: &mut impl BufRead,
+    range: GuessRange,
+) -> io::Result<Result<Guess, GuessError>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(Err(GuessError::Eof));
+    }
+    Ok(Guess::try_new(&line, range))
+}
+
+/// Plays one game reading guesses from `input` and writing everything the player sees to `output`.
+///
+/// The secret is the first thing drawn from `rng`.
+pub fn play<R: BufRead, W: Write, G: Rng>(
+    difficulty: Difficulty,
+    mut input: R,
+    mut output: W,
+    rng: &mut G,
+) -> io::Result<Outcome> {
+    let range = difficulty.range();
+
+    writeln!(output, "Guess the number!")?;
+    writeln!(
+        output,
+        "Difficulty: {}, between {}, {} attempts.",
+        difficulty,
+        range,
+        difficulty.attempts()
+    )?;
+
+    let secret_number = range.secret(rng);
+
+    // println!("The secret number generated is {}", secret_number.value());
+
+    let mut attempts = 0;
+    while attempts < difficulty.attempts() {
+        writeln!(output, "Please enter your guess.")?;
+        output.flush()?;
+
+        let guess = read_guess(&mut input, range)?;
+
+        /*
+        let guess: u32 = guess.trim().parse().expect("Please type a number!");
+        */
+
+        /*
+        let guess: u32 = match guess.trim().parse() {
+            Ok(num) => num,
+            Err(_) => continue,
+        };
+        */
+
+        /*
+        // not ideal because we run a big logic on every iteration instead add the variable check to a type
+        let guess: i32 = match guess.trim().parse() {
+            Ok(num) => num,
+            Err(_) => continue,
+        };
+        if guess < 1 || guess > 100 {
+            println!("The secret number will be between 1 and 100.");
+            continue;
+        }
+        */
+
+        /*
+        let guess_i32: i32 = guess.trim().parse().expect("Please type a number!");
+        let guess_i32: i32 = Guess::new(guess_i32, range).value(); // this is type checked value
+        */
+
+        // bad input gets a specific message and doesn't use up an attempt
+        let guess = match guess {
+            Ok(checked) => checked,
+            Err(GuessError::Eof) => {
+                writeln!(output)?;
+                writeln!(output, "Bye! The number was {}.", secret_number.value())?;
+                return Ok(Outcome::Quit);
+            }
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+        attempts += 1;
+
+        writeln!(output, "Your guess is {}", guess.value())?;
+
+        /*
+        match guess.cmp(&secret_number) {
+            Ordering::Less => println!("Too small!"),
+            Ordering::Greater => println!("Too big!"),
+            Ordering::Equal => {
+                println!("You win!");
+                break;
+            }
+        }
+        */
+        match guess.compare(&secret_number) {
+            Ordering::Less => writeln!(output, "Too small!")?,
+            Ordering::Greater => writeln!(output, "Too big!")?,
+            Ordering::Equal => {
+                writeln!(output, "You win!")?;
+                return Ok(Outcome::Won { attempts });
+            }
+        }
+    }
+
+    writeln!(
+        output,
+        "Out of guesses! The number was {}.",
+        secret_number.value()
+    )?;
+    Ok(Outcome::Lost)
+}
@@ -1,85 +1,273 @@
 #![allow(unused)]
-use rand::Rng;
-use std::{cmp::Ordering, io};
-fn main() {
-    pub struct Guess {
-        value: i32,
-    }
+use guessing_game::leaderboard::{self, Leaderboard, Record};
+use guessing_game::net;
+use guessing_game::solver::{self, Strategy};
+use guessing_game::{Difficulty, GuessRange, Outcome};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    env, io,
+    net::TcpListener,
+    process,
+    time::{Duration, Instant},
+};
 
-    impl Guess {
-        pub fn new(value: i32) -> Guess {
-            if value < 1 || value > 100 {
-                panic!("Guess value must be between 1 and 100, got {}.", value);
+struct Options {
+    difficulty: Difficulty,
+    /// Replays the same secret, for demos and tests.
+    seed: Option<u64>,
+    /// Who a win is recorded for.
+    name: String,
+    leaderboard_file: String,
+    /// Print the leaderboard instead of playing.
+    show_leaderboard: bool,
+    /// Let the computer play these strategies instead of a person.
+    auto: Vec<Strategy>,
+    /// Simulated games per strategy.
+    games: u32,
+    /// Pick the secret for a guesser joining on this address.
+    host: Option<String>,
+    /// Guess a secret picked by the host at this address.
+    join: Option<String>,
+    /// How long either side waits for the other in a two player game.
+    timeout: Duration,
+}
+
+// --difficulty easy|normal|hard, or --range 1..=500 with an optional --attempts for a custom game
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut difficulty = Difficulty::Normal;
+    let mut range = None;
+    let mut attempts = None;
+    let mut seed = None;
+    let mut name = env::var("USER").unwrap_or_else(|_| "player".to_string());
+    let mut leaderboard_file = leaderboard::DEFAULT_FILE.to_string();
+    let mut show_leaderboard = false;
+    let mut auto = Vec::new();
+    let mut games = 1000;
+    let mut host = None;
+    let mut join = None;
+    let mut timeout = Duration::from_secs(60);
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--difficulty" => {
+                difficulty = match value()?.as_str() {
+                    "easy" => Difficulty::Easy,
+                    "normal" => Difficulty::Normal,
+                    "hard" => Difficulty::Hard,
+                    other => return Err(format!("unknown difficulty {}", other)),
+                }
             }
-    
-            Guess { value }
-        }
-    
-        pub fn value(&self) -> i32 {
-            self.value
+            "--range" => {
+                let text = value()?;
+                let (min, max) = text
+                    .split_once("..=")
+                    .ok_or(format!("range should look like 1..=100, got {}", text))?;
+                let min = min
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("bad range {}", text))?;
+                let max = max
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("bad range {}", text))?;
+                range = Some(GuessRange::new(min, max).ok_or(format!("empty range {}", text))?);
+            }
+            "--attempts" => {
+                let text = value()?;
+                attempts = Some(text.parse().ok().filter(|&n| n > 0).ok_or(format!(
+                    "attempts should be a positive number, got {}",
+                    text
+                ))?);
+            }
+            "--seed" => {
+                let text = value()?;
+                seed = Some(
+                    text.parse()
+                        .map_err(|_| format!("seed should be a number, got {}", text))?,
+                );
+            }
+            "--name" => name = value()?,
+            "--leaderboard-file" => leaderboard_file = value()?,
+            "--leaderboard" => show_leaderboard = true,
+            "--auto" => {
+                auto = match value()?.as_str() {
+                    "all" => Strategy::ALL.to_vec(),
+                    strategy => vec![strategy.parse()?],
+                }
+            }
+            "--games" => {
+                let text = value()?;
+                games = text
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("games should be a positive number, got {}", text))?;
+            }
+            "--host" => host = Some(value()?),
+            "--join" => join = Some(value()?),
+            "--timeout" => {
+                let text = value()?;
+                timeout = text
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .map(Duration::from_secs)
+                    .ok_or(format!(
+                        "timeout should be a number of seconds, got {}",
+                        text
+                    ))?;
+            }
+            other => return Err(format!("unknown option {}", other)),
         }
     }
+    if range.is_some() || attempts.is_some() {
+        difficulty = Difficulty::Custom {
+            range: range.unwrap_or(difficulty.range()),
+            attempts: attempts.unwrap_or(difficulty.attempts()),
+        };
+    }
+    Ok(Options {
+        difficulty,
+        seed,
+        name,
+        leaderboard_file,
+        show_leaderboard,
+        auto,
+        games,
+        host,
+        join,
+        timeout,
+    })
+}
 
-    println!("Guess the number!");
-
-    let secret_number:i32 = rand::thread_rng().gen_range(1..=100);
-
-    // println!("The secret number generated is {secret_number}");
-
-    loop {
-        println!("Please enter your guess.");
-
-        let mut guess = String::new();
-
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line.");
+fn main() {
+    let options = match parse_args(env::args()) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
 
-        /*
-        let guess: u32 = guess.trim().parse().expect("Please type a number!");
-        */
+    let board = Leaderboard::new(&options.leaderboard_file);
+    if options.show_leaderboard {
+        match board.boards() {
+            Ok(boards) if boards.is_empty() => println!("No wins recorded yet."),
+            Ok(boards) => {
+                for (i, board) in boards.iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    print!("{}", board);
+                }
+            }
+            Err(e) => {
+                eprintln!("could not read {}: {}", board.path().display(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
-        /*
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
+    if let Some(addr) = &options.host {
+        let listener = match TcpListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("could not listen on {}: {}", addr, e);
+                process::exit(1);
+            }
         };
-        */
-
-        /*
-        // not ideal because we run a big logic on every iteration instead add the variable check to a type
-        let guess: i32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
+        // the real address, so --host 127.0.0.1:0 tells the guest where to go
+        match listener.local_addr() {
+            Ok(addr) => println!("Waiting for a guesser on {}", addr),
+            Err(e) => eprintln!("{}", e),
+        }
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
         };
-        if guess < 1 || guess > 100 {
-            println!("The secret number will be between 1 and 100.");
-            continue;
+        let stdout = io::stdout();
+        if let Err(e) = net::host(
+            &listener,
+            options.difficulty,
+            &mut rng,
+            options.timeout,
+            stdout.lock(),
+        ) {
+            eprintln!("{}", e);
+            process::exit(1);
         }
-        */
-
-        let guess_i32: i32 = guess.trim().parse().expect("Please type a number!");
-        let guess_i32: i32 = Guess::new(guess_i32).value(); // this is type checked value
-
-        println!("Your guess is {guess}");
+        return;
+    }
+    if let Some(addr) = &options.join {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        if let Err(e) = net::join(addr, stdin.lock(), stdout.lock(), options.timeout) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
 
-        /*
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
+    if !options.auto.is_empty() {
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        for (i, &strategy) in options.auto.iter().enumerate() {
+            if i > 0 {
+                println!();
             }
+            print!(
+                "{}",
+                solver::simulate(strategy, options.difficulty, options.games, &mut rng)
+            );
         }
-        */
-        match guess_i32.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
-            }
+        return;
+    }
+
+    let started = Instant::now();
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let played = match options.seed {
+        Some(seed) => guessing_game::play(
+            options.difficulty,
+            stdin.lock(),
+            stdout.lock(),
+            &mut StdRng::seed_from_u64(seed),
+        ),
+        None => guessing_game::play(
+            options.difficulty,
+            stdin.lock(),
+            stdout.lock(),
+            &mut rand::thread_rng(),
+        ),
+    };
+    let attempts = match played {
+        Ok(Outcome::Won { attempts }) => attempts,
+        Ok(_) => return,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    // a leaderboard that can't be written shouldn't spoil the win
+    let record = Record {
+        name: options.name,
+        attempts,
+        elapsed: started.elapsed(),
+        difficulty: options.difficulty.key(),
+    };
+    match board
+        .record(&record)
+        .and_then(|_| board.top(&record.difficulty))
+    {
+        Ok(top) => {
+            println!();
+            print!("{}", top);
         }
+        Err(e) => eprintln!("could not update {}: {}", board.path().display(), e),
     }
 }
@@ -0,0 +1,307 @@
+// two player mode: the host picks the secret, the guest guesses over tcp
+// every message is a frame: one length byte, then a tag byte and a fixed size big endian payload
+// the host is the referee, it checks every guess itself and counts the attempts
+// both sides put a timeout on the socket so a vanished player can't hang the other one forever
+use rand::Rng;
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{self, BufRead, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::{read_guess, Difficulty, Guess, GuessError, GuessRange, Outcome};
+
+/// One message on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    /// Host to guest when the game starts.
+    Hello { min: i32, max: i32, attempts: u32 },
+    /// Guest to host.
+    Guess(i32),
+    /// The host's answer, `Less` means the guess was too small.
+    Answer(Ordering),
+    /// The guess was outside the range, it didn't count.
+    OutOfRange,
+    /// No attempts left, here is what the secret was.
+    GameOver { secret: i32 },
+    /// Either side is leaving.
+    Bye,
+}
+
+const HELLO: u8 = b'H';
+const GUESS: u8 = b'G';
+const ANSWER: u8 = b'A';
+const OUT_OF_RANGE: u8 = b'R';
+const GAME_OVER: u8 = b'O';
+const BYE: u8 = b'B';
+
+impl Frame {
+    pub fn write_to(&self, out: &mut impl Write) -> Result<(), NetError> {
+        let mut body = Vec::with_capacity(13);
+        match *self {
+            Frame::Hello { min, max, attempts } => {
+                body.push(HELLO);
+                body.extend(min.to_be_bytes());
+                body.extend(max.to_be_bytes());
+                body.extend(attempts.to_be_bytes());
+            }
+            Frame::Guess(value) => {
+                body.push(GUESS);
+                body.extend(value.to_be_bytes());
+            }
+            Frame::Answer(ordering) => {
+                body.push(ANSWER);
+                body.push(ordering as i8 as u8);
+            }
+            Frame::OutOfRange => body.push(OUT_OF_RANGE),
+            Frame::GameOver { secret } => {
+                body.push(GAME_OVER);
+                body.extend(secret.to_be_bytes());
+            }
+            Frame::Bye => body.push(BYE),
+        }
+        out.write_all(&[body.len() as u8])?;
+        out.write_all(&body)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(input: &mut impl Read) -> Result<Frame, NetError> {
+        let mut len = [0; 1];
+        input.read_exact(&mut len)?;
+        let mut body = vec![0; len[0] as usize];
+        input.read_exact(&mut body)?;
+        let bad = || NetError::Protocol(format!("bad frame {:?}", body));
+        let (&tag, payload) = body.split_first().ok_or_else(bad)?;
+        let int = |at: usize| -> Result<[u8; 4], NetError> {
+            payload
+                .get(at..at + 4)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(bad)
+        };
+        let frame = match (tag, payload.len()) {
+            (HELLO, 12) => Frame::Hello {
+                min: i32::from_be_bytes(int(0)?),
+                max: i32::from_be_bytes(int(4)?),
+                attempts: u32::from_be_bytes(int(8)?),
+            },
+            (GUESS, 4) => Frame::Guess(i32::from_be_bytes(int(0)?)),
+            (ANSWER, 1) => Frame::Answer(match payload[0] as i8 {
+                -1 => Ordering::Less,
+                0 => Ordering::Equal,
+                1 => Ordering::Greater,
+                _ => return Err(bad()),
+            }),
+            (OUT_OF_RANGE, 0) => Frame::OutOfRange,
+            (GAME_OVER, 4) => Frame::GameOver {
+                secret: i32::from_be_bytes(int(0)?),
+            },
+            (BYE, 0) => Frame::Bye,
+            _ => return Err(bad()),
+        };
+        Ok(frame)
+    }
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    /// Nothing arrived within the timeout.
+    Timeout,
+    /// The other side closed the connection or said bye.
+    Disconnected,
+    /// The other side sent something that isn't a frame we expect.
+    Protocol(String),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetError::Io(e) => write!(f, "network error: {}", e),
+            NetError::Timeout => write!(f, "the other player took too long"),
+            NetError::Disconnected => write!(f, "the other player left"),
+            NetError::Protocol(e) => write!(f, "protocol error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> NetError {
+        match error.kind() {
+            // a read timeout shows up as either of these depending on the platform
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => NetError::Timeout,
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe => NetError::Disconnected,
+            _ => NetError::Io(error),
+        }
+    }
+}
+
+/// Waits for one guest on `listener` and referees a game against it.
+///
+/// `log` gets a line per guess for the host to watch. The outcome is the guest's.
+pub fn host(
+    listener: &TcpListener,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+    timeout: Duration,
+    mut log: impl Write,
+) -> Result<Outcome, NetError> {
+    let (mut stream, peer) = listener.accept()?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+    writeln!(log, "{} joined.", peer)?;
+
+    let range = difficulty.range();
+    let secret = range.secret(rng);
+    Frame::Hello {
+        min: range.min(),
+        max: range.max(),
+        attempts: difficulty.attempts(),
+    }
+    .write_to(&mut stream)?;
+
+    let mut attempts = 0;
+    while attempts < difficulty.attempts() {
+        let value = match Frame::read_from(&mut stream) {
+            Ok(Frame::Guess(value)) => value,
+            Ok(Frame::Bye) => {
+                writeln!(log, "They gave up, the number was {}.", secret.value())?;
+                return Ok(Outcome::Quit);
+            }
+            Ok(other) => {
+                return Err(NetError::Protocol(format!(
+                    "expected a guess, got {:?}",
+                    other
+                )))
+            }
+            Err(e) => {
+                // tell a slow guest why the game ended, it may not be listening any more
+                if let NetError::Timeout = e {
+                    let _ = Frame::Bye.write_to(&mut stream);
+                }
+                return Err(e);
+            }
+        };
+        // the guest checks its own input too, but the host doesn't take that on trust
+        let Ok(guess) = Guess::in_range(value, range) else {
+            Frame::OutOfRange.write_to(&mut stream)?;
+            continue;
+        };
+        attempts += 1;
+        let answer = guess.compare(&secret);
+        Frame::Answer(answer).write_to(&mut stream)?;
+        writeln!(log, "Guess {}: {} ({:?})", attempts, value, answer)?;
+        if answer == Ordering::Equal {
+            writeln!(log, "They won in {} attempts.", attempts)?;
+            return Ok(Outcome::Won { attempts });
+        }
+    }
+    Frame::GameOver {
+        secret: secret.value(),
+    }
+    .write_to(&mut stream)?;
+    writeln!(
+        log,
+        "They ran out of guesses, the number was {}.",
+        secret.value()
+    )?;
+    Ok(Outcome::Lost)
+}
+
+/// Joins the game at `addr` and guesses with lines from `input`, like `play` but the host has the secret.
+pub fn join(
+    addr: impl ToSocketAddrs,
+    mut input: impl BufRead,
+    mut output: impl Write,
+    timeout: Duration,
+) -> Result<Outcome, NetError> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+
+    let (range, limit) = match Frame::read_from(&mut stream)? {
+        Frame::Hello { min, max, attempts } => (
+            GuessRange::new(min, max)
+                .ok_or_else(|| NetError::Protocol(format!("empty range {}..={}", min, max)))?,
+            attempts,
+        ),
+        other => {
+            return Err(NetError::Protocol(format!(
+                "expected hello, got {:?}",
+                other
+            )))
+        }
+    };
+    writeln!(output, "Guess the number!")?;
+    writeln!(
+        output,
+        "The host picked a number between {}, {} attempts.",
+        range, limit
+    )?;
+
+    let mut attempts = 0;
+    loop {
+        writeln!(output, "Please enter your guess.")?;
+        output.flush()?;
+        let guess = match read_guess(&mut input, range)? {
+            Ok(guess) => guess,
+            Err(GuessError::Eof) => {
+                // best effort, the host may already be gone
+                let _ = Frame::Bye.write_to(&mut stream);
+                writeln!(output)?;
+                writeln!(output, "Bye!")?;
+                return Ok(Outcome::Quit);
+            }
+            Err(e) => {
+                writeln!(output, "{}", e)?;
+                continue;
+            }
+        };
+        Frame::Guess(guess.value()).write_to(&mut stream)?;
+        match Frame::read_from(&mut stream)? {
+            Frame::Answer(answer) => {
+                attempts += 1;
+                writeln!(output, "Your guess is {}", guess.value())?;
+                match answer {
+                    Ordering::Less => writeln!(output, "Too small!")?,
+                    Ordering::Greater => writeln!(output, "Too big!")?,
+                    Ordering::Equal => {
+                        writeln!(output, "You win!")?;
+                        return Ok(Outcome::Won { attempts });
+                    }
+                }
+            }
+            Frame::OutOfRange => {
+                writeln!(output, "The host says {} is out of range.", guess.value())?
+            }
+            Frame::Bye => return Err(NetError::Disconnected),
+            other => {
+                return Err(NetError::Protocol(format!(
+                    "expected an answer, got {:?}",
+                    other
+                )))
+            }
+        }
+        if attempts == limit {
+            return match Frame::read_from(&mut stream)? {
+                Frame::GameOver { secret } => {
+                    writeln!(output, "Out of guesses! The number was {}.", secret)?;
+                    Ok(Outcome::Lost)
+                }
+                other => Err(NetError::Protocol(format!(
+                    "expected game over, got {:?}",
+                    other
+                ))),
+            };
+        }
+    }
+}
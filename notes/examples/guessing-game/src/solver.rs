@@ -0,0 +1,196 @@
+// the computer plays against the same feedback a person gets, `Guess::compare`
+// each strategy only learns from the ordering of its guesses, never the secret itself
+// simulate plays many games and counts how many attempts each one took
+use rand::Rng;
+use std::{cmp::Ordering, collections::BTreeMap, fmt, str::FromStr};
+
+use crate::{Difficulty, Guess, GuessRange};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Halves what is left every time, never needs more than log2 of the range.
+    Binary,
+    /// Any number that is still possible.
+    Random,
+    /// Counts up from the bottom, the naive baseline.
+    Linear,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::Binary, Strategy::Random, Strategy::Linear];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Strategy::Binary => write!(f, "binary"),
+            Strategy::Random => write!(f, "random"),
+            Strategy::Linear => write!(f, "linear"),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strategy, String> {
+        match s {
+            "binary" => Ok(Strategy::Binary),
+            "random" => Ok(Strategy::Random),
+            "linear" => Ok(Strategy::Linear),
+            other => Err(format!("unknown strategy {}", other)),
+        }
+    }
+}
+
+/// A computer player in the middle of a game, it keeps the bounds the feedback has given it.
+#[derive(Debug, Clone)]
+pub struct Player {
+    strategy: Strategy,
+    range: GuessRange,
+    low: i32,
+    high: i32,
+}
+
+impl Player {
+    pub fn new(strategy: Strategy, range: GuessRange) -> Player {
+        Player {
+            strategy,
+            range,
+            low: range.min(),
+            high: range.max(),
+        }
+    }
+
+    pub fn next_guess(&mut self, rng: &mut impl Rng) -> Guess {
+        let value = match self.strategy {
+            // i64 so the midpoint of a range near i32::MAX doesn't overflow
+            Strategy::Binary => ((self.low as i64 + self.high as i64) / 2) as i32,
+            Strategy::Random => rng.gen_range(self.low..=self.high),
+            Strategy::Linear => self.low,
+        };
+        Guess::new(value, self.range)
+    }
+
+    pub fn feedback(&mut self, guess: Guess, result: Ordering) {
+        match result {
+            Ordering::Less => self.low = guess.value() + 1,
+            // counting up never needs to know it went too high
+            Ordering::Greater if self.strategy != Strategy::Linear => self.high = guess.value() - 1,
+            _ => {}
+        }
+    }
+}
+
+/// Plays until the secret is found, returns the number of attempts.
+pub fn solve(strategy: Strategy, secret: &Guess, rng: &mut impl Rng) -> u32 {
+    let mut player = Player::new(strategy, secret.range());
+    let mut attempts = 0;
+    loop {
+        let guess = player.next_guess(rng);
+        attempts += 1;
+        match guess.compare(secret) {
+            Ordering::Equal => return attempts,
+            result => player.feedback(guess, result),
+        }
+    }
+}
+
+/// How many attempts `games` simulated games took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    pub strategy: Strategy,
+    pub difficulty: Difficulty,
+    /// Games by the number of attempts they took.
+    pub attempts: BTreeMap<u32, u32>,
+}
+
+impl Distribution {
+    pub fn games(&self) -> u32 {
+        self.attempts.values().sum()
+    }
+
+    /// Games finished within the difficulty's attempt limit.
+    pub fn wins(&self) -> u32 {
+        self.attempts
+            .range(..=self.difficulty.attempts())
+            .map(|(_, games)| games)
+            .sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let total: u64 = self
+            .attempts
+            .iter()
+            .map(|(&attempts, &games)| attempts as u64 * games as u64)
+            .sum();
+        total as f64 / self.games().max(1) as f64
+    }
+
+    pub fn worst(&self) -> Option<u32> {
+        self.attempts.keys().next_back().copied()
+    }
+}
+
+// widest histogram bar, in characters
+const BAR: u32 = 40;
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} over {} games on {} (between {}, {} attempts)",
+            self.strategy,
+            self.games(),
+            self.difficulty,
+            self.difficulty.range(),
+            self.difficulty.attempts()
+        )?;
+        writeln!(
+            f,
+            "  mean {:.2}, worst {}, won {}/{}",
+            self.mean(),
+            self.worst().unwrap_or(0),
+            self.wins(),
+            self.games()
+        )?;
+        // long tails are bucketed so linear on a big range still fits on screen
+        let worst = self.worst().unwrap_or(0);
+        let bucket = worst.div_ceil(20).max(1);
+        let mut buckets: BTreeMap<u32, u32> = BTreeMap::new();
+        for (&attempts, &games) in &self.attempts {
+            *buckets.entry((attempts - 1) / bucket).or_default() += games;
+        }
+        let tallest = buckets.values().copied().max().unwrap_or(0).max(1);
+        for (&index, &games) in &buckets {
+            let label = if bucket == 1 {
+                format!("{}", index + 1)
+            } else {
+                format!("{}-{}", index * bucket + 1, (index + 1) * bucket)
+            };
+            // in u64, a bucket of a few hundred million games times the bar width doesn't fit a u32
+            let bar = (games as u64 * BAR as u64).div_ceil(tallest as u64) as usize;
+            writeln!(f, "  {:>9} | {:>6} {}", label, games, "#".repeat(bar))?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `games` games with secrets drawn from `rng`, the strategy uses the same rng.
+pub fn simulate(
+    strategy: Strategy,
+    difficulty: Difficulty,
+    games: u32,
+    rng: &mut impl Rng,
+) -> Distribution {
+    let mut attempts = BTreeMap::new();
+    for _ in 0..games {
+        let secret = difficulty.range().secret(rng);
+        *attempts.entry(solve(strategy, &secret, rng)).or_default() += 1;
+    }
+    Distribution {
+        strategy,
+        difficulty,
+        attempts,
+    }
+}
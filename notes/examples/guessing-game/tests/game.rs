@@ -0,0 +1,169 @@
+// whole games played through the library with scripted input and a seeded rng
+// the secret is drawn the same way the game draws it, so the transcripts are exact
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+
+use guessing_game::{play, Difficulty, Guess, GuessError, GuessRange, Outcome};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn secret(difficulty: Difficulty, seed: u64) -> i32 {
+    difficulty
+        .range()
+        .secret(&mut StdRng::seed_from_u64(seed))
+        .value()
+}
+
+fn transcript(difficulty: Difficulty, seed: u64, input: &str) -> (Outcome, String) {
+    let mut output = Vec::new();
+    let outcome = play(
+        difficulty,
+        Cursor::new(input),
+        &mut output,
+        &mut StdRng::seed_from_u64(seed),
+    )
+    .unwrap();
+    (outcome, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn a_won_game() {
+    let n = secret(Difficulty::Normal, 7);
+    let input = format!("{}\n{}\n{}\n", n - 1, n + 1, n);
+    let (outcome, output) = transcript(Difficulty::Normal, 7, &input);
+    assert_eq!(outcome, Outcome::Won { attempts: 3 });
+    assert_eq!(
+        output,
+        format!(
+            "Guess the number!\n\
+             Difficulty: normal, between 1 and 100, 7 attempts.\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             Too small!\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             Too big!\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             You win!\n",
+            n - 1,
+            n + 1,
+            n
+        )
+    );
+}
+
+#[test]
+fn bad_input_is_reprompted_without_using_an_attempt() {
+    let difficulty = Difficulty::Custom {
+        range: GuessRange::new(1, 3).unwrap(),
+        attempts: 1,
+    };
+    let n = secret(difficulty, 3);
+    let input = format!("abc\n\n9\n{}\n", n);
+    let (outcome, output) = transcript(difficulty, 3, &input);
+    assert_eq!(outcome, Outcome::Won { attempts: 1 });
+    assert_eq!(
+        output,
+        format!(
+            "Guess the number!\n\
+             Difficulty: custom, between 1 and 3, 1 attempts.\n\
+             Please enter your guess.\n\
+             \"abc\" is not a number, please type a number!\n\
+             Please enter your guess.\n\
+             Please type a number!\n\
+             Please enter your guess.\n\
+             9 is out of range, the number is between 1 and 3.\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             You win!\n",
+            n
+        )
+    );
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let n = secret(Difficulty::Easy, 11);
+    let wrong = if n == 1 { 2 } else { 1 };
+    let input = format!("{}\n", wrong).repeat(5);
+    let (outcome, output) = transcript(Difficulty::Easy, 11, &input);
+    assert_eq!(outcome, Outcome::Lost);
+    assert_eq!(output.matches("Your guess is").count(), 5);
+    assert!(output.ends_with(&format!("Out of guesses! The number was {}.\n", n)));
+}
+
+#[test]
+fn end_of_input_quits() {
+    let n = secret(Difficulty::Hard, 5);
+    let (outcome, output) = transcript(Difficulty::Hard, 5, "");
+    assert_eq!(outcome, Outcome::Quit);
+    assert_eq!(
+        output,
+        format!(
+            "Guess the number!\n\
+             Difficulty: hard, between 1 and 1000, 10 attempts.\n\
+             Please enter your guess.\n\
+             \n\
+             Bye! The number was {}.\n",
+            n
+        )
+    );
+}
+
+#[test]
+fn integers_are_checked_without_parsing() {
+    let range = GuessRange::new(1, 10).unwrap();
+    assert_eq!(Guess::in_range(7, range).unwrap().value(), 7);
+    assert_eq!(
+        Guess::in_range(11, range),
+        Err(GuessError::OutOfRange { value: 11, range })
+    );
+    assert_eq!(
+        Guess::in_range(i32::MIN, range),
+        Guess::try_new("-2147483648", range)
+    );
+}
+
+#[test]
+fn unreadable_input_is_an_error_not_a_quit() {
+    let error = play(
+        Difficulty::Easy,
+        Cursor::new(b"\xff\xfe\n".to_vec()),
+        Vec::new(),
+        &mut StdRng::seed_from_u64(1),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn the_binary_replays_a_seed() {
+    let n = secret(Difficulty::Normal, 42);
+    let board = std::env::temp_dir().join(format!("guessing-game-seed-{}.tsv", std::process::id()));
+    let _ = std::fs::remove_file(&board);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_guessing-game"))
+        .args(["--seed", "42", "--name", "ann", "--leaderboard-file"])
+        .arg(&board)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    writeln!(child.stdin.take().unwrap(), "{}", n).unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&board).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (game, top) = stdout.split_once("\n\n").unwrap();
+    assert_eq!(
+        game,
+        format!(
+            "Guess the number!\n\
+             Difficulty: normal, between 1 and 100, 7 attempts.\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             You win!",
+            n
+        )
+    );
+    assert!(top.starts_with("Top 10 (normal):\n  1. ann "));
+}
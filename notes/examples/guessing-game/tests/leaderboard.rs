@@ -0,0 +1,114 @@
+// the leaderboard file, including several writers at once
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::leaderboard::{Leaderboard, Record};
+
+fn scratch(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("guessing-game-{}-{}.tsv", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn win(name: &str, attempts: u32, millis: u64, difficulty: &str) -> Record {
+    Record {
+        name: name.to_string(),
+        attempts,
+        elapsed: Duration::from_millis(millis),
+        difficulty: difficulty.to_string(),
+    }
+}
+
+#[test]
+fn boards_keep_the_best_ten_per_difficulty() {
+    let path = scratch("best");
+    let board = Leaderboard::new(&path);
+    assert!(board.boards().unwrap().is_empty());
+    for i in 0..12 {
+        board
+            .record(&win(&format!("p{}", i), 12 - i, 1000, "normal"))
+            .unwrap();
+    }
+    board.record(&win("fast", 3, 500, "normal")).unwrap();
+    board.record(&win("easy", 1, 100, "easy")).unwrap();
+
+    let top = board.top("normal").unwrap();
+    let names: Vec<&str> = top.records.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["p11", "p10", "fast", "p9", "p8", "p7", "p6", "p5", "p4", "p3"]
+    );
+    assert_eq!(board.boards().unwrap().len(), 2);
+    assert_eq!(board.top("hard").unwrap().records, Vec::new());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn names_cant_break_the_format_and_bad_lines_are_skipped() {
+    let path = scratch("format");
+    let board = Leaderboard::new(&path);
+    board.record(&win("tab\there", 4, 2500, "hard")).unwrap();
+    fs::write(
+        &path,
+        fs::read_to_string(&path).unwrap() + "hard\thalf a li",
+    )
+    .unwrap();
+    let records = board.records().unwrap();
+    assert_eq!(records, [win("tab here", 4, 2500, "hard")]);
+    assert_eq!(
+        board.top("hard").unwrap().to_string(),
+        "Top 10 (hard):\n  1. tab here           4 attempts     2.5s\n"
+    );
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn concurrent_writers_dont_lose_or_mangle_records() {
+    let path = scratch("concurrent");
+    let writers: Vec<_> = (0..8)
+        .map(|w| {
+            let path = path.clone();
+            thread::spawn(move || {
+                let board = Leaderboard::new(path);
+                for i in 0..50 {
+                    board
+                        .record(&win(&format!("writer {} game {}", w, i), i + 1, 10, "easy"))
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 400);
+    assert_eq!(Leaderboard::new(&path).records().unwrap().len(), 400);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn the_flag_prints_every_board() {
+    let path = scratch("flag");
+    let board = Leaderboard::new(&path);
+    board.record(&win("ann", 2, 1500, "easy")).unwrap();
+    board.record(&win("bob", 5, 9000, "normal")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_guessing-game"))
+        .arg("--leaderboard")
+        .arg("--leaderboard-file")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Top 10 (easy):\n  1. ann                2 attempts     1.5s\n\
+         \n\
+         Top 10 (normal):\n  1. bob                5 attempts     9.0s\n"
+    );
+}
@@ -0,0 +1,225 @@
+// two player games with both sides on 127.0.0.1, in threads and as two processes
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use guessing_game::net::{self, Frame, NetError};
+use guessing_game::{Difficulty, Outcome};
+use rand::{rngs::StdRng, SeedableRng};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn secret(difficulty: Difficulty, seed: u64) -> i32 {
+    difficulty
+        .range()
+        .secret(&mut StdRng::seed_from_u64(seed))
+        .value()
+}
+
+fn listen() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").unwrap()
+}
+
+#[test]
+fn frames_round_trip() {
+    let frames = [
+        Frame::Hello {
+            min: -5,
+            max: 500,
+            attempts: 9,
+        },
+        Frame::Guess(-3),
+        Frame::Answer(std::cmp::Ordering::Less),
+        Frame::Answer(std::cmp::Ordering::Greater),
+        Frame::OutOfRange,
+        Frame::GameOver { secret: 42 },
+        Frame::Bye,
+    ];
+    let mut wire = Vec::new();
+    for frame in &frames {
+        frame.write_to(&mut wire).unwrap();
+    }
+    let mut wire = Cursor::new(wire);
+    for frame in frames {
+        assert_eq!(Frame::read_from(&mut wire).unwrap(), frame);
+    }
+    assert!(matches!(
+        Frame::read_from(&mut wire),
+        Err(NetError::Disconnected)
+    ));
+    assert!(matches!(
+        Frame::read_from(&mut Cursor::new([2, b'A', 7])),
+        Err(NetError::Protocol(_))
+    ));
+}
+
+#[test]
+fn a_game_between_two_players() {
+    let listener = listen();
+    let addr = listener.local_addr().unwrap();
+    let n = secret(Difficulty::Normal, 4);
+    let host = thread::spawn(move || {
+        let mut log = Vec::new();
+        let outcome = net::host(
+            &listener,
+            Difficulty::Normal,
+            &mut StdRng::seed_from_u64(4),
+            TIMEOUT,
+            &mut log,
+        )
+        .unwrap();
+        (outcome, String::from_utf8(log).unwrap())
+    });
+
+    let input = format!("abc\n500\n{}\n{}\n", n + 1, n);
+    let mut output = Vec::new();
+    let outcome = net::join(addr, Cursor::new(input), &mut output, TIMEOUT).unwrap();
+    assert_eq!(outcome, Outcome::Won { attempts: 2 });
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!(
+            "Guess the number!\n\
+             The host picked a number between 1 and 100, 7 attempts.\n\
+             Please enter your guess.\n\
+             \"abc\" is not a number, please type a number!\n\
+             Please enter your guess.\n\
+             500 is out of range, the number is between 1 and 100.\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             Too big!\n\
+             Please enter your guess.\n\
+             Your guess is {}\n\
+             You win!\n",
+            n + 1,
+            n
+        )
+    );
+
+    let (outcome, log) = host.join().unwrap();
+    assert_eq!(outcome, Outcome::Won { attempts: 2 });
+    assert!(log.ends_with(&format!(
+        "Guess 1: {} (Greater)\nGuess 2: {} (Equal)\nThey won in 2 attempts.\n",
+        n + 1,
+        n
+    )));
+}
+
+#[test]
+fn the_guest_can_run_out_of_guesses_or_leave() {
+    let difficulty = Difficulty::Easy;
+    let n = secret(difficulty, 8);
+    let wrong = if n == 1 { 2 } else { 1 };
+
+    for (input, expected) in [
+        (format!("{}\n", wrong).repeat(5), Outcome::Lost),
+        (format!("{}\n", wrong), Outcome::Quit),
+    ] {
+        let listener = listen();
+        let addr = listener.local_addr().unwrap();
+        let host = thread::spawn(move || {
+            net::host(
+                &listener,
+                difficulty,
+                &mut StdRng::seed_from_u64(8),
+                TIMEOUT,
+                Vec::new(),
+            )
+            .unwrap()
+        });
+        let mut output = Vec::new();
+        let outcome = net::join(addr, Cursor::new(input), &mut output, TIMEOUT).unwrap();
+        assert_eq!(outcome, expected);
+        assert_eq!(host.join().unwrap(), expected);
+        if expected == Outcome::Lost {
+            assert!(String::from_utf8(output)
+                .unwrap()
+                .ends_with(&format!("Out of guesses! The number was {}.\n", n)));
+        }
+    }
+}
+
+#[test]
+fn a_silent_guest_times_out() {
+    let listener = listen();
+    let addr = listener.local_addr().unwrap();
+    let host = thread::spawn(move || {
+        net::host(
+            &listener,
+            Difficulty::Normal,
+            &mut StdRng::seed_from_u64(1),
+            Duration::from_millis(200),
+            Vec::new(),
+        )
+    });
+    let mut guest = TcpStream::connect(addr).unwrap();
+    assert!(matches!(
+        Frame::read_from(&mut guest).unwrap(),
+        Frame::Hello { .. }
+    ));
+    assert!(matches!(host.join().unwrap(), Err(NetError::Timeout)));
+    // the host says goodbye before hanging up
+    assert_eq!(Frame::read_from(&mut guest).unwrap(), Frame::Bye);
+}
+
+#[test]
+fn a_vanished_player_is_a_disconnect() {
+    let listener = listen();
+    let addr = listener.local_addr().unwrap();
+    let host = thread::spawn(move || {
+        net::host(
+            &listener,
+            Difficulty::Normal,
+            &mut StdRng::seed_from_u64(1),
+            TIMEOUT,
+            Vec::new(),
+        )
+    });
+    drop(TcpStream::connect(addr).unwrap());
+    assert!(matches!(host.join().unwrap(), Err(NetError::Disconnected)));
+
+    // and from the guest's side, a host that accepts and hangs up
+    let listener = listen();
+    let addr = listener.local_addr().unwrap();
+    let host = thread::spawn(move || drop(listener.accept().unwrap()));
+    let joined = net::join(addr, Cursor::new("5\n"), Vec::new(), TIMEOUT);
+    host.join().unwrap();
+    assert!(matches!(joined, Err(NetError::Disconnected)));
+}
+
+#[test]
+fn two_processes_play_over_localhost() {
+    let bin = env!("CARGO_BIN_EXE_guessing-game");
+    let mut host = Command::new(bin)
+        .args(["--host", "127.0.0.1:0", "--seed", "12", "--timeout", "10"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut host_out = BufReader::new(host.stdout.take().unwrap());
+    let mut waiting = String::new();
+    host_out.read_line(&mut waiting).unwrap();
+    let addr = waiting
+        .trim()
+        .strip_prefix("Waiting for a guesser on ")
+        .unwrap()
+        .to_string();
+
+    let n = secret(Difficulty::Normal, 12);
+    let mut guest = Command::new(bin)
+        .args(["--join", &addr])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    writeln!(guest.stdin.take().unwrap(), "{}", n).unwrap();
+    let guest = guest.wait_with_output().unwrap();
+    assert!(guest.status.success());
+    assert!(String::from_utf8(guest.stdout)
+        .unwrap()
+        .ends_with(&format!("Your guess is {}\nYou win!\n", n)));
+
+    assert!(host.wait().unwrap().success());
+    let log: Vec<String> = host_out.lines().map(|l| l.unwrap()).collect();
+    assert_eq!(log.last().unwrap(), "They won in 1 attempts.");
+}
@@ -0,0 +1,116 @@
+// the computer players, checked against every secret in a range where that is cheap
+use std::process::Command;
+
+use guessing_game::solver::{self, Player, Strategy};
+use guessing_game::{Difficulty, Guess, GuessRange};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn binary_search_never_needs_more_than_log2() {
+    let range = Difficulty::Hard.range();
+    let mut rng = StdRng::seed_from_u64(0);
+    let worst = (range.min()..=range.max())
+        .map(|n| solver::solve(Strategy::Binary, &Guess::new(n, range), &mut rng))
+        .max();
+    assert_eq!(worst, Some(10));
+}
+
+#[test]
+fn linear_counts_up_from_the_bottom() {
+    let range = GuessRange::new(-5, 5).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    for n in -5..=5 {
+        assert_eq!(
+            solver::solve(Strategy::Linear, &Guess::new(n, range), &mut rng),
+            (n + 6) as u32
+        );
+    }
+}
+
+#[test]
+fn random_guesses_only_what_is_still_possible() {
+    let range = Difficulty::Normal.range();
+    let mut rng = StdRng::seed_from_u64(9);
+    for n in range.min()..=range.max() {
+        // never repeating itself, it can't take more guesses than there are numbers
+        assert!(solver::solve(Strategy::Random, &Guess::new(n, range), &mut rng) <= 100);
+    }
+}
+
+#[test]
+fn feedback_narrows_the_next_guess() {
+    let range = GuessRange::new(1, 100).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut player = Player::new(Strategy::Binary, range);
+    let first = player.next_guess(&mut rng);
+    assert_eq!(first.value(), 50);
+    player.feedback(first, std::cmp::Ordering::Greater);
+    assert_eq!(player.next_guess(&mut rng).value(), 25);
+}
+
+#[test]
+fn distributions_are_reproducible() {
+    let run = || {
+        solver::simulate(
+            Strategy::Random,
+            Difficulty::Easy,
+            500,
+            &mut StdRng::seed_from_u64(3),
+        )
+    };
+    let distribution = run();
+    assert_eq!(distribution, run());
+    assert_eq!(distribution.games(), 500);
+    assert!(distribution.wins() <= 500);
+    assert!(distribution.worst().unwrap() <= 10);
+
+    let binary = solver::simulate(
+        Strategy::Binary,
+        Difficulty::Normal,
+        500,
+        &mut StdRng::seed_from_u64(3),
+    );
+    let random = solver::simulate(
+        Strategy::Random,
+        Difficulty::Normal,
+        500,
+        &mut StdRng::seed_from_u64(3),
+    );
+    assert_eq!(binary.wins(), 500);
+    assert!(binary.mean() < random.mean());
+}
+
+#[test]
+fn huge_buckets_still_draw_their_bars() {
+    let distribution = solver::Distribution {
+        strategy: Strategy::Binary,
+        difficulty: Difficulty::Easy,
+        attempts: [(1, 1_000_000_000), (2, 500_000_000)].into(),
+    };
+    let text = distribution.to_string();
+    assert!(text.contains(&format!("1000000000 {}\n", "#".repeat(40))));
+    assert!(text.contains(&format!(" 500000000 {}\n", "#".repeat(20))));
+}
+
+#[test]
+fn the_binary_compares_strategies() {
+    let output = Command::new(env!("CARGO_BIN_EXE_guessing-game"))
+        .args(["--auto", "all", "--games", "50", "--seed", "1"])
+        .args(["--difficulty", "easy"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let headers: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains(" over "))
+        .collect();
+    assert_eq!(
+        headers,
+        [
+            "binary over 50 games on easy (between 1 and 10, 5 attempts)",
+            "random over 50 games on easy (between 1 and 10, 5 attempts)",
+            "linear over 50 games on easy (between 1 and 10, 5 attempts)",
+        ]
+    );
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::front_of_house::serving::{InKitchen, Order, Served};
+
+pub mod inventory;
+pub mod kitchen;
+pub mod menu;
+
+use inventory::{Inventory, InventoryError};
+use kitchen::{Kitchen, KitchenError, Priority, RemakeReason, TicketId};
+use menu::Season;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breakfast {
+    pub toast: String,
+    seasonal_fruit: String,
+}
+
+impl Breakfast {
+    pub fn in_season(toast: &str, season: Season) -> Breakfast {
+        Breakfast {
+            toast: String::from(toast),
+            seasonal_fruit: String::from(season.default_fruit()),
+        }
+    }
+
+    pub fn spring(toast: &str) -> Breakfast {
+        Breakfast::in_season(toast, Season::Spring)
+    }
+
+    pub fn summer(toast: &str) -> Breakfast {
+        Breakfast::in_season(toast, Season::Summer)
+    }
+
+    pub fn autumn(toast: &str) -> Breakfast {
+        Breakfast::in_season(toast, Season::Autumn)
+    }
+
+    pub fn winter(toast: &str) -> Breakfast {
+        Breakfast::in_season(toast, Season::Winter)
+    }
+
+    /// Key of this dish in the menu catalog.
+    pub fn menu_key(&self) -> &'static str {
+        "breakfast"
+    }
+}
+
+// this makes all the enum variats public
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Appetizer {
+    Soup,
+    Salad,
+}
+
+impl Appetizer {
+    /// Key of this dish in the menu catalog.
+    pub fn menu_key(&self) -> &'static str {
+        match self {
+            Appetizer::Soup => "soup",
+            Appetizer::Salad => "salad",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemakeError {
+    Kitchen(KitchenError),
+    Inventory(InventoryError),
+}
+
+impl fmt::Display for RemakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RemakeError::Kitchen(e) => write!(f, "{}", e),
+            RemakeError::Inventory(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RemakeError {}
+
+impl From<KitchenError> for RemakeError {
+    fn from(error: KitchenError) -> RemakeError {
+        RemakeError::Kitchen(error)
+    }
+}
+
+impl From<InventoryError> for RemakeError {
+    fn from(error: InventoryError) -> RemakeError {
+        RemakeError::Inventory(error)
+    }
+}
+
+/// Fires a rush remake of a ticket that came back from the table.
+///
+/// The remake uses up ingredients like the first cook did, nothing is fired if the
+/// pantry can't cover it.
+pub fn fix_incorrect_order(
+    kitchen: &mut Kitchen,
+    inventory: &mut Inventory,
+    ticket: TicketId,
+    reason: RemakeReason,
+    now: u32,
+) -> Result<TicketId, RemakeError> {
+    let original = kitchen.ticket(ticket)?;
+    // checked before anything leaves the shelf, the remake itself can't fail after that
+    if original.done_at.is_none() {
+        return Err(KitchenError::NotDone(ticket).into());
+    }
+    inventory.consume(&original.lines)?;
+    Ok(kitchen.remake(ticket, reason, now)?)
+}
+
+/// Takes the ingredients off the shelf and sends the order to the kitchen display,
+/// one ticket per station.
+///
+/// Nothing is fired if the pantry can't cover the whole order.
+pub fn cook_order(
+    kitchen: &mut Kitchen,
+    inventory: &mut Inventory,
+    order: &Order<InKitchen>,
+    now: u32,
+) -> Result<Vec<TicketId>, InventoryError> {
+    inventory.consume(order.lines())?;
+    Ok(kitchen.fire(order.table(), order.lines(), Priority::Normal, now))
+}
+
+/// Serves the order once every ticket for its table is done, otherwise hands it back.
+pub fn deliver_when_ready(
+    kitchen: &Kitchen,
+    order: Order<InKitchen>,
+) -> Result<Order<Served>, Order<InKitchen>> {
+    if !kitchen.is_ready(order.table()) {
+        return Err(order);
+    }
+    // call function in parent module of this module
+    Ok(super::deliver_order(order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_of_house::serving;
+
+    #[test]
+    fn order_is_delivered_once_the_kitchen_is_done() {
+        let mut kitchen = Kitchen::new();
+        let mut inventory = Inventory::default();
+        let order = serving::take_order(2, vec![Appetizer::Soup.into()]).unwrap();
+        let order = serving::send_to_kitchen(order);
+        let tickets = cook_order(&mut kitchen, &mut inventory, &order, 0).unwrap();
+
+        let order = deliver_when_ready(&kitchen, order).unwrap_err();
+        kitchen.complete(tickets[0], 5).unwrap();
+        let order = deliver_when_ready(&kitchen, order).unwrap();
+        assert_eq!(order.table(), 2);
+
+        let remake = fix_incorrect_order(
+            &mut kitchen,
+            &mut inventory,
+            tickets[0],
+            RemakeReason::Cold,
+            9,
+        )
+        .unwrap();
+        assert_eq!(kitchen.pending()[0].id, remake);
+        assert_eq!(kitchen.remakes().len(), 1);
+    }
+
+    #[test]
+    fn remakes_use_up_the_pantry_too() {
+        let mut kitchen = Kitchen::new();
+        let mut inventory = Inventory::new(&menu::Menu::house());
+        inventory.set_stock(
+            "soup",
+            inventory::Stock {
+                on_hand: 2,
+                low_at: None,
+            },
+        );
+        let order = serving::take_order(1, vec![Appetizer::Soup.into()]).unwrap();
+        let order = serving::send_to_kitchen(order);
+        let ticket = cook_order(&mut kitchen, &mut inventory, &order, 0).unwrap()[0];
+        assert!(matches!(
+            fix_incorrect_order(&mut kitchen, &mut inventory, ticket, RemakeReason::Cold, 1),
+            Err(RemakeError::Kitchen(KitchenError::NotDone(_)))
+        ));
+        kitchen.complete(ticket, 5).unwrap();
+
+        let remake =
+            fix_incorrect_order(&mut kitchen, &mut inventory, ticket, RemakeReason::Cold, 6)
+                .unwrap();
+        assert_eq!(inventory.on_hand("soup"), Some(0));
+        kitchen.complete(remake, 10).unwrap();
+        assert!(matches!(
+            fix_incorrect_order(&mut kitchen, &mut inventory, remake, RemakeReason::Cold, 11),
+            Err(RemakeError::Inventory(_))
+        ));
+        assert_eq!(kitchen.tickets().len(), 2);
+        assert_eq!(kitchen.remakes().len(), 1);
+    }
+
+    #[test]
+    fn cooking_uses_up_the_pantry() {
+        let mut kitchen = Kitchen::new();
+        let mut inventory = Inventory::new(&menu::Menu::house());
+        inventory.set_stock(
+            "eggs",
+            inventory::Stock {
+                on_hand: 3,
+                low_at: None,
+            },
+        );
+        let order = serving::take_order(1, vec![Breakfast::summer("Rye").into()]).unwrap();
+        let order = serving::send_to_kitchen(order);
+        cook_order(&mut kitchen, &mut inventory, &order, 0).unwrap();
+        assert_eq!(inventory.on_hand("eggs"), Some(1));
+        assert!(!inventory.is_available("breakfast"));
+
+        assert!(cook_order(&mut kitchen, &mut inventory, &order, 1).is_err());
+        assert_eq!(kitchen.tickets().len(), 1);
+    }
+}
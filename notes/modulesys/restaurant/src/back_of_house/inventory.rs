@@ -0,0 +1,418 @@
+// the pantry: recipes say what a dish uses, stock says what is left
+// only ingredients that have been counted are tracked, anything else is assumed to be plentiful
+// an order either gets every ingredient it needs or takes nothing, half a breakfast is no use
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::menu::{Menu, MenuError};
+use crate::front_of_house::serving::OrderLine;
+
+/// Ingredient quantities for one portion of a dish, in whatever unit the pantry counts.
+pub type Recipe = BTreeMap<String, u32>;
+
+/// How much of one ingredient is on hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stock {
+    pub on_hand: u32,
+    /// An alert fires when `on_hand` drops to this level or below.
+    #[serde(default)]
+    pub low_at: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StockAlert {
+    Low {
+        ingredient: String,
+        on_hand: u32,
+    },
+    /// A dish can't be made any more until its ingredients are restocked.
+    Unavailable {
+        item: String,
+    },
+}
+
+impl fmt::Display for StockAlert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StockAlert::Low {
+                ingredient,
+                on_hand,
+            } => write!(f, "{} is running low, {} left", ingredient, on_hand),
+            StockAlert::Unavailable { item } => write!(f, "{} is no longer available", item),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryError {
+    Unavailable {
+        item: String,
+    },
+    Short {
+        ingredient: String,
+        needed: u32,
+        on_hand: u32,
+    },
+    /// A delivery that would take the count past `u32::MAX`.
+    Overflow {
+        ingredient: String,
+        quantity: u32,
+        on_hand: u32,
+    },
+    Parse(String),
+}
+
+impl fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InventoryError::Unavailable { item } => write!(f, "{} is unavailable", item),
+            InventoryError::Short {
+                ingredient,
+                needed,
+                on_hand,
+            } => write!(
+                f,
+                "not enough {}: {} needed, {} on hand",
+                ingredient, needed, on_hand
+            ),
+            InventoryError::Overflow {
+                ingredient,
+                quantity,
+                on_hand,
+            } => write!(
+                f,
+                "can't add {} {} to the {} on hand",
+                quantity, ingredient, on_hand
+            ),
+            InventoryError::Parse(e) => write!(f, "could not parse stock: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inventory {
+    recipes: BTreeMap<String, Recipe>,
+    stock: BTreeMap<String, Stock>,
+    unavailable: BTreeSet<String>,
+    alerts: Vec<StockAlert>,
+}
+
+impl Inventory {
+    /// An inventory using the recipes of every item on `menu`, with nothing counted yet.
+    pub fn new(menu: &Menu) -> Inventory {
+        Inventory {
+            recipes: menu
+                .items()
+                .iter()
+                .map(|item| (item.key.clone(), item.recipe.clone()))
+                .collect(),
+            ..Inventory::default()
+        }
+    }
+
+    /// Reads stock levels from toml, one table per ingredient:
+    ///
+    /// ```toml
+    /// [eggs]
+    /// on_hand = 60
+    /// low_at = 12
+    /// ```
+    pub fn from_toml(menu: &Menu, text: &str) -> Result<Inventory, InventoryError> {
+        let stock: BTreeMap<String, Stock> =
+            toml::from_str(text).map_err(|e| InventoryError::Parse(e.to_string()))?;
+        let mut inventory = Inventory::new(menu);
+        for (ingredient, stock) in stock {
+            inventory.set_stock(&ingredient, stock);
+        }
+        Ok(inventory)
+    }
+
+    pub fn recipe(&self, item: &str) -> Result<&Recipe, MenuError> {
+        self.recipes
+            .get(item)
+            .ok_or_else(|| MenuError::UnknownItem(item.to_string()))
+    }
+
+    /// Counts an ingredient, replacing whatever was known about it.
+    pub fn set_stock(&mut self, ingredient: &str, stock: Stock) {
+        self.stock.insert(ingredient.to_string(), stock);
+        self.refresh();
+    }
+
+    /// Adds a delivery to the shelf, which may bring dishes back.
+    ///
+    /// A delivery too large to count is refused and changes nothing.
+    pub fn restock(&mut self, ingredient: &str, quantity: u32) -> Result<(), InventoryError> {
+        let stock = self.stock.entry(ingredient.to_string()).or_insert(Stock {
+            on_hand: 0,
+            low_at: None,
+        });
+        let Some(on_hand) = stock.on_hand.checked_add(quantity) else {
+            return Err(InventoryError::Overflow {
+                ingredient: ingredient.to_string(),
+                quantity,
+                on_hand: stock.on_hand,
+            });
+        };
+        stock.on_hand = on_hand;
+        self.refresh();
+        Ok(())
+    }
+
+    pub fn stock(&self) -> &BTreeMap<String, Stock> {
+        &self.stock
+    }
+
+    /// `None` for ingredients nobody has counted.
+    pub fn on_hand(&self, ingredient: &str) -> Option<u32> {
+        self.stock.get(ingredient).map(|s| s.on_hand)
+    }
+
+    pub fn is_available(&self, item: &str) -> bool {
+        !self.unavailable.contains(item)
+    }
+
+    /// Menu items that ran out, by key.
+    pub fn unavailable(&self) -> impl Iterator<Item = &str> {
+        self.unavailable.iter().map(|item| item.as_str())
+    }
+
+    /// Ingredients at or below their alert level.
+    pub fn low(&self) -> impl Iterator<Item = (&str, &Stock)> {
+        self.stock
+            .iter()
+            .filter(|(_, s)| s.low_at.is_some_and(|low| s.on_hand <= low))
+            .map(|(ingredient, stock)| (ingredient.as_str(), stock))
+    }
+
+    /// Every alert raised so far, oldest first.
+    pub fn alerts(&self) -> &[StockAlert] {
+        &self.alerts
+    }
+
+    /// Takes the ingredients for `lines` off the shelf.
+    ///
+    /// Nothing is taken unless everything is there. Returns the alerts this raised.
+    pub fn consume(&mut self, lines: &[OrderLine]) -> Result<&[StockAlert], InventoryError> {
+        let mut needed: BTreeMap<&str, u32> = BTreeMap::new();
+        for line in lines {
+            let item = line.menu_key();
+            if !self.is_available(item) {
+                return Err(InventoryError::Unavailable {
+                    item: item.to_string(),
+                });
+            }
+            for (ingredient, quantity) in self.recipes.get(item).into_iter().flatten() {
+                let total = needed.entry(ingredient).or_default();
+                match (total.checked_add(*quantity), self.stock.get(ingredient)) {
+                    (Some(sum), _) => *total = sum,
+                    // more than can be counted is more than is on hand
+                    (None, Some(stock)) => {
+                        return Err(InventoryError::Short {
+                            ingredient: ingredient.to_string(),
+                            needed: u32::MAX,
+                            on_hand: stock.on_hand,
+                        })
+                    }
+                    // nobody counts it, so there is always enough
+                    (None, None) => *total = u32::MAX,
+                }
+            }
+        }
+        for (&ingredient, &needed) in &needed {
+            if let Some(stock) = self.stock.get(ingredient) {
+                if stock.on_hand < needed {
+                    return Err(InventoryError::Short {
+                        ingredient: ingredient.to_string(),
+                        needed,
+                        on_hand: stock.on_hand,
+                    });
+                }
+            }
+        }
+
+        let raised = self.alerts.len();
+        let needed: Vec<(String, u32)> = needed
+            .into_iter()
+            .map(|(ingredient, quantity)| (ingredient.to_string(), quantity))
+            .collect();
+        for (ingredient, quantity) in needed {
+            let Some(stock) = self.stock.get_mut(&ingredient) else {
+                continue;
+            };
+            let was_low = stock.low_at.is_some_and(|low| stock.on_hand <= low);
+            stock.on_hand -= quantity;
+            if !was_low && stock.low_at.is_some_and(|low| stock.on_hand <= low) {
+                let on_hand = stock.on_hand;
+                self.alerts.push(StockAlert::Low {
+                    ingredient,
+                    on_hand,
+                });
+            }
+        }
+        self.refresh();
+        Ok(&self.alerts[raised..])
+    }
+
+    // an item is unavailable while any counted ingredient can't cover one portion
+    fn refresh(&mut self) {
+        for (item, recipe) in &self.recipes {
+            let short = recipe.iter().any(|(ingredient, quantity)| {
+                self.stock
+                    .get(ingredient)
+                    .is_some_and(|s| s.on_hand < *quantity)
+            });
+            if short && self.unavailable.insert(item.clone()) {
+                self.alerts
+                    .push(StockAlert::Unavailable { item: item.clone() });
+            } else if !short {
+                self.unavailable.remove(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::{Appetizer, Breakfast};
+
+    fn stocked(text: &str) -> Inventory {
+        Inventory::from_toml(&Menu::house(), text).unwrap()
+    }
+
+    #[test]
+    fn recipes_come_from_the_menu() {
+        let inventory = Inventory::new(&Menu::house());
+        assert_eq!(inventory.recipe("breakfast").unwrap()["eggs"], 2);
+        assert_eq!(inventory.recipe("soup").unwrap()["soup"], 1);
+        assert!(inventory.recipe("steak").is_err());
+    }
+
+    #[test]
+    fn cooking_depletes_counted_ingredients() {
+        let mut inventory = stocked("[eggs]\non_hand = 10\n\n[soup]\non_hand = 4\n");
+        let lines = vec![
+            Breakfast::summer("Rye").into(),
+            Breakfast::summer("Wheat").into(),
+            Appetizer::Soup.into(),
+        ];
+        assert!(inventory.consume(&lines).unwrap().is_empty());
+        assert_eq!(inventory.on_hand("eggs"), Some(6));
+        assert_eq!(inventory.on_hand("soup"), Some(3));
+        // never counted, so never runs out
+        assert_eq!(inventory.on_hand("bread"), None);
+    }
+
+    #[test]
+    fn low_stock_alerts_fire_once_per_crossing() {
+        let mut inventory = stocked("[eggs]\non_hand = 8\nlow_at = 4\n");
+        let breakfast: Vec<OrderLine> = vec![Breakfast::summer("Rye").into()];
+        assert!(inventory.consume(&breakfast).unwrap().is_empty());
+        assert_eq!(
+            inventory.consume(&breakfast).unwrap(),
+            [StockAlert::Low {
+                ingredient: "eggs".to_string(),
+                on_hand: 4
+            }]
+        );
+        assert!(inventory.consume(&breakfast).unwrap().is_empty());
+        assert_eq!(inventory.low().count(), 1);
+
+        inventory.restock("eggs", 10).unwrap();
+        assert_eq!(inventory.low().count(), 0);
+        assert_eq!(inventory.consume(&breakfast).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn items_run_out_and_come_back() {
+        let mut inventory = stocked("[soup]\non_hand = 1\n");
+        assert_eq!(
+            inventory.consume(&[Appetizer::Soup.into()]).unwrap(),
+            [StockAlert::Unavailable {
+                item: "soup".to_string()
+            }]
+        );
+        assert!(!inventory.is_available("soup"));
+        assert_eq!(inventory.unavailable().collect::<Vec<_>>(), vec!["soup"]);
+        assert_eq!(
+            inventory.consume(&[Appetizer::Soup.into()]),
+            Err(InventoryError::Unavailable {
+                item: "soup".to_string()
+            })
+        );
+
+        inventory.restock("soup", 5).unwrap();
+        assert!(inventory.is_available("soup"));
+        assert!(inventory.consume(&[Appetizer::Soup.into()]).is_ok());
+    }
+
+    #[test]
+    fn restocking_past_the_largest_count_is_refused() {
+        let mut inventory = stocked("[eggs]\non_hand = 10\n");
+        assert_eq!(
+            inventory.restock("eggs", u32::MAX),
+            Err(InventoryError::Overflow {
+                ingredient: "eggs".to_string(),
+                quantity: u32::MAX,
+                on_hand: 10
+            })
+        );
+        assert_eq!(inventory.on_hand("eggs"), Some(10));
+        inventory.restock("eggs", u32::MAX - 10).unwrap();
+        assert_eq!(inventory.on_hand("eggs"), Some(u32::MAX));
+    }
+
+    #[test]
+    fn short_orders_take_nothing() {
+        let mut inventory = stocked("[eggs]\non_hand = 3\n\n[greens]\non_hand = 5\n");
+        let lines = vec![
+            Appetizer::Salad.into(),
+            Breakfast::summer("Rye").into(),
+            Breakfast::summer("Rye").into(),
+        ];
+        assert_eq!(
+            inventory.consume(&lines),
+            Err(InventoryError::Short {
+                ingredient: "eggs".to_string(),
+                needed: 4,
+                on_hand: 3
+            })
+        );
+        assert_eq!(inventory.on_hand("greens"), Some(5));
+        assert_eq!(inventory.on_hand("eggs"), Some(3));
+    }
+
+    #[test]
+    fn orders_needing_more_than_can_be_counted_are_short() {
+        let mut inventory = stocked("[broth]\non_hand = 4294967295\n");
+        let recipe = Recipe::from([
+            ("broth".to_string(), u32::MAX),
+            ("salt".to_string(), u32::MAX),
+        ]);
+        inventory.recipes.insert("soup".to_string(), recipe);
+        let lines: Vec<OrderLine> = vec![Appetizer::Soup.into(), Appetizer::Soup.into()];
+        assert_eq!(
+            inventory.consume(&lines),
+            Err(InventoryError::Short {
+                ingredient: "broth".to_string(),
+                needed: u32::MAX,
+                on_hand: u32::MAX
+            })
+        );
+        assert_eq!(inventory.on_hand("broth"), Some(u32::MAX));
+
+        // salt isn't counted, so only the broth runs out
+        inventory
+            .recipes
+            .get_mut("soup")
+            .unwrap()
+            .insert("broth".to_string(), 1);
+        assert!(inventory.consume(&lines).is_ok());
+        assert_eq!(inventory.on_hand("broth"), Some(u32::MAX - 2));
+    }
+}
@@ -0,0 +1,366 @@
+// the kitchen display: every order is split into tickets, one per station
+// tickets are cooked in priority order and anything past its prep estimate is late
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::back_of_house::Appetizer;
+use crate::front_of_house::serving::OrderLine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TicketId(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Station {
+    Grill,
+    Cold,
+}
+
+impl Station {
+    pub fn for_line(line: &OrderLine) -> Station {
+        match line {
+            OrderLine::Breakfast(_) | OrderLine::Appetizer(Appetizer::Soup) => Station::Grill,
+            OrderLine::Appetizer(Appetizer::Salad) => Station::Cold,
+        }
+    }
+}
+
+/// Higher priorities are cooked first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    Normal,
+    High,
+    Rush,
+}
+
+/// Why a ticket had to be cooked again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RemakeReason {
+    WrongItem,
+    Undercooked,
+    Overcooked,
+    Cold,
+    Allergy,
+    Dropped,
+}
+
+/// Minutes one line takes to prepare.
+pub fn prep_minutes(line: &OrderLine) -> u32 {
+    match line {
+        OrderLine::Breakfast(_) => 12,
+        OrderLine::Appetizer(Appetizer::Soup) => 5,
+        OrderLine::Appetizer(Appetizer::Salad) => 4,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub table: u32,
+    pub station: Station,
+    pub priority: Priority,
+    pub lines: Vec<OrderLine>,
+    /// Estimated minutes to prepare every line.
+    pub prep_minutes: u32,
+    /// Minutes since opening.
+    pub created_at: u32,
+    pub done_at: Option<u32>,
+    /// The ticket this one remakes.
+    pub remake_of: Option<TicketId>,
+}
+
+impl Ticket {
+    /// Stops at `u32::MAX`, a ticket fired that late is due at the end of time.
+    pub fn due_at(&self) -> u32 {
+        self.created_at.saturating_add(self.prep_minutes)
+    }
+
+    pub fn is_late(&self, now: u32) -> bool {
+        self.done_at.is_none() && now > self.due_at()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remake {
+    pub original: TicketId,
+    pub ticket: TicketId,
+    pub reason: RemakeReason,
+    /// Minutes since opening.
+    pub at: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KitchenError {
+    UnknownTicket(TicketId),
+    AlreadyDone(TicketId),
+    NotDone(TicketId),
+}
+
+impl fmt::Display for KitchenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KitchenError::UnknownTicket(id) => write!(f, "ticket {} does not exist", id.0),
+            KitchenError::AlreadyDone(id) => write!(f, "ticket {} is already done", id.0),
+            KitchenError::NotDone(id) => write!(f, "ticket {} has not been cooked yet", id.0),
+        }
+    }
+}
+
+impl std::error::Error for KitchenError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Kitchen {
+    tickets: Vec<Ticket>,
+    remakes: Vec<Remake>,
+    next_id: u64,
+}
+
+impl Kitchen {
+    pub fn new() -> Kitchen {
+        Kitchen::default()
+    }
+
+    /// Splits `lines` into one ticket per station.
+    pub fn fire(
+        &mut self,
+        table: u32,
+        lines: &[OrderLine],
+        priority: Priority,
+        now: u32,
+    ) -> Vec<TicketId> {
+        let mut ids = Vec::new();
+        for station in [Station::Grill, Station::Cold] {
+            let lines: Vec<OrderLine> = lines
+                .iter()
+                .filter(|l| Station::for_line(l) == station)
+                .cloned()
+                .collect();
+            if !lines.is_empty() {
+                ids.push(self.push(table, station, priority, lines, now, None));
+            }
+        }
+        ids
+    }
+
+    /// Marks a ticket as cooked.
+    pub fn complete(&mut self, id: TicketId, now: u32) -> Result<(), KitchenError> {
+        let ticket = self.ticket_mut(id)?;
+        if ticket.done_at.is_some() {
+            return Err(KitchenError::AlreadyDone(id));
+        }
+        ticket.done_at = Some(now);
+        Ok(())
+    }
+
+    /// Fires a rush copy of a cooked ticket that came back.
+    pub fn remake(
+        &mut self,
+        id: TicketId,
+        reason: RemakeReason,
+        now: u32,
+    ) -> Result<TicketId, KitchenError> {
+        let original = self.ticket(id)?;
+        if original.done_at.is_none() {
+            return Err(KitchenError::NotDone(id));
+        }
+        let (table, station, lines) = (original.table, original.station, original.lines.clone());
+        let ticket = self.push(table, station, Priority::Rush, lines, now, Some(id));
+        self.remakes.push(Remake {
+            original: id,
+            ticket,
+            reason,
+            at: now,
+        });
+        Ok(ticket)
+    }
+
+    pub fn ticket(&self, id: TicketId) -> Result<&Ticket, KitchenError> {
+        self.tickets
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or(KitchenError::UnknownTicket(id))
+    }
+
+    pub fn tickets(&self) -> &[Ticket] {
+        &self.tickets
+    }
+
+    pub fn remakes(&self) -> &[Remake] {
+        &self.remakes
+    }
+
+    /// Tickets still to cook, highest priority first, then oldest first.
+    pub fn pending(&self) -> Vec<&Ticket> {
+        let mut pending: Vec<&Ticket> = self
+            .tickets
+            .iter()
+            .filter(|t| t.done_at.is_none())
+            .collect();
+        pending.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.created_at, t.id));
+        pending
+    }
+
+    pub fn pending_at(&self, station: Station) -> Vec<&Ticket> {
+        self.pending()
+            .into_iter()
+            .filter(|t| t.station == station)
+            .collect()
+    }
+
+    /// Pending tickets that are past their prep estimate.
+    pub fn late(&self, now: u32) -> Vec<&Ticket> {
+        self.pending()
+            .into_iter()
+            .filter(|t| t.is_late(now))
+            .collect()
+    }
+
+    /// True when nothing is left to cook for `table`.
+    pub fn is_ready(&self, table: u32) -> bool {
+        self.tickets
+            .iter()
+            .all(|t| t.table != table || t.done_at.is_some())
+    }
+
+    pub fn remakes_by(&self, reason: RemakeReason) -> usize {
+        self.remakes.iter().filter(|r| r.reason == reason).count()
+    }
+
+    fn push(
+        &mut self,
+        table: u32,
+        station: Station,
+        priority: Priority,
+        lines: Vec<OrderLine>,
+        now: u32,
+        remake_of: Option<TicketId>,
+    ) -> TicketId {
+        self.next_id += 1;
+        let id = TicketId(self.next_id);
+        self.tickets.push(Ticket {
+            id,
+            table,
+            station,
+            priority,
+            prep_minutes: lines.iter().fold(0, |total: u32, line| {
+                total.saturating_add(prep_minutes(line))
+            }),
+            lines,
+            created_at: now,
+            done_at: None,
+            remake_of,
+        });
+        id
+    }
+
+    fn ticket_mut(&mut self, id: TicketId) -> Result<&mut Ticket, KitchenError> {
+        self.tickets
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or(KitchenError::UnknownTicket(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::Breakfast;
+
+    fn lines() -> Vec<OrderLine> {
+        vec![
+            Breakfast::summer("Rye").into(),
+            Appetizer::Salad.into(),
+            Appetizer::Soup.into(),
+        ]
+    }
+
+    #[test]
+    fn orders_split_by_station() {
+        let mut kitchen = Kitchen::new();
+        let ids = kitchen.fire(3, &lines(), Priority::Normal, 10);
+        assert_eq!(ids.len(), 2);
+
+        let grill = kitchen.ticket(ids[0]).unwrap();
+        assert_eq!(grill.station, Station::Grill);
+        assert_eq!(grill.lines.len(), 2);
+        assert_eq!(grill.prep_minutes, 17);
+        assert_eq!(grill.due_at(), 27);
+
+        let cold = kitchen.ticket(ids[1]).unwrap();
+        assert_eq!(cold.station, Station::Cold);
+        assert_eq!(cold.prep_minutes, 4);
+    }
+
+    #[test]
+    fn pending_is_ordered_by_priority_then_age() {
+        let mut kitchen = Kitchen::new();
+        let first = kitchen.fire(1, &[Appetizer::Soup.into()], Priority::Normal, 0)[0];
+        let second = kitchen.fire(2, &[Appetizer::Soup.into()], Priority::Normal, 1)[0];
+        let urgent = kitchen.fire(3, &[Appetizer::Salad.into()], Priority::High, 2)[0];
+
+        let ids: Vec<TicketId> = kitchen.pending().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![urgent, first, second]);
+
+        let grill: Vec<TicketId> = kitchen
+            .pending_at(Station::Grill)
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(grill, vec![first, second]);
+    }
+
+    #[test]
+    fn late_tickets_are_reported_until_done() {
+        let mut kitchen = Kitchen::new();
+        let id = kitchen.fire(1, &[Appetizer::Soup.into()], Priority::Normal, 0)[0];
+        assert!(kitchen.late(5).is_empty());
+        assert_eq!(kitchen.late(6)[0].id, id);
+
+        kitchen.complete(id, 7).unwrap();
+        assert!(kitchen.late(8).is_empty());
+        assert_eq!(kitchen.complete(id, 8), Err(KitchenError::AlreadyDone(id)));
+    }
+
+    #[test]
+    fn tickets_fired_at_the_end_of_time_are_never_late() {
+        let mut kitchen = Kitchen::new();
+        let id = kitchen.fire(1, &lines(), Priority::Normal, u32::MAX - 1)[0];
+        assert_eq!(kitchen.ticket(id).unwrap().due_at(), u32::MAX);
+        assert!(kitchen.late(u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn remakes_are_rushed_and_tracked() {
+        let mut kitchen = Kitchen::new();
+        let id = kitchen.fire(1, &lines(), Priority::Normal, 0)[0];
+        assert_eq!(
+            kitchen.remake(id, RemakeReason::Cold, 5),
+            Err(KitchenError::NotDone(id))
+        );
+
+        kitchen.complete(id, 15).unwrap();
+        let remake = kitchen.remake(id, RemakeReason::Cold, 20).unwrap();
+        let ticket = kitchen.ticket(remake).unwrap();
+        assert_eq!(ticket.priority, Priority::Rush);
+        assert_eq!(ticket.remake_of, Some(id));
+        assert_eq!(kitchen.pending()[0].id, remake);
+        assert_eq!(kitchen.remakes_by(RemakeReason::Cold), 1);
+        assert_eq!(kitchen.remakes_by(RemakeReason::Allergy), 0);
+        assert_eq!(
+            kitchen.remake(TicketId(99), RemakeReason::Cold, 20),
+            Err(KitchenError::UnknownTicket(TicketId(99)))
+        );
+    }
+
+    #[test]
+    fn table_is_ready_when_every_ticket_is_done() {
+        let mut kitchen = Kitchen::new();
+        let ids = kitchen.fire(1, &lines(), Priority::Normal, 0);
+        assert!(!kitchen.is_ready(1));
+        assert!(kitchen.is_ready(2));
+        for id in ids {
+            kitchen.complete(id, 10).unwrap();
+        }
+        assert!(kitchen.is_ready(1));
+    }
+}
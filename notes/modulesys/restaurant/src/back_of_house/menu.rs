@@ -0,0 +1,473 @@
+// the menu catalog: what we sell, what it costs and what is in it
+// prices are whole cents so nothing here ever touches floats
+// the catalog is plain data so it can be loaded from a toml or json file instead of being compiled in
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::inventory::Recipe;
+use super::Breakfast;
+use crate::front_of_house::serving::OrderLine;
+
+/// A calendar date, only used to pick seasonal produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Date, MenuError> {
+        if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return Err(MenuError::InvalidDate { year, month, day });
+        }
+        Ok(Date { year, month, day })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// 1 for January 1st up to 365 or 366.
+    pub fn day_of_year(&self) -> u32 {
+        (1..self.month)
+            .map(|m| days_in_month(self.year, m))
+            .sum::<u32>()
+            + self.day
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Meteorological seasons: spring starts March 1st, summer June 1st and so on.
+    pub fn of(date: Date) -> Season {
+        match date.month {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// Fruit used when no catalog is at hand.
+    pub fn default_fruit(self) -> &'static str {
+        match self {
+            Season::Spring => "strawberries",
+            Season::Summer => "peaches",
+            Season::Autumn => "apples",
+            Season::Winter => "oranges",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Egg,
+    Nuts,
+    Soy,
+    Fish,
+    Shellfish,
+    Celery,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Breakfast,
+    Appetizer,
+}
+
+/// One option of a modifier, `extra` is added to the item price.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Choice {
+    pub name: String,
+    #[serde(default)]
+    pub extra: u64,
+}
+
+/// A choice the guest makes when ordering, like the kind of toast.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifier {
+    pub name: String,
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MenuItem {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub category: Category,
+    /// Price in cents.
+    pub price: u64,
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+    /// What one portion takes from the pantry.
+    #[serde(default)]
+    pub recipe: Recipe,
+}
+
+impl MenuItem {
+    pub fn modifier(&self, name: &str) -> Option<&Modifier> {
+        self.modifiers.iter().find(|m| m.name == name)
+    }
+}
+
+/// Fruit served with breakfast, rotated weekly within each season.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FruitRotation {
+    pub spring: Vec<String>,
+    pub summer: Vec<String>,
+    pub autumn: Vec<String>,
+    pub winter: Vec<String>,
+}
+
+impl FruitRotation {
+    pub fn for_season(&self, season: Season) -> &[String] {
+        match season {
+            Season::Spring => &self.spring,
+            Season::Summer => &self.summer,
+            Season::Autumn => &self.autumn,
+            Season::Winter => &self.winter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+    fruit: FruitRotation,
+}
+
+#[derive(Debug)]
+pub enum MenuError {
+    Io(io::Error),
+    Parse(String),
+    UnknownFormat(String),
+    InvalidDate { year: i32, month: u32, day: u32 },
+    DuplicateItem(String),
+    NoFruit(Season),
+    UnknownItem(String),
+    UnknownChoice { modifier: String, choice: String },
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MenuError::Io(e) => write!(f, "could not read menu: {}", e),
+            MenuError::Parse(e) => write!(f, "could not parse menu: {}", e),
+            MenuError::UnknownFormat(path) => {
+                write!(f, "{} is neither a .toml nor a .json menu", path)
+            }
+            MenuError::InvalidDate { year, month, day } => {
+                write!(f, "{}-{:02}-{:02} is not a valid date", year, month, day)
+            }
+            MenuError::DuplicateItem(key) => write!(f, "menu item {} is listed twice", key),
+            MenuError::NoFruit(season) => write!(f, "no fruit listed for {:?}", season),
+            MenuError::UnknownItem(key) => write!(f, "{} is not on the menu", key),
+            MenuError::UnknownChoice { modifier, choice } => {
+                write!(f, "{} is not a {} choice", choice, modifier)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MenuError {}
+
+impl From<io::Error> for MenuError {
+    fn from(error: io::Error) -> MenuError {
+        MenuError::Io(error)
+    }
+}
+
+impl Menu {
+    pub fn new(items: Vec<MenuItem>, fruit: FruitRotation) -> Result<Menu, MenuError> {
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].iter().any(|other| other.key == item.key) {
+                return Err(MenuError::DuplicateItem(item.key.clone()));
+            }
+        }
+        for season in [
+            Season::Spring,
+            Season::Summer,
+            Season::Autumn,
+            Season::Winter,
+        ] {
+            if fruit.for_season(season).is_empty() {
+                return Err(MenuError::NoFruit(season));
+            }
+        }
+        Ok(Menu { items, fruit })
+    }
+
+    /// The menu shipped with the crate in `menu.toml`.
+    pub fn house() -> Menu {
+        Menu::from_toml(include_str!("../../menu.toml")).expect("menu.toml should be a valid menu")
+    }
+
+    pub fn from_toml(text: &str) -> Result<Menu, MenuError> {
+        let menu: Menu = toml::from_str(text).map_err(|e| MenuError::Parse(e.to_string()))?;
+        Menu::new(menu.items, menu.fruit)
+    }
+
+    pub fn from_json(text: &str) -> Result<Menu, MenuError> {
+        let menu: Menu = serde_json::from_str(text).map_err(|e| MenuError::Parse(e.to_string()))?;
+        Menu::new(menu.items, menu.fruit)
+    }
+
+    /// Reads a menu file, the extension decides between toml and json.
+    pub fn load(path: impl AsRef<Path>) -> Result<Menu, MenuError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Menu::from_toml(&text),
+            Some("json") => Menu::from_json(&text),
+            _ => Err(MenuError::UnknownFormat(path.display().to_string())),
+        }
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    pub fn item(&self, key: &str) -> Result<&MenuItem, MenuError> {
+        self.items
+            .iter()
+            .find(|item| item.key == key)
+            .ok_or_else(|| MenuError::UnknownItem(key.to_string()))
+    }
+
+    /// Items that are safe for a guest avoiding `allergen`.
+    pub fn free_of(&self, allergen: Allergen) -> impl Iterator<Item = &MenuItem> {
+        self.items
+            .iter()
+            .filter(move |item| !item.allergens.contains(&allergen))
+    }
+
+    /// This week's fruit for the season `date` falls in.
+    pub fn fruit_for(&self, date: Date) -> &str {
+        let fruit = self.fruit.for_season(Season::of(date));
+        let week = (date.day_of_year() - 1) / 7;
+        &fruit[week as usize % fruit.len()]
+    }
+
+    /// A breakfast with the toast checked against the menu and the fruit of the day.
+    pub fn breakfast(&self, toast: &str, date: Date) -> Result<Breakfast, MenuError> {
+        self.choice("breakfast", "toast", toast)?;
+        Ok(Breakfast {
+            toast: toast.to_string(),
+            seasonal_fruit: self.fruit_for(date).to_string(),
+        })
+    }
+
+    /// Price in cents of one order line including modifier extras.
+    pub fn price_of(&self, line: &OrderLine) -> Result<u64, MenuError> {
+        match line {
+            OrderLine::Breakfast(breakfast) => {
+                let base = self.item(breakfast.menu_key())?.price;
+                let toast = self.choice(breakfast.menu_key(), "toast", &breakfast.toast)?;
+                Ok(base + toast.extra)
+            }
+            OrderLine::Appetizer(appetizer) => Ok(self.item(appetizer.menu_key())?.price),
+        }
+    }
+
+    fn choice(&self, key: &str, modifier: &str, name: &str) -> Result<&Choice, MenuError> {
+        let unknown = || MenuError::UnknownChoice {
+            modifier: modifier.to_string(),
+            choice: name.to_string(),
+        };
+        self.item(key)?
+            .modifier(modifier)
+            .ok_or_else(unknown)?
+            .choices
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::Appetizer;
+
+    #[test]
+    fn dates_are_validated() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(matches!(
+            Date::new(2023, 2, 29),
+            Err(MenuError::InvalidDate { .. })
+        ));
+        assert!(Date::new(2023, 13, 1).is_err());
+        assert_eq!(Date::new(2024, 3, 1).unwrap().day_of_year(), 61);
+        assert_eq!(Date::new(2023, 12, 31).unwrap().day_of_year(), 365);
+    }
+
+    #[test]
+    fn seasons_follow_the_month() {
+        let season = |m| Season::of(Date::new(2024, m, 1).unwrap());
+        assert_eq!(season(2), Season::Winter);
+        assert_eq!(season(3), Season::Spring);
+        assert_eq!(season(7), Season::Summer);
+        assert_eq!(season(11), Season::Autumn);
+        assert_eq!(season(12), Season::Winter);
+    }
+
+    #[test]
+    fn every_season_has_a_breakfast() {
+        assert_eq!(
+            Breakfast::summer("Rye"),
+            Breakfast::in_season("Rye", Season::Summer)
+        );
+        assert_eq!(Breakfast::spring("Rye").seasonal_fruit, "strawberries");
+        assert_eq!(Breakfast::autumn("Rye").seasonal_fruit, "apples");
+        assert_eq!(Breakfast::winter("Rye").seasonal_fruit, "oranges");
+    }
+
+    #[test]
+    fn house_menu_loads() {
+        let menu = Menu::house();
+        assert_eq!(menu.item("soup").unwrap().category, Category::Appetizer);
+        assert!(menu.item("breakfast").unwrap().modifier("toast").is_some());
+        assert!(matches!(menu.item("steak"), Err(MenuError::UnknownItem(_))));
+    }
+
+    #[test]
+    fn fruit_rotates_weekly_within_the_season() {
+        let menu = Menu::house();
+        let summer = &menu.fruit.summer;
+        let june_1 = Date::new(2024, 6, 1).unwrap();
+        let a_week_later = Date::new(2024, 6, 8).unwrap();
+        assert!(summer.iter().any(|f| f == menu.fruit_for(june_1)));
+        assert_ne!(menu.fruit_for(june_1), menu.fruit_for(a_week_later));
+        assert!(menu
+            .fruit
+            .winter
+            .iter()
+            .any(|f| f == menu.fruit_for(Date::new(2024, 1, 15).unwrap())));
+    }
+
+    #[test]
+    fn breakfast_checks_the_toast() {
+        let menu = Menu::house();
+        let date = Date::new(2024, 6, 1).unwrap();
+        let breakfast = menu.breakfast("Rye", date).unwrap();
+        assert_eq!(breakfast.seasonal_fruit, menu.fruit_for(date));
+        assert!(matches!(
+            menu.breakfast("Cardboard", date),
+            Err(MenuError::UnknownChoice { .. })
+        ));
+    }
+
+    #[test]
+    fn prices_include_modifier_extras() {
+        let menu = Menu::house();
+        let base = menu.item("breakfast").unwrap().price;
+        assert_eq!(
+            menu.price_of(&Breakfast::summer("Wheat").into()).unwrap(),
+            base
+        );
+        assert_eq!(
+            menu.price_of(&Breakfast::summer("Sourdough").into())
+                .unwrap(),
+            base + 50
+        );
+        assert_eq!(
+            menu.price_of(&Appetizer::Soup.into()).unwrap(),
+            menu.item("soup").unwrap().price
+        );
+    }
+
+    #[test]
+    fn allergens_filter_items() {
+        let menu = Menu::house();
+        let keys: Vec<&str> = menu
+            .free_of(Allergen::Gluten)
+            .map(|i| i.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["salad"]);
+    }
+
+    #[test]
+    fn json_and_toml_menus_match() {
+        let menu = Menu::house();
+        let json = serde_json::to_string(&menu).unwrap();
+        assert_eq!(Menu::from_json(&json).unwrap(), menu);
+    }
+
+    #[test]
+    fn invalid_menus_are_rejected() {
+        let mut menu = Menu::house();
+        menu.fruit.autumn.clear();
+        assert!(matches!(
+            Menu::new(menu.items.clone(), menu.fruit.clone()),
+            Err(MenuError::NoFruit(Season::Autumn))
+        ));
+
+        let mut items = Menu::house().items;
+        items.push(items[0].clone());
+        assert!(matches!(
+            Menu::new(items, Menu::house().fruit),
+            Err(MenuError::DuplicateItem(_))
+        ));
+
+        assert!(matches!(
+            Menu::from_toml("items = 3"),
+            Err(MenuError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn load_picks_the_format_from_the_extension() {
+        let dir = std::env::temp_dir().join(format!("restaurant-menu-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let json = dir.join("menu.json");
+        fs::write(&json, serde_json::to_string(&Menu::house()).unwrap()).unwrap();
+        assert_eq!(Menu::load(&json).unwrap(), Menu::house());
+
+        let txt = dir.join("menu.txt");
+        fs::write(&txt, "").unwrap();
+        assert!(matches!(Menu::load(&txt), Err(MenuError::UnknownFormat(_))));
+        assert!(matches!(
+            Menu::load(dir.join("missing.toml")),
+            Err(MenuError::Io(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
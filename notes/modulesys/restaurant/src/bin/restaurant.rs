@@ -0,0 +1,854 @@
+// a front desk repl on top of the restaurant library
+// every command becomes a service event, so with --journal the day survives a restart
+// with --script the commands come from a file and are echoed, which makes demos and regression tests repeatable
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use restaurant::hosting::{PartyId, Table};
+use restaurant::inventory::Inventory;
+use restaurant::journal::Journal;
+use restaurant::kitchen::{RemakeReason, Station, TicketId};
+use restaurant::menu::{Date, Menu};
+use restaurant::report::DayReport;
+use restaurant::reservations::{ReservationId, ReservationRules, ReservationStatus};
+use restaurant::service::{Applied, Event, OrderState, Restaurant};
+use restaurant::serving::payment::{Bill, Money, Payment, Rounding, Split, TaxPolicy, Tip};
+use restaurant::serving::{OrderId, OrderLine};
+use restaurant::Appetizer;
+
+const HELP: &str = "\
+commands:
+  time <minute>                        set the clock, minutes since opening
+  waitlist add <name> <size>           put a party on the waitlist
+  waitlist [list]                      show waiting parties and their estimated wait
+  waitlist leave <party>               a party gave up waiting
+  reserve <name> <size> <min> [table]  book a table for a later slot
+  reservations                         upcoming bookings and the tables held for them
+  arrive <reservation>                 a party that booked checked in
+  cancel <reservation>                 a booking was called off
+  noshows                              give up on bookings past their grace period
+  seat <party> | seat next             give a party the best free table
+  clear <table>                        the party at a table has left
+  order <table> <item>...              items are soup, salad or breakfast:<toast>
+  kitchen                              show pending tickets, late ones are marked
+  kitchen fire <order>                 send an order to the kitchen, using up ingredients
+  kitchen done <ticket>                a ticket is cooked
+  kitchen remake <ticket> <reason>     wrong-item, undercooked, overcooked, cold, allergy or dropped
+  serve <order>                        serve an order once its tickets are done
+  pay <order> [split even <n> | split items <i,j> <k>...] [tip <p>% | tip <amount>]
+  stock                                ingredient levels and sold out dishes
+  restock <ingredient> <quantity>      a delivery came in
+  report                               a summary of the day so far
+  report day                           end of day sales and service figures
+  report csv [file]                    the same figures as csv, printed or saved
+  help
+  quit";
+
+struct Options {
+    tables: Vec<u32>,
+    turn_time: u32,
+    tax: u32,
+    date: Option<Date>,
+    script: Option<String>,
+    journal: Option<String>,
+    stock: Option<String>,
+    overbook: Option<u32>,
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: restaurant [--tables 2,2,4,4,6] [--turn <minutes>] [--tax <percent>] \
+         [--date YYYY-MM-DD] [--journal <dir>] [--stock <file>] \
+         [--overbook <n>] [--script <file>]"
+    );
+    process::exit(2);
+}
+
+fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        tables: vec![2, 2, 4, 4, 6],
+        turn_time: 45,
+        tax: 825,
+        date: None,
+        script: None,
+        journal: None,
+        stock: None,
+        overbook: None,
+    };
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--tables" => {
+                options.tables = value()?
+                    .split(',')
+                    .map(|seats| parse_number(seats, "seat count"))
+                    .collect::<Result<_, _>>()?
+            }
+            "--turn" => options.turn_time = parse_number(&value()?, "turn time")?,
+            "--tax" => options.tax = parse_percent(&value()?)?,
+            "--date" => options.date = Some(parse_date(&value()?)?),
+            "--journal" => options.journal = Some(value()?),
+            "--script" => options.script = Some(value()?),
+            "--stock" => options.stock = Some(value()?),
+            "--overbook" => options.overbook = Some(parse_number(&value()?, "overbook limit")?),
+            "-h" | "--help" => usage(),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_number(text: &str, what: &str) -> Result<u32, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("{} is not a valid {}", text, what))
+}
+
+// "8.25" or "8.25%" becomes 825 basis points, no floats involved
+fn parse_percent(text: &str) -> Result<u32, String> {
+    let cents = parse_cents(text.trim_end_matches('%'))
+        .map_err(|_| format!("{} is not a valid percentage", text))?;
+    u32::try_from(cents).map_err(|_| format!("{} is not a valid percentage", text))
+}
+
+// "12", "12.5" and "12.50" are all whole cents
+fn parse_cents(text: &str) -> Result<u64, String> {
+    let invalid = || format!("{} is not a valid amount", text);
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if whole.is_empty() || fraction.len() > 2 {
+        return Err(invalid());
+    }
+    let whole: u64 = whole.parse().map_err(|_| invalid())?;
+    let fraction: u64 = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u64>().map_err(|_| invalid())? * 10,
+        _ => fraction.parse().map_err(|_| invalid())?,
+    };
+    Ok(whole * 100 + fraction)
+}
+
+fn parse_date(text: &str) -> Result<Date, String> {
+    let invalid = || format!("{} is not a YYYY-MM-DD date", text);
+    let parts: Vec<&str> = text.split('-').collect();
+    if parts.len() != 3 {
+        return Err(invalid());
+    }
+    let year = parts[0].parse().map_err(|_| invalid())?;
+    let month = parts[1].parse().map_err(|_| invalid())?;
+    let day = parts[2].parse().map_err(|_| invalid())?;
+    Date::new(year, month, day).map_err(|e| e.to_string())
+}
+
+// days since 1970-01-01 to a calendar date, Howard Hinnant's civil_from_days
+fn today() -> Date {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400 + (month <= 2) as i64) as i32;
+    Date::new(year, month, day).expect("civil_from_days gives valid dates")
+}
+
+// the in-memory restaurant or one backed by a journal, commands don't care which
+enum Backend {
+    Memory(Restaurant),
+    Journal(Journal),
+}
+
+impl Backend {
+    fn restaurant(&self) -> &Restaurant {
+        match self {
+            Backend::Memory(restaurant) => restaurant,
+            Backend::Journal(journal) => journal.restaurant(),
+        }
+    }
+
+    fn record(&mut self, event: Event) -> Result<Applied, String> {
+        match self {
+            Backend::Memory(restaurant) => restaurant.apply(&event).map_err(|e| e.to_string()),
+            Backend::Journal(journal) => {
+                let applied = journal.record(event).map_err(|e| e.to_string());
+                // the event is on disk either way, only recovery gets slower
+                if let Some(e) = journal.take_snapshot_error() {
+                    eprintln!("could not write a snapshot: {}", e);
+                }
+                applied
+            }
+        }
+    }
+}
+
+struct Session {
+    backend: Backend,
+    date: Date,
+    tax: TaxPolicy,
+    now: u32,
+}
+
+impl Session {
+    fn menu(&self) -> &Menu {
+        self.backend.restaurant().menu()
+    }
+
+    /// Runs one command line, `Ok(None)` means quit.
+    fn run(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let output = match words.as_slice() {
+            [] => String::new(),
+            ["quit"] | ["exit"] => return Ok(None),
+            ["help"] => HELP.to_string(),
+            ["time", minute] => {
+                self.now = parse_number(minute, "minute")?;
+                format!("the time is now {}", self.now)
+            }
+            ["waitlist", "add", name, size] => self.add_party(name, size)?,
+            ["waitlist"] | ["waitlist", "list"] => self.list_waitlist(),
+            ["reserve", name, size, slot, table @ ..] if table.len() <= 1 => {
+                self.reserve(name, size, slot, table.first())?
+            }
+            ["reservations"] => self.list_reservations(),
+            ["arrive", reservation] => {
+                let reservation = self.reservation_id(reservation)?;
+                match self.record(Event::ReservationArrived {
+                    reservation,
+                    at: self.now,
+                })? {
+                    Applied::Party(party) => format!(
+                        "reservation {} checked in as party {}",
+                        reservation.0, party.0
+                    ),
+                    other => unexpected(other),
+                }
+            }
+            ["cancel", reservation] => {
+                let reservation = self.reservation_id(reservation)?;
+                self.record(Event::ReservationCancelled {
+                    reservation,
+                    at: self.now,
+                })?;
+                format!("reservation {} cancelled", reservation.0)
+            }
+            ["noshows"] => match self.record(Event::NoShowsMarked { at: self.now })? {
+                Applied::NoShows(ids) if ids.is_empty() => "no bookings are overdue".to_string(),
+                Applied::NoShows(ids) => ids
+                    .iter()
+                    .map(|id| format!("reservation {} is a no-show", id.0))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                other => unexpected(other),
+            },
+            ["waitlist", "leave", party] => {
+                let party = self.party(party)?;
+                self.record(Event::PartyLeft {
+                    party,
+                    at: self.now,
+                })?;
+                format!("party {} left the waitlist", party.0)
+            }
+            ["seat", "next"] => {
+                let party = self
+                    .backend
+                    .restaurant()
+                    .waitlist()
+                    .next_seatable(self.now)
+                    .ok_or("no waiting party fits a free table")?;
+                self.seat(party)?
+            }
+            ["seat", party] => {
+                let party = self.party(party)?;
+                self.seat(party)?
+            }
+            ["clear", table] => {
+                let table = parse_number(table, "table")?;
+                match self.record(Event::TableCleared {
+                    table,
+                    at: self.now,
+                })? {
+                    Applied::Cleared(seating) => format!(
+                        "table {} cleared after {} min",
+                        table,
+                        self.now.saturating_sub(seating.seated_at)
+                    ),
+                    other => unexpected(other),
+                }
+            }
+            ["order", table, items @ ..] if !items.is_empty() => self.order(table, items)?,
+            ["kitchen"] => self.list_kitchen(),
+            ["kitchen", "fire", order] => {
+                let order = self.order_id(order)?;
+                let raised = self.backend.restaurant().inventory().alerts().len();
+                let mut output = match self.record(Event::OrderFired {
+                    order,
+                    at: self.now,
+                })? {
+                    Applied::Tickets(tickets) => format!(
+                        "order {} fired as {}",
+                        order.0,
+                        tickets
+                            .iter()
+                            .map(|t| format!("ticket {}", t.0))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    other => unexpected(other),
+                };
+                for alert in &self.backend.restaurant().inventory().alerts()[raised..] {
+                    output.push_str(&format!("\nalert: {}", alert));
+                }
+                output
+            }
+            ["kitchen", "done", ticket] => {
+                let ticket = TicketId(parse_number(ticket, "ticket")?.into());
+                self.record(Event::TicketCompleted {
+                    ticket,
+                    at: self.now,
+                })?;
+                format!("ticket {} done", ticket.0)
+            }
+            ["kitchen", "remake", ticket, reason] => {
+                let ticket = TicketId(parse_number(ticket, "ticket")?.into());
+                let reason = parse_reason(reason)?;
+                match self.record(Event::TicketRemade {
+                    ticket,
+                    reason,
+                    at: self.now,
+                })? {
+                    Applied::Ticket(remake) => {
+                        format!("ticket {} remade as ticket {}", ticket.0, remake.0)
+                    }
+                    other => unexpected(other),
+                }
+            }
+            ["serve", order] => {
+                let order = self.order_id(order)?;
+                self.record(Event::OrderServed {
+                    order,
+                    at: self.now,
+                })?;
+                format!("order {} served", order.0)
+            }
+            ["pay", order, rest @ ..] => self.pay(order, rest)?,
+            ["stock"] => self.list_stock(),
+            ["restock", ingredient, quantity] => {
+                let quantity = parse_number(quantity, "quantity")?;
+                self.record(Event::Restocked {
+                    ingredient: ingredient.to_string(),
+                    quantity,
+                    at: self.now,
+                })?;
+                let on_hand = self.backend.restaurant().inventory().on_hand(ingredient);
+                format!("{} {} on hand", on_hand.unwrap_or(0), ingredient)
+            }
+            ["report"] => self.report(),
+            ["report", "day"] => self.day_report()?.to_table().trim_end().to_string(),
+            ["report", "csv"] => self.day_report()?.to_csv().trim_end().to_string(),
+            ["report", "csv", path] => {
+                fs::write(path, self.day_report()?.to_csv()).map_err(|e| e.to_string())?;
+                format!("report written to {}", path)
+            }
+            _ => return Err(format!("unknown command: {} (try help)", line.trim())),
+        };
+        Ok(Some(output))
+    }
+
+    fn record(&mut self, event: Event) -> Result<Applied, String> {
+        self.backend.record(event)
+    }
+
+    fn party(&self, text: &str) -> Result<PartyId, String> {
+        Ok(PartyId(parse_number(text, "party")?.into()))
+    }
+
+    fn order_id(&self, text: &str) -> Result<OrderId, String> {
+        Ok(OrderId(parse_number(text, "order")?.into()))
+    }
+
+    fn add_party(&mut self, name: &str, size: &str) -> Result<String, String> {
+        let size = parse_number(size, "party size")?;
+        let party = match self.record(Event::PartyAdded {
+            name: name.to_string(),
+            size,
+            at: self.now,
+        })? {
+            Applied::Party(party) => party,
+            other => return Ok(unexpected(other)),
+        };
+        let wait = self
+            .backend
+            .restaurant()
+            .waitlist()
+            .estimate_wait(party, self.now)
+            .map_err(|e| e.to_string())?;
+        Ok(format!(
+            "party {}: {} ({}), estimated wait {} min",
+            party.0, name, size, wait
+        ))
+    }
+
+    fn reservation_id(&self, text: &str) -> Result<ReservationId, String> {
+        Ok(ReservationId(parse_number(text, "reservation")?.into()))
+    }
+
+    fn reserve(
+        &mut self,
+        name: &str,
+        size: &str,
+        slot: &str,
+        table: Option<&&str>,
+    ) -> Result<String, String> {
+        let size = parse_number(size, "party size")?;
+        let slot = parse_number(slot, "minute")?;
+        let table = table.map(|t| parse_number(t, "table")).transpose()?;
+        let reservation = match self.record(Event::ReservationMade {
+            name: name.to_string(),
+            size,
+            slot,
+            table,
+            at: self.now,
+        })? {
+            Applied::Reservation(reservation) => reservation,
+            other => return Ok(unexpected(other)),
+        };
+        let booked = self
+            .backend
+            .restaurant()
+            .waitlist()
+            .reservations()
+            .get(reservation)
+            .map_err(|e| e.to_string())?;
+        Ok(match booked.table {
+            Some(table) => format!(
+                "reservation {}: {} ({}) at {}, table {}",
+                reservation.0, name, size, slot, table
+            ),
+            None => format!(
+                "reservation {}: {} ({}) at {}, overbooked",
+                reservation.0, name, size, slot
+            ),
+        })
+    }
+
+    fn list_reservations(&self) -> String {
+        let waitlist = self.backend.restaurant().waitlist();
+        let held = waitlist.held_tables(self.now);
+        let lines: Vec<String> = waitlist
+            .reservations()
+            .upcoming()
+            .iter()
+            .map(|r| {
+                let table = match r.table {
+                    Some(table) if held.contains(&(table, r.id)) => {
+                        format!("table {}, held", table)
+                    }
+                    Some(table) => format!("table {}", table),
+                    None => "overbooked".to_string(),
+                };
+                let status = match r.status {
+                    ReservationStatus::Arrived(party) => format!(", arrived as party {}", party.0),
+                    _ => String::new(),
+                };
+                format!(
+                    "reservation {}: {} ({}) at {}, {}{}",
+                    r.id.0, r.name, r.size, r.at, table, status
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            "no upcoming reservations".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+
+    fn list_waitlist(&self) -> String {
+        let waitlist = self.backend.restaurant().waitlist();
+        let lines: Vec<String> = waitlist
+            .waiting()
+            .map(|p| {
+                let wait = waitlist.estimate_wait(p.id, self.now).unwrap_or(0);
+                format!(
+                    "party {}: {} ({}), waiting {} min, about {} min to go",
+                    p.id.0,
+                    p.name,
+                    p.size,
+                    self.now.saturating_sub(p.arrived_at),
+                    wait
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            "nobody is waiting".to_string()
+        } else {
+            lines.join("\n")
+        }
+    }
+
+    fn seat(&mut self, party: PartyId) -> Result<String, String> {
+        match self.record(Event::PartySeated {
+            party,
+            at: self.now,
+        })? {
+            Applied::Seated(seating) => Ok(format!(
+                "party {} ({}) seated at table {} ({} seats)",
+                party.0, seating.party.name, seating.table.number, seating.table.seats
+            )),
+            other => Ok(unexpected(other)),
+        }
+    }
+
+    fn order(&mut self, table: &str, items: &[&str]) -> Result<String, String> {
+        let table = parse_number(table, "table")?;
+        let lines = items
+            .iter()
+            .map(|item| self.order_line(item))
+            .collect::<Result<Vec<OrderLine>, _>>()?;
+        let inventory = self.backend.restaurant().inventory();
+        if let Some(line) = lines.iter().find(|l| !inventory.is_available(l.menu_key())) {
+            return Err(format!("{} is sold out", line.menu_key()));
+        }
+        let order = match self.record(Event::OrderTaken {
+            table,
+            lines,
+            at: self.now,
+        })? {
+            Applied::Order(order) => order,
+            other => return Ok(unexpected(other)),
+        };
+        let names = match &self
+            .backend
+            .restaurant()
+            .order(order)
+            .map_err(|e| e.to_string())?
+            .state
+        {
+            OrderState::Taken(taken) => Bill::for_order(taken, self.menu())
+                .map_err(|e| e.to_string())?
+                .lines()
+                .iter()
+                .map(|l| l.name.clone())
+                .collect::<Vec<_>>()
+                .join(", "),
+            other => other.name().to_string(),
+        };
+        Ok(format!("order {} for table {}: {}", order.0, table, names))
+    }
+
+    fn order_line(&self, item: &str) -> Result<OrderLine, String> {
+        match item.split_once(':') {
+            Some(("breakfast", toast)) => self
+                .menu()
+                .breakfast(toast, self.date)
+                .map(OrderLine::from)
+                .map_err(|e| e.to_string()),
+            None if item == "soup" => Ok(Appetizer::Soup.into()),
+            None if item == "salad" => Ok(Appetizer::Salad.into()),
+            None if item == "breakfast" => {
+                Err("breakfast needs a toast, like breakfast:Rye".into())
+            }
+            _ => Err(format!("{} is not on the menu", item)),
+        }
+    }
+
+    fn list_stock(&self) -> String {
+        let inventory = self.backend.restaurant().inventory();
+        let mut lines: Vec<String> = inventory
+            .stock()
+            .iter()
+            .map(|(ingredient, stock)| {
+                let low = stock.low_at.is_some_and(|low| stock.on_hand <= low);
+                format!(
+                    "{}: {}{}",
+                    ingredient,
+                    stock.on_hand,
+                    if low { ", LOW" } else { "" }
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("nothing has been counted".to_string());
+        }
+        let sold_out: Vec<&str> = inventory.unavailable().collect();
+        if !sold_out.is_empty() {
+            lines.push(format!("sold out: {}", sold_out.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn list_kitchen(&self) -> String {
+        let pending = self.backend.restaurant().kitchen().pending();
+        if pending.is_empty() {
+            return "the kitchen is clear".to_string();
+        }
+        pending
+            .iter()
+            .map(|t| {
+                let station = match t.station {
+                    Station::Grill => "grill",
+                    Station::Cold => "cold",
+                };
+                format!(
+                    "ticket {}: table {}, {}, {:?} priority, due at {}{}",
+                    t.id.0,
+                    t.table,
+                    station,
+                    t.priority,
+                    t.due_at(),
+                    if t.is_late(self.now) { ", LATE" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn pay(&mut self, order: &str, rest: &[&str]) -> Result<String, String> {
+        let order = self.order_id(order)?;
+        let (split, tip) = parse_payment(rest)?;
+        let payment = Payment {
+            split,
+            tip,
+            tax: self.tax,
+        };
+        self.record(Event::OrderPaid {
+            order,
+            payment,
+            at: self.now,
+        })?;
+        let text = match &self
+            .backend
+            .restaurant()
+            .order(order)
+            .map_err(|e| e.to_string())?
+            .state
+        {
+            OrderState::Paid(paid) => paid
+                .receipts()
+                .iter()
+                .map(|r| r.to_text())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            other => unreachable!("a paid order is {}", other.name()),
+        };
+        Ok(format!("order {} paid\n{}", order.0, text.trim_end()))
+    }
+
+    fn day_report(&self) -> Result<DayReport, String> {
+        DayReport::new(self.backend.restaurant()).map_err(|e| e.to_string())
+    }
+
+    fn report(&self) -> String {
+        let restaurant = self.backend.restaurant();
+        let mut by_state = [0; 4];
+        let mut revenue = Money(0);
+        for tracked in restaurant.orders().values() {
+            let slot = match &tracked.state {
+                OrderState::Taken(_) => 0,
+                OrderState::InKitchen(_) => 1,
+                OrderState::Served(_) => 2,
+                OrderState::Paid(paid) => {
+                    revenue = revenue + paid.receipts().iter().map(|r| r.total).sum();
+                    3
+                }
+            };
+            by_state[slot] += 1;
+        }
+        format!(
+            "parties seated: {}\nparties waiting: {}\nparties that left: {}\n\
+             orders taken: {}, in kitchen: {}, served: {}, paid: {}\n\
+             tickets pending: {}, late: {}, remakes: {}\nrevenue: {}",
+            restaurant.seated_count(),
+            restaurant.waitlist().waiting().count(),
+            restaurant.left().len(),
+            by_state[0],
+            by_state[1],
+            by_state[2],
+            by_state[3],
+            restaurant.kitchen().pending().len(),
+            restaurant.kitchen().late(self.now).len(),
+            restaurant.kitchen().remakes().len(),
+            revenue
+        )
+    }
+}
+
+fn unexpected(applied: Applied) -> String {
+    format!("unexpected result {:?}", applied)
+}
+
+fn parse_reason(text: &str) -> Result<RemakeReason, String> {
+    match text {
+        "wrong-item" => Ok(RemakeReason::WrongItem),
+        "undercooked" => Ok(RemakeReason::Undercooked),
+        "overcooked" => Ok(RemakeReason::Overcooked),
+        "cold" => Ok(RemakeReason::Cold),
+        "allergy" => Ok(RemakeReason::Allergy),
+        "dropped" => Ok(RemakeReason::Dropped),
+        other => Err(format!("{} is not a remake reason", other)),
+    }
+}
+
+fn parse_payment(words: &[&str]) -> Result<(Split, Tip), String> {
+    let mut split = Split::Whole;
+    let mut tip = Tip::None;
+    let mut words = words;
+    while !words.is_empty() {
+        match words {
+            ["split", "even", guests, rest @ ..] => {
+                split = Split::Even(parse_number(guests, "guest count")?);
+                words = rest;
+            }
+            ["split", "items", rest @ ..] => {
+                let shares: Vec<&str> = rest.iter().take_while(|w| **w != "tip").copied().collect();
+                split = Split::ByItem(
+                    shares
+                        .iter()
+                        .map(|share| {
+                            share
+                                .split(',')
+                                .map(|i| parse_number(i, "line").map(|i| i as usize))
+                                .collect::<Result<Vec<_>, _>>()
+                        })
+                        .collect::<Result<_, _>>()?,
+                );
+                words = &rest[shares.len()..];
+            }
+            ["tip", amount, rest @ ..] => {
+                tip = if amount.ends_with('%') {
+                    Tip::Percent(parse_percent(amount)?)
+                } else {
+                    Tip::Fixed(Money(parse_cents(amount)?))
+                };
+                words = rest;
+            }
+            _ => return Err(format!("can't understand '{}'", words.join(" "))),
+        }
+    }
+    Ok((split, tip))
+}
+
+fn main() {
+    let options = parse_options(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        usage()
+    });
+
+    let tables: Vec<Table> = options
+        .tables
+        .iter()
+        .enumerate()
+        .map(|(i, &seats)| Table {
+            number: i as u32 + 1,
+            seats,
+        })
+        .collect();
+    let backend = match &options.journal {
+        Some(dir) if std::path::Path::new(dir).join("events.log").exists() => {
+            Journal::open(dir).map(Backend::Journal)
+        }
+        Some(dir) => {
+            Journal::create(dir, tables, options.turn_time, Menu::house()).map(Backend::Journal)
+        }
+        None => Restaurant::open(tables, options.turn_time, Menu::house())
+            .map(Backend::Memory)
+            .map_err(|e| e.into()),
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("could not open the restaurant: {}", e);
+        process::exit(1);
+    });
+
+    let mut session = Session {
+        backend,
+        date: options.date.unwrap_or_else(today),
+        tax: TaxPolicy {
+            rate: options.tax,
+            rounding: Rounding::HalfUp,
+        },
+        now: 0,
+    };
+    if let Some(overbook) = options.overbook {
+        let rules = ReservationRules {
+            overbook,
+            ..session
+                .backend
+                .restaurant()
+                .waitlist()
+                .reservations()
+                .rules()
+        };
+        if let Err(e) = session.record(Event::ReservationRulesSet { rules }) {
+            eprintln!("could not set the overbooking limit: {}", e);
+            process::exit(1);
+        }
+    }
+    // counting replaces whatever stock a reopened journal had
+    if let Some(path) = &options.stock {
+        let inventory = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Inventory::from_toml(session.menu(), &text).map_err(|e| e.to_string()))
+            .and_then(|inventory| session.record(Event::StockCounted { inventory }));
+        if let Err(e) = inventory {
+            eprintln!("could not count stock from {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+
+    let (input, echo): (Box<dyn BufRead>, bool) = match &options.script {
+        Some(path) => match File::open(path) {
+            Ok(file) => (Box::new(BufReader::new(file)), true),
+            Err(e) => {
+                eprintln!("could not read {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => (Box::new(BufReader::new(io::stdin())), false),
+    };
+    let interactive = !echo && io::stdin().is_terminal();
+
+    let mut failed = false;
+    let mut lines = input.lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().unwrap();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => {
+                eprintln!("could not read input: {}", e);
+                process::exit(1);
+            }
+            None => break,
+        };
+        // scripts can have comments and blank lines
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        if echo && !line.trim().is_empty() {
+            println!("> {}", line.trim());
+        }
+        match session.run(&line) {
+            Ok(Some(output)) if !output.is_empty() => println!("{}", output),
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => {
+                println!("error: {}", e);
+                failed = true;
+            }
+        }
+    }
+    if echo && failed {
+        process::exit(1);
+    }
+}
@@ -1,2 +1,341 @@
 // we dd this module to the module tree inside courier.rs
-pub fn deliver_ordes(){}
\ No newline at end of file
+// delivery planning: orders go to intersections of a road map, couriers take batches
+// travel times come from dijkstra and every batch is ordered nearest-neighbour first
+// ties are always broken by the smaller id so the same input gives the same plan
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fmt;
+
+/// An intersection on the road map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+/// Roads between intersections, weighted in minutes and usable both ways.
+#[derive(Debug, Clone, Default)]
+pub struct RoadMap {
+    roads: BTreeMap<NodeId, Vec<(NodeId, u32)>>,
+}
+
+impl RoadMap {
+    pub fn new() -> RoadMap {
+        RoadMap::default()
+    }
+
+    /// A `width` by `height` grid where node `y * width + x` sits at column `x`, row `y`.
+    pub fn grid(width: u32, height: u32, minutes_per_block: u32) -> RoadMap {
+        let mut map = RoadMap::new();
+        for y in 0..height {
+            for x in 0..width {
+                let node = NodeId(y * width + x);
+                map.roads.entry(node).or_default();
+                if x + 1 < width {
+                    map.add_road(node, NodeId(node.0 + 1), minutes_per_block);
+                }
+                if y + 1 < height {
+                    map.add_road(node, NodeId(node.0 + width), minutes_per_block);
+                }
+            }
+        }
+        map
+    }
+
+    pub fn add_road(&mut self, a: NodeId, b: NodeId, minutes: u32) {
+        self.roads.entry(a).or_default().push((b, minutes));
+        self.roads.entry(b).or_default().push((a, minutes));
+    }
+
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.roads.contains_key(&node)
+    }
+
+    /// Fastest travel time from `from` to every reachable node.
+    pub fn travel_times(&self, from: NodeId) -> HashMap<NodeId, u32> {
+        let mut best = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, from)));
+        while let Some(Reverse((minutes, node))) = queue.pop() {
+            if best.contains_key(&node) {
+                continue;
+            }
+            best.insert(node, minutes);
+            for &(next, cost) in self.roads.get(&node).into_iter().flatten() {
+                if !best.contains_key(&next) {
+                    queue.push(Reverse((minutes + cost, next)));
+                }
+            }
+        }
+        best
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub order: u64,
+    pub destination: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Courier {
+    pub name: String,
+    /// Most orders carried on one trip.
+    pub capacity: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stop {
+    pub order: u64,
+    pub destination: NodeId,
+    /// Minutes since opening.
+    pub eta: u32,
+}
+
+/// One trip of one courier, leaving and coming back to the restaurant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub courier: String,
+    pub departs_at: u32,
+    pub stops: Vec<Stop>,
+    pub returns_at: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    NoCouriers,
+    ZeroCapacity(String),
+    UnknownNode(NodeId),
+    Unreachable { order: u64, destination: NodeId },
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeliveryError::NoCouriers => write!(f, "there are no couriers to deliver with"),
+            DeliveryError::ZeroCapacity(name) => write!(f, "courier {} can't carry anything", name),
+            DeliveryError::UnknownNode(node) => write!(f, "node {} is not on the map", node.0),
+            DeliveryError::Unreachable { order, destination } => write!(
+                f,
+                "order {} goes to node {} which can't be reached",
+                order, destination.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+/// Plans every delivery starting at minute `start`.
+///
+/// Whenever a courier is back at `depot` it takes up to `capacity` orders,
+/// always driving to the nearest undelivered one next. Couriers that are free
+/// at the same time go in the order they were given.
+pub fn deliver_ordes(
+    map: &RoadMap,
+    depot: NodeId,
+    couriers: &[Courier],
+    deliveries: &[Delivery],
+    start: u32,
+) -> Result<Vec<Route>, DeliveryError> {
+    if couriers.is_empty() {
+        return Err(DeliveryError::NoCouriers);
+    }
+    if let Some(courier) = couriers.iter().find(|c| c.capacity == 0) {
+        return Err(DeliveryError::ZeroCapacity(courier.name.clone()));
+    }
+    if !map.contains(depot) {
+        return Err(DeliveryError::UnknownNode(depot));
+    }
+
+    let mut times = HashMap::new();
+    times.insert(depot, map.travel_times(depot));
+    for delivery in deliveries {
+        if !times[&depot].contains_key(&delivery.destination) {
+            return Err(DeliveryError::Unreachable {
+                order: delivery.order,
+                destination: delivery.destination,
+            });
+        }
+        times
+            .entry(delivery.destination)
+            .or_insert_with(|| map.travel_times(delivery.destination));
+    }
+
+    let mut left: Vec<&Delivery> = deliveries.iter().collect();
+    left.sort_by_key(|d| d.order);
+    let mut free_at = vec![start; couriers.len()];
+    let mut routes = Vec::new();
+
+    while !left.is_empty() {
+        let (index, departs_at) = free_at
+            .iter()
+            .copied()
+            .enumerate()
+            .min_by_key(|&(i, at)| (at, i))
+            .unwrap();
+        let courier = &couriers[index];
+
+        let mut here = depot;
+        let mut clock = departs_at;
+        let mut stops = Vec::new();
+        while stops.len() < courier.capacity && !left.is_empty() {
+            let (pos, minutes) = left
+                .iter()
+                .enumerate()
+                .map(|(pos, d)| (pos, times[&here][&d.destination]))
+                .min_by_key(|&(pos, minutes)| (minutes, left[pos].order))
+                .unwrap();
+            let delivery = left.remove(pos);
+            clock += minutes;
+            here = delivery.destination;
+            stops.push(Stop {
+                order: delivery.order,
+                destination: here,
+                eta: clock,
+            });
+        }
+        let returns_at = clock + times[&here][&depot];
+        free_at[index] = returns_at;
+        routes.push(Route {
+            courier: courier.name.clone(),
+            departs_at,
+            stops,
+            returns_at,
+        });
+    }
+    Ok(routes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn courier(name: &str, capacity: usize) -> Courier {
+        Courier {
+            name: name.to_string(),
+            capacity,
+        }
+    }
+
+    fn delivery(order: u64, node: u32) -> Delivery {
+        Delivery {
+            order,
+            destination: NodeId(node),
+        }
+    }
+
+    #[test]
+    fn dijkstra_takes_the_fast_road() {
+        let mut map = RoadMap::new();
+        map.add_road(NodeId(0), NodeId(1), 10);
+        map.add_road(NodeId(0), NodeId(2), 2);
+        map.add_road(NodeId(2), NodeId(1), 3);
+        let times = map.travel_times(NodeId(0));
+        assert_eq!(times[&NodeId(1)], 5);
+        assert_eq!(times[&NodeId(2)], 2);
+    }
+
+    #[test]
+    fn grid_distance_is_manhattan() {
+        let map = RoadMap::grid(4, 3, 2);
+        let times = map.travel_times(NodeId(0));
+        // column 3, row 2
+        assert_eq!(times[&NodeId(11)], (3 + 2) * 2);
+        assert_eq!(times.len(), 12);
+    }
+
+    #[test]
+    fn nearest_stop_goes_first() {
+        let map = RoadMap::grid(5, 5, 1);
+        let routes = deliver_ordes(
+            &map,
+            NodeId(0),
+            &[courier("Ann", 3)],
+            &[delivery(1, 24), delivery(2, 2), delivery(3, 4)],
+            60,
+        )
+        .unwrap();
+        assert_eq!(routes.len(), 1);
+        let route = &routes[0];
+        let orders: Vec<u64> = route.stops.iter().map(|s| s.order).collect();
+        assert_eq!(orders, vec![2, 3, 1]);
+        let etas: Vec<u32> = route.stops.iter().map(|s| s.eta).collect();
+        assert_eq!(etas, vec![62, 64, 68]);
+        assert_eq!(route.returns_at, 76);
+    }
+
+    #[test]
+    fn batches_are_split_by_capacity_and_courier() {
+        let map = RoadMap::grid(5, 1, 1);
+        let deliveries = [
+            delivery(1, 1),
+            delivery(2, 2),
+            delivery(3, 3),
+            delivery(4, 4),
+        ];
+        let routes = deliver_ordes(
+            &map,
+            NodeId(0),
+            &[courier("Ann", 2), courier("Bob", 1)],
+            &deliveries,
+            0,
+        )
+        .unwrap();
+
+        let summary: Vec<(&str, u32, Vec<u64>)> = routes
+            .iter()
+            .map(|r| {
+                (
+                    r.courier.as_str(),
+                    r.departs_at,
+                    r.stops.iter().map(|s| s.order).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Ann", 0, vec![1, 2]),
+                ("Bob", 0, vec![3]),
+                ("Ann", 4, vec![4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn plans_are_deterministic() {
+        let map = RoadMap::grid(6, 6, 3);
+        let deliveries: Vec<Delivery> = (1..=9).map(|i| delivery(i, (i as u32 * 7) % 36)).collect();
+        let couriers = [courier("Ann", 2), courier("Bob", 3)];
+        let first = deliver_ordes(&map, NodeId(14), &couriers, &deliveries, 0).unwrap();
+        let mut reversed = deliveries.clone();
+        reversed.reverse();
+        let second = deliver_ordes(&map, NodeId(14), &couriers, &reversed, 0).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.iter().map(|r| r.stops.len()).sum::<usize>(), 9);
+    }
+
+    #[test]
+    fn bad_plans_are_rejected() {
+        let mut map = RoadMap::grid(2, 2, 1);
+        map.add_road(NodeId(10), NodeId(11), 1);
+        let ann = [courier("Ann", 1)];
+        assert_eq!(
+            deliver_ordes(&map, NodeId(0), &[], &[], 0),
+            Err(DeliveryError::NoCouriers)
+        );
+        assert_eq!(
+            deliver_ordes(&map, NodeId(0), &[courier("Bob", 0)], &[], 0),
+            Err(DeliveryError::ZeroCapacity("Bob".to_string()))
+        );
+        assert_eq!(
+            deliver_ordes(&map, NodeId(7), &ann, &[], 0),
+            Err(DeliveryError::UnknownNode(NodeId(7)))
+        );
+        assert_eq!(
+            deliver_ordes(&map, NodeId(0), &ann, &[delivery(5, 11)], 0),
+            Err(DeliveryError::Unreachable {
+                order: 5,
+                destination: NodeId(11)
+            })
+        );
+    }
+}
@@ -0,0 +1,5 @@
+// front_of_house used to be an inline module in lib.rs
+// it grew real code so, like courier, it moved to its own file and each child module got a file under src/front_of_house/
+pub mod hosting;
+pub mod reservations;
+pub mod serving;
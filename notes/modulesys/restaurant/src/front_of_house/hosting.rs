@@ -0,0 +1,607 @@
+// the host keeps the waitlist and decides which party goes to which table
+// times are plain minutes counted from when the restaurant opened so the engine stays deterministic
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::reservations::{
+    Reservation, ReservationBook, ReservationId, ReservationRules, ReservationStatus,
+};
+
+/// Identifies a party for as long as it is waiting or seated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PartyId(pub u64);
+
+/// A group of guests waiting for a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Party {
+    pub id: PartyId,
+    pub name: String,
+    pub size: u32,
+    /// Minutes since opening.
+    pub arrived_at: u32,
+    /// The booking this party checked in for, walk-ins have none.
+    #[serde(default)]
+    pub reservation: Option<ReservationId>,
+}
+
+/// A table on the floor plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    pub number: u32,
+    pub seats: u32,
+}
+
+/// The tables available for seating.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloorPlan {
+    tables: Vec<Table>,
+}
+
+impl FloorPlan {
+    pub fn new(tables: Vec<Table>) -> Result<FloorPlan, HostingError> {
+        let mut seen = Vec::new();
+        for table in &tables {
+            if table.seats == 0 {
+                return Err(HostingError::EmptyTable(table.number));
+            }
+            if seen.contains(&table.number) {
+                return Err(HostingError::DuplicateTable(table.number));
+            }
+            seen.push(table.number);
+        }
+        Ok(FloorPlan { tables })
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    pub fn table(&self, number: u32) -> Option<Table> {
+        self.tables.iter().copied().find(|t| t.number == number)
+    }
+
+    fn largest(&self) -> u32 {
+        self.tables.iter().map(|t| t.seats).max().unwrap_or(0)
+    }
+}
+
+/// A party that has been given a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seating {
+    pub party: Party,
+    pub table: Table,
+    /// Minutes since opening.
+    pub seated_at: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostingError {
+    EmptyTable(u32),
+    DuplicateTable(u32),
+    EmptyName,
+    EmptyParty,
+    PartyTooLarge { size: u32, largest: u32 },
+    UnknownParty(PartyId),
+    UnknownTable(u32),
+    NoTableAvailable(PartyId),
+    TableNotOccupied(u32),
+    NotOnSlot { at: u32, slot_minutes: u32 },
+    InThePast { at: u32, now: u32 },
+    TableTooSmall { table: u32, size: u32 },
+    TableBooked { table: u32, by: ReservationId },
+    FullyBooked { at: u32 },
+    UnknownReservation(ReservationId),
+    ReservationClosed(ReservationId),
+}
+
+impl fmt::Display for HostingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HostingError::EmptyTable(n) => write!(f, "table {} has no seats", n),
+            HostingError::DuplicateTable(n) => write!(f, "table {} is listed twice", n),
+            HostingError::EmptyName => write!(f, "party name must not be empty"),
+            HostingError::EmptyParty => write!(f, "party must have at least one guest"),
+            HostingError::PartyTooLarge { size, largest } => write!(
+                f,
+                "party of {} does not fit the largest table ({} seats)",
+                size, largest
+            ),
+            HostingError::UnknownParty(id) => write!(f, "party {} is not on the waitlist", id.0),
+            HostingError::UnknownTable(n) => write!(f, "table {} is not on the floor plan", n),
+            HostingError::NoTableAvailable(id) => {
+                write!(f, "no free table fits party {}", id.0)
+            }
+            HostingError::TableNotOccupied(n) => write!(f, "table {} is not occupied", n),
+            HostingError::NotOnSlot { at, slot_minutes } => write!(
+                f,
+                "minute {} is not on a {} minute booking slot",
+                at, slot_minutes
+            ),
+            HostingError::InThePast { at, now } => {
+                write!(f, "minute {} is already past, it is minute {}", at, now)
+            }
+            HostingError::TableTooSmall { table, size } => {
+                write!(f, "table {} is too small for a party of {}", table, size)
+            }
+            HostingError::TableBooked { table, by } => {
+                write!(
+                    f,
+                    "table {} is already booked by reservation {}",
+                    table, by.0
+                )
+            }
+            HostingError::FullyBooked { at } => write!(f, "minute {} is fully booked", at),
+            HostingError::UnknownReservation(id) => {
+                write!(f, "reservation {} does not exist", id.0)
+            }
+            HostingError::ReservationClosed(id) => {
+                write!(f, "reservation {} is no longer open", id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostingError {}
+
+/// Waiting parties in arrival order plus the tables they currently occupy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waitlist {
+    floor_plan: FloorPlan,
+    /// Average minutes a table stays occupied, used for wait estimates and bookings.
+    turn_time: u32,
+    queue: VecDeque<Party>,
+    occupied: HashMap<u32, Seating>,
+    next_id: u64,
+    #[serde(default)]
+    book: ReservationBook,
+}
+
+impl Waitlist {
+    pub fn new(floor_plan: FloorPlan, turn_time: u32) -> Waitlist {
+        Waitlist {
+            floor_plan,
+            turn_time,
+            queue: VecDeque::new(),
+            occupied: HashMap::new(),
+            next_id: 1,
+            book: ReservationBook::default(),
+        }
+    }
+
+    /// Changes how bookings are taken from now on, existing bookings stay.
+    pub fn set_reservation_rules(&mut self, rules: ReservationRules) {
+        self.book.set_rules(rules);
+    }
+
+    pub fn reservations(&self) -> &ReservationBook {
+        &self.book
+    }
+
+    pub fn floor_plan(&self) -> &FloorPlan {
+        &self.floor_plan
+    }
+
+    /// Parties still waiting, first arrival first.
+    pub fn waiting(&self) -> impl Iterator<Item = &Party> {
+        self.queue.iter()
+    }
+
+    pub fn seated(&self, table: u32) -> Option<&Seating> {
+        self.occupied.get(&table)
+    }
+
+    pub fn free_tables(&self) -> impl Iterator<Item = Table> + '_ {
+        self.floor_plan
+            .tables()
+            .iter()
+            .copied()
+            .filter(|t| !self.occupied.contains_key(&t.number))
+    }
+
+    pub fn add_to_waitlist(
+        &mut self,
+        name: &str,
+        size: u32,
+        arrived_at: u32,
+    ) -> Result<PartyId, HostingError> {
+        if name.trim().is_empty() {
+            return Err(HostingError::EmptyName);
+        }
+        if size == 0 {
+            return Err(HostingError::EmptyParty);
+        }
+        let largest = self.floor_plan.largest();
+        if size > largest {
+            return Err(HostingError::PartyTooLarge { size, largest });
+        }
+
+        let id = PartyId(self.next_id);
+        self.next_id += 1;
+        self.queue.push_back(Party {
+            id,
+            name: name.trim().to_string(),
+            size,
+            arrived_at,
+            reservation: None,
+        });
+        Ok(id)
+    }
+
+    /// Books `size` guests for the slot starting at minute `at`, on `table`
+    /// or on the smallest table that is free for a whole turn.
+    pub fn reserve(
+        &mut self,
+        name: &str,
+        size: u32,
+        at: u32,
+        table: Option<u32>,
+        now: u32,
+    ) -> Result<ReservationId, HostingError> {
+        if at < now {
+            return Err(HostingError::InThePast { at, now });
+        }
+        self.book
+            .reserve(&self.floor_plan, self.turn_time, name, size, at, table)
+    }
+
+    pub fn cancel_reservation(&mut self, id: ReservationId) -> Result<Reservation, HostingError> {
+        let reservation = self.book.get_mut(id)?;
+        if reservation.status != ReservationStatus::Booked {
+            return Err(HostingError::ReservationClosed(id));
+        }
+        reservation.status = ReservationStatus::Cancelled;
+        Ok(reservation.clone())
+    }
+
+    /// Puts a party that booked on the waitlist, ahead of its booked table.
+    pub fn check_in(&mut self, id: ReservationId, now: u32) -> Result<PartyId, HostingError> {
+        let party = PartyId(self.next_id);
+        let reservation = self.book.get_mut(id)?;
+        if reservation.status != ReservationStatus::Booked {
+            return Err(HostingError::ReservationClosed(id));
+        }
+        reservation.status = ReservationStatus::Arrived(party);
+        self.next_id += 1;
+        self.queue.push_back(Party {
+            id: party,
+            name: reservation.name.clone(),
+            size: reservation.size,
+            arrived_at: now,
+            reservation: Some(id),
+        });
+        Ok(party)
+    }
+
+    /// Gives up on bookings more than the grace period late, freeing their tables.
+    pub fn mark_no_shows(&mut self, now: u32) -> Vec<ReservationId> {
+        self.book.mark_no_shows(now)
+    }
+
+    /// Free tables kept for a booking, with the booking they are kept for.
+    pub fn held_tables(&self, now: u32) -> Vec<(u32, ReservationId)> {
+        self.book
+            .held(now, self.turn_time)
+            .into_iter()
+            .filter(|(table, _)| !self.occupied.contains_key(table))
+            .collect()
+    }
+
+    /// Takes a party off the waitlist without seating it, closing its booking.
+    pub fn remove(&mut self, id: PartyId) -> Result<Party, HostingError> {
+        let pos = self.position(id)?;
+        let party = self.queue.remove(pos).unwrap();
+        if let Some(reservation) = party.reservation {
+            self.book.get_mut(reservation)?.status = ReservationStatus::Left;
+        }
+        Ok(party)
+    }
+
+    /// Estimated minutes from `now` until `id` gets a table.
+    ///
+    /// Plays the queue forward: every party ahead takes the fitting table
+    /// that frees up first and keeps it for `turn_time` minutes. A table held
+    /// for someone else's booking frees up a turn after the booked slot.
+    pub fn estimate_wait(&self, id: PartyId, now: u32) -> Result<u32, HostingError> {
+        let pos = self.position(id)?;
+        let held = self.held_tables(now);
+        let mut free_at: Vec<(Table, u32)> = self
+            .floor_plan
+            .tables()
+            .iter()
+            .map(|t| {
+                let booked = held
+                    .iter()
+                    .filter(|(table, r)| {
+                        *table == t.number && Some(*r) != self.queue[pos].reservation
+                    })
+                    .filter_map(|(_, r)| self.book.get(*r).ok())
+                    .map(|r| r.at.saturating_add(self.turn_time))
+                    .max();
+                let at = match (self.occupied.get(&t.number), booked) {
+                    (Some(seating), _) => now.max(seating.seated_at.saturating_add(self.turn_time)),
+                    (None, Some(at)) => now.max(at),
+                    (None, None) => now,
+                };
+                (*t, at)
+            })
+            .collect();
+
+        let mut wait = 0;
+        for party in self.queue.iter().take(pos + 1) {
+            // every queued party fits some table, add_to_waitlist checked that
+            let slot = free_at
+                .iter_mut()
+                .filter(|(t, _)| t.seats >= party.size)
+                .min_by_key(|(t, at)| (*at, t.seats, t.number))
+                .unwrap();
+            wait = slot.1 - now;
+            slot.1 = slot.1.saturating_add(self.turn_time);
+        }
+        Ok(wait)
+    }
+
+    /// Seats `id` at the smallest free table that holds the whole party.
+    ///
+    /// Tables held for a booking are skipped unless `id` is the party that
+    /// booked it, who gets its own table whenever that one is free.
+    pub fn seat_at_table(&mut self, id: PartyId, now: u32) -> Result<Seating, HostingError> {
+        let pos = self.position(id)?;
+        let table = self
+            .table_for(&self.queue[pos], now)
+            .ok_or(HostingError::NoTableAvailable(id))?;
+        let party = self.queue.remove(pos).unwrap();
+        if let Some(reservation) = party.reservation {
+            self.book.get_mut(reservation)?.status = ReservationStatus::Seated;
+        }
+        let seating = Seating {
+            party,
+            table,
+            seated_at: now,
+        };
+        self.occupied.insert(table.number, seating.clone());
+        Ok(seating)
+    }
+
+    /// The earliest waiting party that fits one of the free tables.
+    pub fn next_seatable(&self, now: u32) -> Option<PartyId> {
+        self.queue
+            .iter()
+            .find(|p| self.table_for(p, now).is_some())
+            .map(|p| p.id)
+    }
+
+    /// Seats the earliest waiting party that fits one of the free tables.
+    pub fn seat_next(&mut self, now: u32) -> Option<Seating> {
+        let id = self.next_seatable(now)?;
+        self.seat_at_table(id, now).ok()
+    }
+
+    /// Frees a table once its party leaves.
+    pub fn clear_table(&mut self, number: u32) -> Result<Seating, HostingError> {
+        if self.floor_plan.table(number).is_none() {
+            return Err(HostingError::UnknownTable(number));
+        }
+        self.occupied
+            .remove(&number)
+            .ok_or(HostingError::TableNotOccupied(number))
+    }
+
+    fn position(&self, id: PartyId) -> Result<usize, HostingError> {
+        self.queue
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or(HostingError::UnknownParty(id))
+    }
+
+    fn table_for(&self, party: &Party, now: u32) -> Option<Table> {
+        let held = self.book.held(now, self.turn_time);
+        let own = held
+            .iter()
+            .find(|(_, reservation)| Some(*reservation) == party.reservation)
+            .map(|(table, _)| *table);
+        if let Some(table) = own.and_then(|n| self.free_tables().find(|t| t.number == n)) {
+            return Some(table);
+        }
+        self.free_tables()
+            .filter(|t| t.seats >= party.size)
+            .filter(|t| !held.iter().any(|(table, _)| *table == t.number))
+            .min_by_key(|t| (t.seats, t.number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waitlist() -> Waitlist {
+        let plan = FloorPlan::new(vec![
+            Table { number: 1, seats: 2 },
+            Table { number: 2, seats: 4 },
+            Table { number: 3, seats: 6 },
+        ])
+        .unwrap();
+        Waitlist::new(plan, 45)
+    }
+
+    #[test]
+    fn rejects_bad_floor_plans() {
+        assert_eq!(
+            FloorPlan::new(vec![Table { number: 1, seats: 0 }]),
+            Err(HostingError::EmptyTable(1))
+        );
+        assert_eq!(
+            FloorPlan::new(vec![
+                Table { number: 1, seats: 2 },
+                Table { number: 1, seats: 4 }
+            ]),
+            Err(HostingError::DuplicateTable(1))
+        );
+    }
+
+    #[test]
+    fn rejects_parties_that_cannot_be_seated() {
+        let mut list = waitlist();
+        assert_eq!(list.add_to_waitlist(" ", 2, 0), Err(HostingError::EmptyName));
+        assert_eq!(list.add_to_waitlist("Ann", 0, 0), Err(HostingError::EmptyParty));
+        assert_eq!(
+            list.add_to_waitlist("Ann", 7, 0),
+            Err(HostingError::PartyTooLarge { size: 7, largest: 6 })
+        );
+    }
+
+    #[test]
+    fn seats_smallest_fitting_table() {
+        let mut list = waitlist();
+        let id = list.add_to_waitlist("Ann", 3, 0).unwrap();
+        let seating = list.seat_at_table(id, 5).unwrap();
+        assert_eq!(seating.table.number, 2);
+        assert_eq!(seating.seated_at, 5);
+        assert_eq!(list.waiting().count(), 0);
+
+        let id = list.add_to_waitlist("Bob", 3, 1).unwrap();
+        assert_eq!(list.seat_at_table(id, 6).unwrap().table.number, 3);
+
+        let id = list.add_to_waitlist("Cat", 3, 2).unwrap();
+        assert_eq!(
+            list.seat_at_table(id, 7),
+            Err(HostingError::NoTableAvailable(id))
+        );
+        assert_eq!(list.waiting().count(), 1);
+    }
+
+    #[test]
+    fn seat_next_skips_parties_that_do_not_fit() {
+        let mut list = waitlist();
+        let big = list.add_to_waitlist("Big", 6, 0).unwrap();
+        list.seat_at_table(big, 0).unwrap();
+        list.add_to_waitlist("Also big", 5, 1).unwrap();
+        let small = list.add_to_waitlist("Small", 2, 2).unwrap();
+
+        let seating = list.seat_next(3).unwrap();
+        assert_eq!(seating.party.id, small);
+        assert_eq!(seating.table.number, 1);
+    }
+
+    #[test]
+    fn estimates_wait_from_parties_ahead() {
+        let mut list = waitlist();
+        let a = list.add_to_waitlist("A", 2, 0).unwrap();
+        let b = list.add_to_waitlist("B", 2, 0).unwrap();
+        let c = list.add_to_waitlist("C", 2, 0).unwrap();
+        let d = list.add_to_waitlist("D", 2, 0).unwrap();
+        assert_eq!(list.estimate_wait(a, 0), Ok(0));
+        assert_eq!(list.estimate_wait(b, 0), Ok(0));
+        assert_eq!(list.estimate_wait(c, 0), Ok(0));
+        assert_eq!(list.estimate_wait(d, 0), Ok(45));
+
+        let e = list.add_to_waitlist("E", 6, 0).unwrap();
+        assert_eq!(list.estimate_wait(e, 0), Ok(45));
+
+        for id in [a, b, c] {
+            list.seat_at_table(id, 0).unwrap();
+        }
+        assert_eq!(list.estimate_wait(d, 10), Ok(35));
+        assert_eq!(list.estimate_wait(e, 10), Ok(35));
+    }
+
+    #[test]
+    fn estimates_count_time_left_on_seated_tables() {
+        let mut list = waitlist();
+        let big = list.add_to_waitlist("Big", 6, 0).unwrap();
+        list.seat_at_table(big, 0).unwrap();
+        let next = list.add_to_waitlist("Next", 5, 20).unwrap();
+        assert_eq!(list.estimate_wait(next, 20), Ok(25));
+        assert_eq!(
+            list.estimate_wait(PartyId(99), 20),
+            Err(HostingError::UnknownParty(PartyId(99)))
+        );
+    }
+
+    #[test]
+    fn clearing_frees_the_table() {
+        let mut list = waitlist();
+        let id = list.add_to_waitlist("Ann", 2, 0).unwrap();
+        list.seat_at_table(id, 0).unwrap();
+        assert_eq!(list.free_tables().count(), 2);
+        assert_eq!(list.clear_table(1).unwrap().party.id, id);
+        assert_eq!(list.clear_table(1), Err(HostingError::TableNotOccupied(1)));
+        assert_eq!(list.clear_table(9), Err(HostingError::UnknownTable(9)));
+        assert_eq!(list.free_tables().count(), 3);
+    }
+
+    #[test]
+    fn booked_tables_are_held_from_walk_ins() {
+        let mut list = waitlist();
+        let booking = list.reserve("Ann", 2, 60, None, 0).unwrap();
+        assert_eq!(list.reservations().get(booking).unwrap().table, Some(1));
+
+        // seated at 30 a walk-in would still be at table 1 when Ann is due
+        let bob = list.add_to_waitlist("Bob", 2, 30).unwrap();
+        assert_eq!(list.held_tables(30), vec![(1, booking)]);
+        assert_eq!(list.seat_at_table(bob, 30).unwrap().table.number, 2);
+
+        let ann = list.check_in(booking, 55).unwrap();
+        let cat = list.add_to_waitlist("Cat", 2, 55).unwrap();
+        assert_eq!(list.next_seatable(55), Some(ann));
+        assert_eq!(list.seat_at_table(ann, 56).unwrap().table.number, 1);
+        assert_eq!(
+            list.reservations().get(booking).unwrap().status,
+            ReservationStatus::Seated
+        );
+        assert_eq!(list.seat_at_table(cat, 56).unwrap().table.number, 3);
+    }
+
+    #[test]
+    fn no_shows_release_their_table() {
+        let mut list = waitlist();
+        let booking = list.reserve("Ann", 2, 60, Some(1), 0).unwrap();
+        let bob = list.add_to_waitlist("Bob", 2, 70).unwrap();
+        assert_eq!(list.held_tables(70), vec![(1, booking)]);
+        assert!(list.mark_no_shows(75).is_empty());
+        assert_eq!(list.mark_no_shows(76), vec![booking]);
+        assert_eq!(list.seat_at_table(bob, 76).unwrap().table.number, 1);
+        assert_eq!(
+            list.check_in(booking, 80),
+            Err(HostingError::ReservationClosed(booking))
+        );
+        assert_eq!(
+            list.reserve("Late", 2, 30, None, 76),
+            Err(HostingError::InThePast { at: 30, now: 76 })
+        );
+    }
+
+    #[test]
+    fn a_party_that_leaves_gives_up_its_booking() {
+        let mut list = waitlist();
+        let booking = list.reserve("Ann", 2, 600, Some(1), 0).unwrap();
+        let ann = list.check_in(booking, 590).unwrap();
+        list.remove(ann).unwrap();
+        assert_eq!(
+            list.reservations().get(booking).unwrap().status,
+            ReservationStatus::Left
+        );
+        assert!(list.held_tables(600).is_empty());
+        assert!(list.mark_no_shows(700).is_empty());
+
+        let bob = list.add_to_waitlist("Bob", 2, 600).unwrap();
+        assert_eq!(list.seat_at_table(bob, 600).unwrap().table.number, 1);
+    }
+
+    #[test]
+    fn cancelled_bookings_free_the_slot() {
+        let mut list = waitlist();
+        let booking = list.reserve("Ann", 6, 60, None, 0).unwrap();
+        assert_eq!(
+            list.reserve("Bob", 6, 90, None, 0),
+            Err(HostingError::FullyBooked { at: 90 })
+        );
+        list.cancel_reservation(booking).unwrap();
+        assert!(list.reserve("Bob", 6, 90, None, 0).is_ok());
+        assert_eq!(
+            list.cancel_reservation(booking),
+            Err(HostingError::ReservationClosed(booking))
+        );
+    }
+}
@@ -0,0 +1,355 @@
+// the reservation book: parties that booked a time slot ahead of arriving
+// each booking gets a table for one turn, so two bookings on the same table must be a turn apart
+// a few bookings per slot may go without a table (overbooking) because some parties never show up
+// the waitlist owns the book since holding a table for a booking changes who can be seated there
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::hosting::{FloorPlan, HostingError, PartyId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ReservationId(pub u64);
+
+/// How the host takes bookings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationRules {
+    /// Bookings start on multiples of this many minutes.
+    pub slot_minutes: u32,
+    /// Bookings per slot accepted without a table once the tables are taken.
+    pub overbook: u32,
+    /// Minutes a late party keeps its table before it counts as a no-show.
+    pub grace: u32,
+}
+
+impl Default for ReservationRules {
+    fn default() -> ReservationRules {
+        ReservationRules {
+            slot_minutes: 15,
+            overbook: 0,
+            grace: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReservationStatus {
+    Booked,
+    /// The party checked in and is waiting for its table.
+    Arrived(PartyId),
+    Seated,
+    /// The party checked in and left the waitlist before it got a table.
+    Left,
+    Cancelled,
+    NoShow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub name: String,
+    pub size: u32,
+    /// Start of the booked slot, minutes since opening.
+    pub at: u32,
+    /// `None` for an overbooked reservation, it gets whatever frees up.
+    pub table: Option<u32>,
+    pub status: ReservationStatus,
+}
+
+impl Reservation {
+    /// Booked or checked in, so it still needs a table.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status,
+            ReservationStatus::Booked | ReservationStatus::Arrived(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReservationBook {
+    rules: ReservationRules,
+    reservations: BTreeMap<ReservationId, Reservation>,
+    next_id: u64,
+}
+
+impl ReservationBook {
+    pub fn new(rules: ReservationRules) -> ReservationBook {
+        ReservationBook {
+            rules,
+            ..ReservationBook::default()
+        }
+    }
+
+    pub fn rules(&self) -> ReservationRules {
+        self.rules
+    }
+
+    pub fn get(&self, id: ReservationId) -> Result<&Reservation, HostingError> {
+        self.reservations
+            .get(&id)
+            .ok_or(HostingError::UnknownReservation(id))
+    }
+
+    /// Every reservation ever made, in booking order.
+    pub fn reservations(&self) -> impl Iterator<Item = &Reservation> {
+        self.reservations.values()
+    }
+
+    /// Active reservations by slot, then booking order.
+    pub fn upcoming(&self) -> Vec<&Reservation> {
+        let mut upcoming: Vec<&Reservation> = self
+            .reservations
+            .values()
+            .filter(|r| r.is_active())
+            .collect();
+        upcoming.sort_by_key(|r| (r.at, r.id));
+        upcoming
+    }
+
+    /// The active reservation holding `table` for a turn that overlaps
+    /// `turn_time` minutes starting at `at`.
+    pub fn conflict(&self, table: u32, at: u32, turn_time: u32) -> Option<&Reservation> {
+        self.reservations.values().find(|r| {
+            r.is_active()
+                && r.table == Some(table)
+                && r.at < at.saturating_add(turn_time)
+                && at < r.at.saturating_add(turn_time)
+        })
+    }
+
+    /// Tables that a party seated at `now` would still be sitting at when a
+    /// reservation is due, together with the reservation they are held for.
+    ///
+    /// Only open bookings hold a table, and only inside their window: a booking
+    /// is released once the party is `grace` minutes late, and a checked in party
+    /// that hasn't been seated by then waits like everyone else.
+    pub fn held(&self, now: u32, turn_time: u32) -> Vec<(u32, ReservationId)> {
+        self.reservations
+            .values()
+            .filter(|r| {
+                r.is_active()
+                    && r.at < now.saturating_add(turn_time)
+                    && now <= r.at.saturating_add(self.rules.grace)
+            })
+            .filter_map(|r| r.table.map(|table| (table, r.id)))
+            .collect()
+    }
+
+    pub(super) fn reserve(
+        &mut self,
+        plan: &FloorPlan,
+        turn_time: u32,
+        name: &str,
+        size: u32,
+        at: u32,
+        table: Option<u32>,
+    ) -> Result<ReservationId, HostingError> {
+        if name.trim().is_empty() {
+            return Err(HostingError::EmptyName);
+        }
+        if size == 0 {
+            return Err(HostingError::EmptyParty);
+        }
+        if self.rules.slot_minutes > 0 && !at.is_multiple_of(self.rules.slot_minutes) {
+            return Err(HostingError::NotOnSlot {
+                at,
+                slot_minutes: self.rules.slot_minutes,
+            });
+        }
+
+        let table = match table {
+            Some(number) => {
+                let table = plan
+                    .table(number)
+                    .ok_or(HostingError::UnknownTable(number))?;
+                if table.seats < size {
+                    return Err(HostingError::TableTooSmall {
+                        table: number,
+                        size,
+                    });
+                }
+                if let Some(other) = self.conflict(number, at, turn_time) {
+                    return Err(HostingError::TableBooked {
+                        table: number,
+                        by: other.id,
+                    });
+                }
+                Some(number)
+            }
+            None => {
+                let largest = plan.tables().iter().map(|t| t.seats).max().unwrap_or(0);
+                if size > largest {
+                    return Err(HostingError::PartyTooLarge { size, largest });
+                }
+                let free = plan
+                    .tables()
+                    .iter()
+                    .filter(|t| t.seats >= size && self.conflict(t.number, at, turn_time).is_none())
+                    .min_by_key(|t| (t.seats, t.number))
+                    .map(|t| t.number);
+                let overbooked = self
+                    .reservations
+                    .values()
+                    .filter(|r| r.is_active() && r.table.is_none() && r.at == at)
+                    .count() as u32;
+                if free.is_none() && overbooked >= self.rules.overbook {
+                    return Err(HostingError::FullyBooked { at });
+                }
+                free
+            }
+        };
+
+        self.next_id += 1;
+        let id = ReservationId(self.next_id);
+        self.reservations.insert(
+            id,
+            Reservation {
+                id,
+                name: name.trim().to_string(),
+                size,
+                at,
+                table,
+                status: ReservationStatus::Booked,
+            },
+        );
+        Ok(id)
+    }
+
+    pub(super) fn set_rules(&mut self, rules: ReservationRules) {
+        self.rules = rules;
+    }
+
+    pub(super) fn get_mut(&mut self, id: ReservationId) -> Result<&mut Reservation, HostingError> {
+        self.reservations
+            .get_mut(&id)
+            .ok_or(HostingError::UnknownReservation(id))
+    }
+
+    /// Marks every booking more than `grace` minutes late as a no-show.
+    pub(super) fn mark_no_shows(&mut self, now: u32) -> Vec<ReservationId> {
+        let grace = self.rules.grace;
+        self.reservations
+            .values_mut()
+            .filter(|r| r.status == ReservationStatus::Booked && now > r.at.saturating_add(grace))
+            .map(|r| {
+                r.status = ReservationStatus::NoShow;
+                r.id
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_of_house::hosting::Table;
+
+    fn plan() -> FloorPlan {
+        FloorPlan::new(vec![
+            Table {
+                number: 1,
+                seats: 2,
+            },
+            Table {
+                number: 2,
+                seats: 4,
+            },
+        ])
+        .unwrap()
+    }
+
+    fn book(overbook: u32) -> ReservationBook {
+        ReservationBook::new(ReservationRules {
+            overbook,
+            ..ReservationRules::default()
+        })
+    }
+
+    #[test]
+    fn bookings_get_the_smallest_free_table() {
+        let plan = plan();
+        let mut book = book(0);
+        let ann = book.reserve(&plan, 60, "Ann", 2, 60, None).unwrap();
+        let bob = book.reserve(&plan, 60, "Bob", 2, 60, None).unwrap();
+        assert_eq!(book.get(ann).unwrap().table, Some(1));
+        assert_eq!(book.get(bob).unwrap().table, Some(2));
+        // a turn later both tables are free again
+        let cat = book.reserve(&plan, 60, "Cat", 2, 120, None).unwrap();
+        assert_eq!(book.get(cat).unwrap().table, Some(1));
+    }
+
+    #[test]
+    fn conflicts_are_detected() {
+        let plan = plan();
+        let mut book = book(0);
+        let ann = book.reserve(&plan, 60, "Ann", 2, 60, Some(2)).unwrap();
+        assert_eq!(
+            book.reserve(&plan, 60, "Bob", 2, 105, Some(2)),
+            Err(HostingError::TableBooked { table: 2, by: ann })
+        );
+        assert!(book.reserve(&plan, 60, "Bob", 2, 120, Some(2)).is_ok());
+        assert_eq!(
+            book.reserve(&plan, 60, "Big", 3, 60, Some(1)),
+            Err(HostingError::TableTooSmall { table: 1, size: 3 })
+        );
+        assert_eq!(
+            book.reserve(&plan, 60, "Odd", 2, 61, None),
+            Err(HostingError::NotOnSlot {
+                at: 61,
+                slot_minutes: 15
+            })
+        );
+    }
+
+    #[test]
+    fn overbooking_is_limited_per_slot() {
+        let plan = plan();
+        let mut book = book(1);
+        book.reserve(&plan, 60, "A", 4, 60, None).unwrap();
+        let extra = book.reserve(&plan, 60, "B", 4, 60, None).unwrap();
+        assert_eq!(book.get(extra).unwrap().table, None);
+        assert_eq!(
+            book.reserve(&plan, 60, "C", 4, 60, None),
+            Err(HostingError::FullyBooked { at: 60 })
+        );
+        // the next slot has its own allowance
+        assert!(book.reserve(&plan, 60, "C", 4, 75, None).is_ok());
+    }
+
+    #[test]
+    fn tables_are_held_until_the_grace_period_ends() {
+        let plan = plan();
+        let mut book = book(0);
+        let ann = book.reserve(&plan, 60, "Ann", 2, 120, None).unwrap();
+        assert!(book.held(0, 60).is_empty());
+        assert_eq!(book.held(61, 60), vec![(1, ann)]);
+        assert_eq!(book.held(135, 60), vec![(1, ann)]);
+        assert!(book.held(136, 60).is_empty());
+
+        // checking in doesn't stretch the hold
+        book.get_mut(ann).unwrap().status = ReservationStatus::Arrived(PartyId(1));
+        assert_eq!(book.held(135, 60), vec![(1, ann)]);
+        assert!(book.held(136, 60).is_empty());
+        book.get_mut(ann).unwrap().status = ReservationStatus::Booked;
+
+        assert!(book.mark_no_shows(135).is_empty());
+        assert_eq!(book.mark_no_shows(136), vec![ann]);
+        assert_eq!(book.get(ann).unwrap().status, ReservationStatus::NoShow);
+        // the table is free for someone else in that slot now
+        assert!(book.reserve(&plan, 60, "Bob", 2, 120, Some(1)).is_ok());
+    }
+
+    // times come straight from the wire, a slot at the end of the day stops there
+    #[test]
+    fn slots_near_the_end_of_time_do_not_overflow() {
+        let plan = plan();
+        let mut book = book(0);
+        let late = u32::MAX - 15;
+        let ann = book.reserve(&plan, 60, "Ann", 2, late, Some(1)).unwrap();
+        assert!(book.conflict(1, u32::MAX - 1, 60).is_some());
+        assert_eq!(book.held(late, 60), vec![(1, ann)]);
+        assert_eq!(book.held(u32::MAX, 60), vec![(1, ann)]);
+        assert!(book.mark_no_shows(u32::MAX).is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+// an order moves Taken -> InKitchen -> Served -> Paid
+// every step consumes the order and hands back the next state, so skipping a step is a compile error
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::back_of_house::menu::Menu;
+use crate::back_of_house::{Appetizer, Breakfast};
+
+pub mod payment;
+
+use payment::{Bill, Payment, PaymentError, Receipt};
+
+/// One item on an order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderLine {
+    Breakfast(Breakfast),
+    Appetizer(Appetizer),
+}
+
+impl OrderLine {
+    /// Key of the dish in the menu catalog.
+    pub fn menu_key(&self) -> &'static str {
+        match self {
+            OrderLine::Breakfast(breakfast) => breakfast.menu_key(),
+            OrderLine::Appetizer(appetizer) => appetizer.menu_key(),
+        }
+    }
+}
+
+impl From<Breakfast> for OrderLine {
+    fn from(breakfast: Breakfast) -> OrderLine {
+        OrderLine::Breakfast(breakfast)
+    }
+}
+
+impl From<Appetizer> for OrderLine {
+    fn from(appetizer: Appetizer) -> OrderLine {
+        OrderLine::Appetizer(appetizer)
+    }
+}
+
+/// Identifies an order across its whole lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrderId(pub u64);
+
+// the states tell the compiler where the order is, only Paid carries data:
+// how the table paid and the receipts take_payment worked out from that
+// none of them is (de)serializable, an order only gets to a state through the functions below
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Taken;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InKitchen;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Served;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paid {
+    payment: Payment,
+    receipts: Vec<Receipt>,
+}
+
+/// An order for one table in lifecycle state `S`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order<S> {
+    table: u32,
+    lines: Vec<OrderLine>,
+    state: S,
+}
+
+impl<S> Order<S> {
+    pub fn table(&self) -> u32 {
+        self.table
+    }
+
+    pub fn lines(&self) -> &[OrderLine] {
+        &self.lines
+    }
+
+    fn into_state<T>(self, state: T) -> Order<T> {
+        Order {
+            table: self.table,
+            lines: self.lines,
+            state,
+        }
+    }
+}
+
+impl Order<Paid> {
+    pub fn payment(&self) -> &Payment {
+        &self.state.payment
+    }
+
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.state.receipts
+    }
+}
+
+impl Order<Taken> {
+    /// Lines can only be added before the order goes to the kitchen.
+    pub fn add_line(&mut self, line: impl Into<OrderLine>) {
+        self.lines.push(line.into());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServingError {
+    EmptyOrder,
+}
+
+impl fmt::Display for ServingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServingError::EmptyOrder => write!(f, "an order needs at least one line"),
+        }
+    }
+}
+
+impl std::error::Error for ServingError {}
+
+pub fn take_order(table: u32, lines: Vec<OrderLine>) -> Result<Order<Taken>, ServingError> {
+    if lines.is_empty() {
+        return Err(ServingError::EmptyOrder);
+    }
+    Ok(Order {
+        table,
+        lines,
+        state: Taken,
+    })
+}
+
+pub fn send_to_kitchen(order: Order<Taken>) -> Order<InKitchen> {
+    order.into_state(InKitchen)
+}
+
+pub fn serve_order(order: Order<InKitchen>) -> Order<Served> {
+    order.into_state(Served)
+}
+
+/// Only served orders can be paid. The receipts are settled here from the order's
+/// bill on `menu`, so they always add up to what the table ate.
+///
+/// A bill that can't be priced or split hands the order back unpaid.
+///
+/// ```compile_fail
+/// use restaurant::serving::payment::{Payment, Rounding, Split, TaxPolicy, Tip};
+/// use restaurant::serving::{self, OrderLine};
+/// use restaurant::menu::Menu;
+/// use restaurant::Appetizer;
+///
+/// let order = serving::take_order(1, vec![OrderLine::from(Appetizer::Soup)]).unwrap();
+/// let payment = Payment {
+///     split: Split::Whole,
+///     tip: Tip::None,
+///     tax: TaxPolicy { rate: 0, rounding: Rounding::HalfUp },
+/// };
+/// serving::take_payment(order, &Menu::house(), payment);
+/// ```
+pub fn take_payment(
+    order: Order<Served>,
+    menu: &Menu,
+    payment: Payment,
+) -> Result<Order<Paid>, (Order<Served>, PaymentError)> {
+    let receipts = Bill::for_order(&order, menu)
+        .and_then(|bill| bill.settle(&payment.split, payment.tip, payment.tax));
+    match receipts {
+        Ok(receipts) => Ok(order.into_state(Paid { payment, receipts })),
+        Err(e) => Err((order, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::payment::{Money, Rounding, Split, TaxPolicy, Tip};
+    use super::*;
+
+    fn payment(split: Split) -> Payment {
+        Payment {
+            split,
+            tip: Tip::None,
+            tax: TaxPolicy {
+                rate: 1000,
+                rounding: Rounding::HalfUp,
+            },
+        }
+    }
+
+    #[test]
+    fn empty_orders_are_rejected() {
+        assert_eq!(take_order(1, vec![]), Err(ServingError::EmptyOrder));
+    }
+
+    #[test]
+    fn order_walks_through_every_state() {
+        let mut order = take_order(4, vec![Breakfast::summer("Rye").into()]).unwrap();
+        order.add_line(Appetizer::Soup);
+
+        let order = send_to_kitchen(order);
+        let order = serve_order(order);
+        let order: Order<Paid> =
+            take_payment(order, &Menu::house(), payment(Split::Whole)).unwrap();
+
+        assert_eq!(order.table(), 4);
+        assert_eq!(
+            order.lines(),
+            &[
+                OrderLine::Breakfast(Breakfast::summer("Rye")),
+                OrderLine::Appetizer(Appetizer::Soup),
+            ]
+        );
+        // breakfast 12.50 with rye and soup 6.50, plus 10% tax
+        assert_eq!(order.receipts().len(), 1);
+        assert_eq!(order.receipts()[0].total, Money(1900 + 190));
+    }
+
+    #[test]
+    fn a_bill_that_cannot_be_settled_hands_the_order_back() {
+        let order = take_order(4, vec![Appetizer::Soup.into()]).unwrap();
+        let order = serve_order(send_to_kitchen(order));
+        let (order, error) =
+            take_payment(order, &Menu::house(), payment(Split::Even(0))).unwrap_err();
+        assert!(matches!(error, PaymentError::NoGuests));
+        assert!(take_payment(order, &Menu::house(), payment(Split::Even(2))).is_ok());
+    }
+}
@@ -0,0 +1,620 @@
+// paying the bill: splits, tips, tax and receipts
+// every amount is a whole number of cents, percentages are basis points (1% = 100)
+// whenever cents don't divide evenly the rounding rule decides, never a float
+// amounts that don't fit in a u64 are an error, a receipt is never wrapped around
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Order;
+use crate::back_of_house::menu::{Menu, MenuError};
+use crate::front_of_house::serving::OrderLine;
+
+/// An amount in cents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Money(pub u64);
+
+impl Money {
+    pub fn cents(self) -> u64 {
+        self.0
+    }
+
+    /// `None` when the sum doesn't fit.
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        self.0.checked_add(other.0).map(Money)
+    }
+
+    /// `None` when the sum doesn't fit.
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Money>) -> Option<Money> {
+        amounts
+            .into_iter()
+            .try_fold(Money(0), |total, amount| total.checked_add(amount))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = format!("{}.{:02}", self.0 / 100, self.0 % 100);
+        // keep width and alignment working for receipts
+        f.pad(&text)
+    }
+}
+
+impl std::ops::Add for Money {
+    type Output = Money;
+
+    /// Panics on overflow in every build, use `checked_add` for amounts from outside.
+    fn add(self, other: Money) -> Money {
+        self.checked_add(other).expect("money overflowed")
+    }
+}
+
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        Money::checked_sum(iter).expect("money overflowed")
+    }
+}
+
+/// What to do with the fraction of a cent left by a percentage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rounding {
+    HalfUp,
+    HalfEven,
+    Down,
+    Up,
+}
+
+impl Rounding {
+    /// `amount * basis_points / 10000` rounded to whole cents, `None` when that doesn't fit.
+    pub fn apply(self, amount: Money, basis_points: u32) -> Option<Money> {
+        // a u64 times a u32 always fits in a u128
+        let scaled = amount.0 as u128 * basis_points as u128;
+        let (whole, rest) = (scaled / 10_000, scaled % 10_000);
+        let up = match self {
+            Rounding::Down => false,
+            Rounding::Up => rest > 0,
+            Rounding::HalfUp => rest >= 5_000,
+            Rounding::HalfEven => rest > 5_000 || (rest == 5_000 && whole % 2 == 1),
+        };
+        u64::try_from(whole + up as u128).ok().map(Money)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaxPolicy {
+    /// In basis points, 825 is 8.25%.
+    pub rate: u32,
+    pub rounding: Rounding,
+}
+
+/// The most guests one bill can be split between.
+pub const MAX_GUESTS: u32 = 100;
+
+/// The largest percent tip, in basis points: the whole subtotal again.
+pub const MAX_TIP_PERCENT: u32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tip {
+    None,
+    /// In basis points of the subtotal, at most `MAX_TIP_PERCENT`.
+    Percent(u32),
+    /// Shared evenly between everyone paying.
+    Fixed(Money),
+}
+
+/// How the bill is divided between guests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Split {
+    Whole,
+    /// The same share for this many guests, leftover cents go to the first guests.
+    Even(u32),
+    /// Line indices each guest pays for, every line exactly once.
+    /// At most `MAX_GUESTS` guests either way.
+    ByItem(Vec<Vec<usize>>),
+}
+
+/// How a table asked to pay, the receipts are worked out from the bill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Payment {
+    pub split: Split,
+    pub tip: Tip,
+    pub tax: TaxPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BillLine {
+    pub name: String,
+    pub price: Money,
+}
+
+/// The priced lines of one order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bill {
+    table: u32,
+    lines: Vec<BillLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub table: u32,
+    /// 1 based.
+    pub guest: u32,
+    pub guests: u32,
+    pub lines: Vec<BillLine>,
+    pub subtotal: Money,
+    /// In basis points.
+    pub tax_rate: u32,
+    pub tax: Money,
+    pub tip: Money,
+    pub total: Money,
+}
+
+#[derive(Debug)]
+pub enum PaymentError {
+    Menu(MenuError),
+    NoGuests,
+    TooManyGuests(u32),
+    TipTooLarge(u32),
+    /// An amount on the bill doesn't fit in a `Money`.
+    Overflow,
+    UnknownLine(usize),
+    LineSplitTwice(usize),
+    LineNotPaid(usize),
+}
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaymentError::Menu(e) => write!(f, "could not price the order: {}", e),
+            PaymentError::NoGuests => write!(f, "a bill needs at least one guest"),
+            PaymentError::TooManyGuests(n) => write!(
+                f,
+                "a bill can be split between at most {} guests, not {}",
+                MAX_GUESTS, n
+            ),
+            PaymentError::TipTooLarge(bp) => write!(
+                f,
+                "a tip of {}.{:02}% is more than the bill",
+                bp / 100,
+                bp % 100
+            ),
+            PaymentError::Overflow => write!(f, "the bill is too large to add up"),
+            PaymentError::UnknownLine(i) => write!(f, "the bill has no line {}", i),
+            PaymentError::LineSplitTwice(i) => write!(f, "line {} is paid by two guests", i),
+            PaymentError::LineNotPaid(i) => write!(f, "nobody pays for line {}", i),
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+impl From<MenuError> for PaymentError {
+    fn from(error: MenuError) -> PaymentError {
+        PaymentError::Menu(error)
+    }
+}
+
+impl Bill {
+    pub fn for_order<S>(order: &Order<S>, menu: &Menu) -> Result<Bill, PaymentError> {
+        let mut lines = Vec::new();
+        for line in order.lines() {
+            let price = Money(menu.price_of(line)?);
+            let name = match line {
+                OrderLine::Breakfast(b) => {
+                    format!("{} ({})", menu.item(b.menu_key())?.name, b.toast)
+                }
+                OrderLine::Appetizer(a) => menu.item(a.menu_key())?.name.clone(),
+            };
+            lines.push(BillLine { name, price });
+        }
+        Ok(Bill {
+            table: order.table(),
+            lines,
+        })
+    }
+
+    pub fn lines(&self) -> &[BillLine] {
+        &self.lines
+    }
+
+    /// Panics if the lines don't add up to a `Money`, `settle` reports that as an error instead.
+    pub fn subtotal(&self) -> Money {
+        self.lines.iter().map(|l| l.price).sum()
+    }
+
+    /// One receipt per guest.
+    pub fn settle(
+        &self,
+        split: &Split,
+        tip: Tip,
+        tax: TaxPolicy,
+    ) -> Result<Vec<Receipt>, PaymentError> {
+        if let Tip::Percent(bp) = tip {
+            if bp > MAX_TIP_PERCENT {
+                return Err(PaymentError::TipTooLarge(bp));
+            }
+        }
+        // checked before anything is allocated per guest
+        let guests = match split {
+            Split::Whole => 1,
+            Split::Even(guests) => *guests,
+            Split::ByItem(shares) => u32::try_from(shares.len()).unwrap_or(u32::MAX),
+        };
+        if guests == 0 {
+            return Err(PaymentError::NoGuests);
+        }
+        if guests > MAX_GUESTS {
+            return Err(PaymentError::TooManyGuests(guests));
+        }
+        match split {
+            Split::Whole => Ok(vec![self.receipt(1, 1, self.lines.clone(), tip, tax)?]),
+            Split::Even(guests) => self.split_evenly(*guests, tip, tax),
+            Split::ByItem(shares) => self.split_by_item(shares, tip, tax),
+        }
+    }
+
+    fn split_evenly(
+        &self,
+        guests: u32,
+        tip: Tip,
+        tax: TaxPolicy,
+    ) -> Result<Vec<Receipt>, PaymentError> {
+        let whole = self.receipt(1, 1, self.lines.clone(), tip, tax)?;
+        let subtotal = share(whole.subtotal, guests);
+        let taxes = share(whole.tax, guests);
+        let tips = share(whole.tip, guests);
+        // every share is at most the whole, so the shares of a receipt that added up add up too
+        Ok((0..guests as usize)
+            .map(|i| Receipt {
+                table: self.table,
+                guest: i as u32 + 1,
+                guests,
+                lines: vec![BillLine {
+                    name: format!("Share 1/{} of {}", guests, whole.subtotal),
+                    price: subtotal[i],
+                }],
+                subtotal: subtotal[i],
+                tax_rate: tax.rate,
+                tax: taxes[i],
+                tip: tips[i],
+                total: subtotal[i] + taxes[i] + tips[i],
+            })
+            .collect())
+    }
+
+    fn split_by_item(
+        &self,
+        shares: &[Vec<usize>],
+        tip: Tip,
+        tax: TaxPolicy,
+    ) -> Result<Vec<Receipt>, PaymentError> {
+        let mut paid = vec![false; self.lines.len()];
+        for &i in shares.iter().flatten() {
+            match paid.get_mut(i) {
+                None => return Err(PaymentError::UnknownLine(i)),
+                Some(true) => return Err(PaymentError::LineSplitTwice(i)),
+                Some(p) => *p = true,
+            }
+        }
+        if let Some(i) = paid.iter().position(|p| !p) {
+            return Err(PaymentError::LineNotPaid(i));
+        }
+
+        let guests = shares.len() as u32;
+        let fixed_tips = match tip {
+            Tip::Fixed(amount) => share(amount, guests),
+            _ => vec![Money(0); shares.len()],
+        };
+        shares
+            .iter()
+            .enumerate()
+            .map(|(g, lines)| {
+                let lines = lines.iter().map(|&i| self.lines[i].clone()).collect();
+                let tip = match tip {
+                    Tip::Fixed(_) => Tip::Fixed(fixed_tips[g]),
+                    other => other,
+                };
+                self.receipt(g as u32 + 1, guests, lines, tip, tax)
+            })
+            .collect()
+    }
+
+    fn receipt(
+        &self,
+        guest: u32,
+        guests: u32,
+        lines: Vec<BillLine>,
+        tip: Tip,
+        tax: TaxPolicy,
+    ) -> Result<Receipt, PaymentError> {
+        let subtotal =
+            Money::checked_sum(lines.iter().map(|l| l.price)).ok_or(PaymentError::Overflow)?;
+        let tax_amount = tax
+            .rounding
+            .apply(subtotal, tax.rate)
+            .ok_or(PaymentError::Overflow)?;
+        let tip = match tip {
+            Tip::None => Money(0),
+            Tip::Percent(bp) => tax
+                .rounding
+                .apply(subtotal, bp)
+                .ok_or(PaymentError::Overflow)?,
+            Tip::Fixed(amount) => amount,
+        };
+        let total =
+            Money::checked_sum([subtotal, tax_amount, tip]).ok_or(PaymentError::Overflow)?;
+        Ok(Receipt {
+            table: self.table,
+            guest,
+            guests,
+            lines,
+            subtotal,
+            tax_rate: tax.rate,
+            tax: tax_amount,
+            tip,
+            total,
+        })
+    }
+}
+
+// splits `amount` into `parts` shares that add back up exactly
+fn share(amount: Money, parts: u32) -> Vec<Money> {
+    let parts = parts as u64;
+    (0..parts)
+        .map(|i| Money(amount.0 / parts + (i < amount.0 % parts) as u64))
+        .collect()
+}
+
+const RECEIPT_WIDTH: usize = 32;
+
+impl Receipt {
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Table {} - guest {} of {}\n",
+            self.table, self.guest, self.guests
+        );
+        let row = |label: &str, amount: Money| {
+            let width = RECEIPT_WIDTH
+                .saturating_sub(label.chars().count() + 1)
+                .max(1);
+            format!("{} {:>width$}\n", label, amount, width = width)
+        };
+        for line in &self.lines {
+            text.push_str(&row(&line.name, line.price));
+        }
+        text.push_str(&"-".repeat(RECEIPT_WIDTH));
+        text.push('\n');
+        text.push_str(&row("Subtotal", self.subtotal));
+        let rate = format!("Tax ({}.{:02}%)", self.tax_rate / 100, self.tax_rate % 100);
+        text.push_str(&row(&rate, self.tax));
+        text.push_str(&row("Tip", self.tip));
+        text.push_str(&row("Total", self.total));
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("receipts only hold plain data")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::{Appetizer, Breakfast};
+    use crate::front_of_house::serving;
+
+    const TAX: TaxPolicy = TaxPolicy {
+        rate: 825,
+        rounding: Rounding::HalfUp,
+    };
+
+    // breakfast 12.50 + sourdough 0.50, soup 6.50, salad 7.50
+    fn bill() -> Bill {
+        let order = serving::take_order(
+            7,
+            vec![
+                Breakfast::summer("Sourdough").into(),
+                Appetizer::Soup.into(),
+                Appetizer::Salad.into(),
+            ],
+        )
+        .unwrap();
+        Bill::for_order(&order, &Menu::house()).unwrap()
+    }
+
+    #[test]
+    fn rounding_rules() {
+        // 0.5 cent
+        assert_eq!(Rounding::HalfUp.apply(Money(5), 1000), Some(Money(1)));
+        assert_eq!(Rounding::HalfEven.apply(Money(5), 1000), Some(Money(0)));
+        assert_eq!(Rounding::HalfEven.apply(Money(15), 1000), Some(Money(2)));
+        assert_eq!(Rounding::Down.apply(Money(19), 1000), Some(Money(1)));
+        assert_eq!(Rounding::Up.apply(Money(11), 1000), Some(Money(2)));
+        assert_eq!(Rounding::Up.apply(Money(10), 1000), Some(Money(1)));
+        // the product doesn't fit in a u64 but the result does
+        assert_eq!(
+            Rounding::Down.apply(Money(u64::MAX), 5000),
+            Some(Money(u64::MAX / 2))
+        );
+        assert_eq!(Rounding::Down.apply(Money(u64::MAX), 20_000), None);
+    }
+
+    #[test]
+    fn money_displays_in_units() {
+        assert_eq!(Money(2342).to_string(), "23.42");
+        assert_eq!(Money(5).to_string(), "0.05");
+        assert_eq!(format!("{:>6}", Money(100)), "  1.00");
+    }
+
+    #[test]
+    fn whole_bill_with_percent_tip() {
+        let bill = bill();
+        assert_eq!(bill.subtotal(), Money(2700));
+        let receipts = bill.settle(&Split::Whole, Tip::Percent(1500), TAX).unwrap();
+        let r = &receipts[0];
+        // 8.25% of 27.00 is 2.2275
+        assert_eq!(r.tax, Money(223));
+        assert_eq!(r.tip, Money(405));
+        assert_eq!(r.total, Money(3328));
+        assert_eq!(r.lines[0].name, "Seasonal breakfast (Sourdough)");
+    }
+
+    #[test]
+    fn even_split_adds_back_up() {
+        let bill = bill();
+        let whole = bill
+            .settle(&Split::Whole, Tip::Fixed(Money(500)), TAX)
+            .unwrap();
+        let receipts = bill
+            .settle(&Split::Even(3), Tip::Fixed(Money(500)), TAX)
+            .unwrap();
+        assert_eq!(receipts.len(), 3);
+        let total: Money = receipts.iter().map(|r| r.total).sum();
+        assert_eq!(total, whole[0].total);
+        let tips: Vec<Money> = receipts.iter().map(|r| r.tip).collect();
+        assert_eq!(tips, vec![Money(167), Money(167), Money(166)]);
+        assert!(matches!(
+            bill.settle(&Split::Even(0), Tip::None, TAX),
+            Err(PaymentError::NoGuests)
+        ));
+    }
+
+    #[test]
+    fn guests_and_tips_are_bounded() {
+        let bill = bill();
+        assert!(matches!(
+            bill.settle(&Split::Even(u32::MAX), Tip::None, TAX),
+            Err(PaymentError::TooManyGuests(u32::MAX))
+        ));
+        assert!(matches!(
+            bill.settle(&Split::ByItem(vec![vec![]; 101]), Tip::None, TAX),
+            Err(PaymentError::TooManyGuests(101))
+        ));
+        assert_eq!(
+            bill.settle(&Split::Even(MAX_GUESTS), Tip::None, TAX)
+                .unwrap()
+                .len(),
+            100
+        );
+        assert!(matches!(
+            bill.settle(&Split::Whole, Tip::Percent(u32::MAX), TAX),
+            Err(PaymentError::TipTooLarge(u32::MAX))
+        ));
+        assert!(bill
+            .settle(&Split::Whole, Tip::Percent(MAX_TIP_PERCENT), TAX)
+            .is_ok());
+    }
+
+    #[test]
+    fn totals_that_do_not_fit_are_refused() {
+        let bill = bill();
+        for split in [
+            Split::Whole,
+            Split::Even(3),
+            Split::ByItem(vec![vec![0, 1, 2]]),
+        ] {
+            assert!(matches!(
+                bill.settle(&split, Tip::Fixed(Money(u64::MAX)), TAX),
+                Err(PaymentError::Overflow)
+            ));
+        }
+        let huge = Bill {
+            table: 1,
+            lines: vec![
+                BillLine {
+                    name: "Caviar".to_string(),
+                    price: Money(u64::MAX),
+                },
+                BillLine {
+                    name: "Toast".to_string(),
+                    price: Money(1),
+                },
+            ],
+        };
+        assert!(matches!(
+            huge.settle(&Split::Whole, Tip::None, TAX),
+            Err(PaymentError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn split_by_item() {
+        let bill = bill();
+        let receipts = bill
+            .settle(
+                &Split::ByItem(vec![vec![0], vec![2, 1]]),
+                Tip::Fixed(Money(301)),
+                TAX,
+            )
+            .unwrap();
+        assert_eq!(receipts[0].subtotal, Money(1300));
+        assert_eq!(receipts[0].tax, Money(107));
+        assert_eq!(receipts[0].tip, Money(151));
+        assert_eq!(receipts[1].subtotal, Money(1400));
+        assert_eq!(receipts[1].tip, Money(150));
+        assert_eq!(receipts[1].lines.len(), 2);
+    }
+
+    #[test]
+    fn split_by_item_must_cover_every_line_once() {
+        let bill = bill();
+        let settle = |shares: Vec<Vec<usize>>| bill.settle(&Split::ByItem(shares), Tip::None, TAX);
+        assert!(matches!(settle(vec![]), Err(PaymentError::NoGuests)));
+        assert!(matches!(
+            settle(vec![vec![0, 1, 2, 3]]),
+            Err(PaymentError::UnknownLine(3))
+        ));
+        assert!(matches!(
+            settle(vec![vec![0, 1], vec![1, 2]]),
+            Err(PaymentError::LineSplitTwice(1))
+        ));
+        assert!(matches!(
+            settle(vec![vec![0, 2]]),
+            Err(PaymentError::LineNotPaid(1))
+        ));
+    }
+
+    #[test]
+    fn text_receipt() {
+        let receipts = bill()
+            .settle(&Split::Whole, Tip::Percent(1500), TAX)
+            .unwrap();
+        let expected = "\
+Table 7 - guest 1 of 1
+Seasonal breakfast (Sourdough) 13.00
+Soup of the day             6.50
+House salad                 7.50
+--------------------------------
+Subtotal                   27.00
+Tax (8.25%)                 2.23
+Tip                         4.05
+Total                      33.28
+";
+        assert_eq!(receipts[0].to_text(), expected);
+    }
+
+    #[test]
+    fn json_receipt() {
+        let receipts = bill()
+            .settle(&Split::ByItem(vec![vec![1]]), Tip::None, TAX)
+            .unwrap_err();
+        assert!(matches!(receipts, PaymentError::LineNotPaid(0)));
+
+        let receipts = bill().settle(&Split::Even(2), Tip::None, TAX).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&receipts[1].to_json()).unwrap();
+        assert_eq!(json["guest"], 2);
+        assert_eq!(json["subtotal"], 1350);
+        assert_eq!(json["tax"], 111);
+        assert_eq!(json["total"], 1461);
+    }
+
+    #[test]
+    fn unknown_menu_items_cannot_be_billed() {
+        let order = serving::take_order(1, vec![Breakfast::summer("Cardboard").into()]).unwrap();
+        assert!(matches!(
+            Bill::for_order(&order, &Menu::house()),
+            Err(PaymentError::Menu(MenuError::UnknownChoice { .. }))
+        ));
+    }
+}
@@ -0,0 +1,726 @@
+// the journal keeps a day of service on disk
+// events.log holds one json event per line and is only ever appended to
+// snapshot.json holds the state after the first n events so recovery doesn't replay the whole day
+// an event is applied to a copy of the state first, so the log never holds an event that was refused,
+// and the copy only replaces the state once the event is on disk
+// an append that fails part way is cut back off the log, so the log only ever ends in whole events
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::back_of_house::inventory::Inventory;
+use crate::back_of_house::kitchen::{RemakeReason, TicketId};
+use crate::back_of_house::menu::Menu;
+use crate::front_of_house::hosting::{PartyId, Seating, Table};
+use crate::front_of_house::reservations::{ReservationId, ReservationRules};
+use crate::front_of_house::serving::payment::Payment;
+use crate::front_of_house::serving::{OrderId, OrderLine};
+use crate::service::{Applied, Event, Recorder, Restaurant, ServiceError};
+
+const LOG: &str = "events.log";
+const SNAPSHOT: &str = "snapshot.json";
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    Service(ServiceError),
+    AlreadyExists(PathBuf),
+    Corrupt { line: usize, message: String },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JournalError::Io(e) => write!(f, "journal i/o failed: {}", e),
+            JournalError::Service(e) => write!(f, "{}", e),
+            JournalError::AlreadyExists(path) => {
+                write!(f, "{} already holds a journal", path.display())
+            }
+            JournalError::Corrupt { line, message } => {
+                write!(f, "event log line {} is corrupt: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+impl From<io::Error> for JournalError {
+    fn from(error: io::Error) -> JournalError {
+        JournalError::Io(error)
+    }
+}
+
+impl From<ServiceError> for JournalError {
+    fn from(error: ServiceError) -> JournalError {
+        JournalError::Service(error)
+    }
+}
+
+// written from a borrowed state, read back as an owned one
+#[derive(Serialize, Deserialize)]
+struct Snapshot<R> {
+    /// Number of log events already folded into `restaurant`.
+    events: u64,
+    restaurant: R,
+}
+
+#[derive(Debug)]
+pub struct Journal {
+    dir: PathBuf,
+    log: File,
+    /// Bytes of whole events in the log.
+    log_len: u64,
+    restaurant: Restaurant,
+    events: u64,
+    snapshot_every: u64,
+    snapshot_error: Option<JournalError>,
+}
+
+impl Journal {
+    /// Starts a new day in `dir`, which must not hold a journal yet.
+    pub fn create(
+        dir: impl AsRef<Path>,
+        tables: Vec<Table>,
+        turn_time: u32,
+        menu: Menu,
+    ) -> Result<Journal, JournalError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        if dir.join(LOG).exists() {
+            return Err(JournalError::AlreadyExists(dir));
+        }
+        let opened = Event::Opened {
+            tables,
+            turn_time,
+            menu,
+        };
+        let restaurant = Restaurant::replay([&opened])?;
+        let log = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(dir.join(LOG))?;
+        let mut journal = Journal {
+            dir,
+            log,
+            log_len: 0,
+            restaurant,
+            events: 0,
+            snapshot_every: 100,
+            snapshot_error: None,
+        };
+        journal.append(&opened)?;
+        Ok(journal)
+    }
+
+    /// Recovers the state of an existing journal from its snapshot and log.
+    ///
+    /// A last line cut short by a crash is dropped from the log.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Journal, JournalError> {
+        let dir = dir.as_ref().to_path_buf();
+        let (events, torn_at) = read_log(&dir.join(LOG))?;
+        if let Some(len) = torn_at {
+            OpenOptions::new()
+                .write(true)
+                .open(dir.join(LOG))?
+                .set_len(len)?;
+        }
+
+        let snapshot = read_snapshot(&dir.join(SNAPSHOT))?;
+        let restaurant = match snapshot {
+            Some(snapshot) if snapshot.events <= events.len() as u64 => {
+                let mut restaurant = snapshot.restaurant;
+                for event in &events[snapshot.events as usize..] {
+                    restaurant.apply(event)?;
+                }
+                restaurant
+            }
+            // a snapshot ahead of the log can't be trusted, start over
+            _ => Restaurant::replay(&events)?,
+        };
+
+        let log = OpenOptions::new().append(true).open(dir.join(LOG))?;
+        Ok(Journal {
+            dir,
+            log_len: log.metadata()?.len(),
+            log,
+            restaurant,
+            events: events.len() as u64,
+            snapshot_every: 100,
+            snapshot_error: None,
+        })
+    }
+
+    /// Writes a snapshot after every `events` events, 0 turns snapshots off.
+    pub fn snapshot_every(mut self, events: u64) -> Journal {
+        self.snapshot_every = events;
+        self
+    }
+
+    pub fn restaurant(&self) -> &Restaurant {
+        &self.restaurant
+    }
+
+    /// Number of events in the log, including `Opened`.
+    pub fn len(&self) -> u64 {
+        self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events == 0
+    }
+
+    /// Every event of the day, for auditing.
+    pub fn events(&self) -> Result<Vec<Event>, JournalError> {
+        Ok(read_log(&self.dir.join(LOG))?.0)
+    }
+
+    /// Applies `event` and appends it to the log.
+    ///
+    /// If the event is refused or can't be written the state stays as it was.
+    /// Once the event is on disk it counts, a periodic snapshot that fails
+    /// afterwards is kept for [`take_snapshot_error`](Journal::take_snapshot_error).
+    pub fn record(&mut self, event: Event) -> Result<Applied, JournalError> {
+        let mut restaurant = self.restaurant.clone();
+        let applied = restaurant.apply(&event)?;
+        self.append(&event)?;
+        self.restaurant = restaurant;
+        if self.snapshot_every > 0 && self.events.is_multiple_of(self.snapshot_every) {
+            if let Err(e) = self.snapshot() {
+                self.snapshot_error = Some(e);
+            }
+        }
+        Ok(applied)
+    }
+
+    /// The last periodic snapshot that failed since this was last asked.
+    ///
+    /// Nothing is lost when a snapshot fails, recovery just replays more of the log.
+    pub fn take_snapshot_error(&mut self) -> Option<JournalError> {
+        self.snapshot_error.take()
+    }
+
+    /// Saves the current state so recovery can skip the events so far.
+    pub fn snapshot(&self) -> Result<(), JournalError> {
+        let snapshot = Snapshot {
+            events: self.events,
+            restaurant: &self.restaurant,
+        };
+        // write aside and rename so a crash never leaves half a snapshot
+        let tmp = self.dir.join(format!("{}.tmp", SNAPSHOT));
+        let mut file = File::create(&tmp)?;
+        serde_json::to_writer(&mut file, &snapshot).map_err(io::Error::from)?;
+        file.sync_all()?;
+        fs::rename(tmp, self.dir.join(SNAPSHOT))?;
+        Ok(())
+    }
+
+    pub fn count_stock(&mut self, inventory: Inventory) -> Result<(), JournalError> {
+        self.record(Event::StockCounted { inventory }).map(|_| ())
+    }
+
+    pub fn restock(
+        &mut self,
+        ingredient: &str,
+        quantity: u32,
+        at: u32,
+    ) -> Result<(), JournalError> {
+        self.record(Event::Restocked {
+            ingredient: ingredient.to_string(),
+            quantity,
+            at,
+        })
+        .map(|_| ())
+    }
+
+    pub fn set_reservation_rules(&mut self, rules: ReservationRules) -> Result<(), JournalError> {
+        self.record(Event::ReservationRulesSet { rules })
+            .map(|_| ())
+    }
+
+    pub fn reserve(
+        &mut self,
+        name: &str,
+        size: u32,
+        slot: u32,
+        table: Option<u32>,
+        at: u32,
+    ) -> Result<ReservationId, JournalError> {
+        match self.record(Event::ReservationMade {
+            name: name.to_string(),
+            size,
+            slot,
+            table,
+            at,
+        })? {
+            Applied::Reservation(id) => Ok(id),
+            other => unreachable!("reservation made produced {:?}", other),
+        }
+    }
+
+    pub fn cancel_reservation(
+        &mut self,
+        reservation: ReservationId,
+        at: u32,
+    ) -> Result<(), JournalError> {
+        self.record(Event::ReservationCancelled { reservation, at })
+            .map(|_| ())
+    }
+
+    pub fn check_in(
+        &mut self,
+        reservation: ReservationId,
+        at: u32,
+    ) -> Result<PartyId, JournalError> {
+        match self.record(Event::ReservationArrived { reservation, at })? {
+            Applied::Party(id) => Ok(id),
+            other => unreachable!("reservation arrived produced {:?}", other),
+        }
+    }
+
+    pub fn mark_no_shows(&mut self, at: u32) -> Result<Vec<ReservationId>, JournalError> {
+        match self.record(Event::NoShowsMarked { at })? {
+            Applied::NoShows(ids) => Ok(ids),
+            other => unreachable!("no-shows marked produced {:?}", other),
+        }
+    }
+
+    pub fn add_to_waitlist(
+        &mut self,
+        name: &str,
+        size: u32,
+        at: u32,
+    ) -> Result<PartyId, JournalError> {
+        match self.record(Event::PartyAdded {
+            name: name.to_string(),
+            size,
+            at,
+        })? {
+            Applied::Party(id) => Ok(id),
+            other => unreachable!("party added produced {:?}", other),
+        }
+    }
+
+    pub fn leave_waitlist(&mut self, party: PartyId, at: u32) -> Result<(), JournalError> {
+        self.record(Event::PartyLeft { party, at }).map(|_| ())
+    }
+
+    pub fn seat_at_table(&mut self, party: PartyId, at: u32) -> Result<Seating, JournalError> {
+        match self.record(Event::PartySeated { party, at })? {
+            Applied::Seated(seating) => Ok(seating),
+            other => unreachable!("party seated produced {:?}", other),
+        }
+    }
+
+    pub fn clear_table(&mut self, table: u32, at: u32) -> Result<Seating, JournalError> {
+        match self.record(Event::TableCleared { table, at })? {
+            Applied::Cleared(seating) => Ok(seating),
+            other => unreachable!("table cleared produced {:?}", other),
+        }
+    }
+
+    pub fn take_order(
+        &mut self,
+        table: u32,
+        lines: Vec<OrderLine>,
+        at: u32,
+    ) -> Result<OrderId, JournalError> {
+        match self.record(Event::OrderTaken { table, lines, at })? {
+            Applied::Order(id) => Ok(id),
+            other => unreachable!("order taken produced {:?}", other),
+        }
+    }
+
+    pub fn cook_order(&mut self, order: OrderId, at: u32) -> Result<Vec<TicketId>, JournalError> {
+        match self.record(Event::OrderFired { order, at })? {
+            Applied::Tickets(ids) => Ok(ids),
+            other => unreachable!("order fired produced {:?}", other),
+        }
+    }
+
+    pub fn complete_ticket(&mut self, ticket: TicketId, at: u32) -> Result<(), JournalError> {
+        self.record(Event::TicketCompleted { ticket, at })
+            .map(|_| ())
+    }
+
+    pub fn fix_incorrect_order(
+        &mut self,
+        ticket: TicketId,
+        reason: RemakeReason,
+        at: u32,
+    ) -> Result<TicketId, JournalError> {
+        match self.record(Event::TicketRemade { ticket, reason, at })? {
+            Applied::Ticket(id) => Ok(id),
+            other => unreachable!("ticket remade produced {:?}", other),
+        }
+    }
+
+    pub fn serve_order(&mut self, order: OrderId, at: u32) -> Result<(), JournalError> {
+        self.record(Event::OrderServed { order, at }).map(|_| ())
+    }
+
+    pub fn take_payment(
+        &mut self,
+        order: OrderId,
+        payment: Payment,
+        at: u32,
+    ) -> Result<(), JournalError> {
+        self.record(Event::OrderPaid { order, payment, at })
+            .map(|_| ())
+    }
+
+    fn append(&mut self, event: &Event) -> Result<(), JournalError> {
+        let mut line = serde_json::to_string(event).map_err(io::Error::from)?;
+        line.push('\n');
+        append_line(
+            &self.log,
+            self.log_len,
+            line.as_bytes(),
+            |mut log, bytes| log.write_all(bytes),
+        )?;
+        self.log_len += line.len() as u64;
+        self.events += 1;
+        Ok(())
+    }
+}
+
+// writes and syncs `line` at the end of a log that holds `len` bytes of whole events
+// on any error the log is cut back to `len`, an event that isn't synced isn't in the log
+fn append_line(
+    log: &File,
+    len: u64,
+    line: &[u8],
+    write: impl FnOnce(&File, &[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    // a failed append that couldn't be cut back the last time is cut off before writing after it
+    if log.metadata()?.len() != len {
+        log.set_len(len)?;
+    }
+    let written = write(log, line).and_then(|()| log.sync_data());
+    if written.is_err() {
+        // if this fails too the next append tries again
+        let _ = log.set_len(len);
+    }
+    written
+}
+
+impl Recorder for Journal {
+    type Error = JournalError;
+
+    fn restaurant(&self) -> &Restaurant {
+        Journal::restaurant(self)
+    }
+
+    fn record(&mut self, event: Event) -> Result<Applied, JournalError> {
+        Journal::record(self, event)
+    }
+}
+
+fn read_snapshot(path: &Path) -> Result<Option<Snapshot<Restaurant>>, JournalError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| JournalError::Corrupt {
+            line: 0,
+            message: format!("{}: {}", SNAPSHOT, e),
+        })
+}
+
+// returns the events and, if the last line was cut short, the length to truncate to
+fn read_log(path: &Path) -> Result<(Vec<Event>, Option<u64>), JournalError> {
+    let text = fs::read_to_string(path)?;
+    let mut events = Vec::new();
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        match serde_json::from_str(line) {
+            Ok(event) => events.push(event),
+            Err(_) if !line.ends_with('\n') => return Ok((events, Some(offset as u64))),
+            Err(e) => {
+                return Err(JournalError::Corrupt {
+                    line: i + 1,
+                    message: e.to_string(),
+                })
+            }
+        }
+        offset += line.len();
+    }
+    Ok((events, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::Appetizer;
+    use crate::front_of_house::serving::payment::{Rounding, Split, TaxPolicy, Tip};
+    use crate::service::OrderState;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "restaurant-journal-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn tables() -> Vec<Table> {
+        vec![
+            Table {
+                number: 1,
+                seats: 2,
+            },
+            Table {
+                number: 2,
+                seats: 4,
+            },
+        ]
+    }
+
+    // orders can only be taken for a table with a party at it
+    fn seat(journal: &mut Journal, name: &str, at: u32) -> u32 {
+        let party = journal.add_to_waitlist(name, 2, at).unwrap();
+        journal.seat_at_table(party, at).unwrap().table.number
+    }
+
+    // a short day touching every kind of event
+    fn service(journal: &mut Journal) -> OrderId {
+        let ann = journal.add_to_waitlist("Ann", 2, 0).unwrap();
+        let bob = journal.add_to_waitlist("Bob", 3, 1).unwrap();
+        journal.leave_waitlist(bob, 20).unwrap();
+        let seating = journal.seat_at_table(ann, 2).unwrap();
+
+        let order = journal
+            .take_order(seating.table.number, vec![Appetizer::Soup.into()], 5)
+            .unwrap();
+        let tickets = journal.cook_order(order, 6).unwrap();
+        journal.complete_ticket(tickets[0], 10).unwrap();
+        let remake = journal
+            .fix_incorrect_order(tickets[0], RemakeReason::Cold, 12)
+            .unwrap();
+        journal.complete_ticket(remake, 15).unwrap();
+        journal.serve_order(order, 16).unwrap();
+
+        let payment = Payment {
+            split: Split::Whole,
+            tip: Tip::None,
+            tax: TaxPolicy {
+                rate: 1000,
+                rounding: Rounding::HalfUp,
+            },
+        };
+        journal.take_payment(order, payment, 40).unwrap();
+        journal.clear_table(1, 45).unwrap();
+        order
+    }
+
+    #[test]
+    fn replaying_the_log_rebuilds_the_state() {
+        let dir = temp_dir("replay");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house())
+            .unwrap()
+            .snapshot_every(0);
+        let order = service(&mut journal);
+        assert_eq!(journal.len(), 13);
+
+        let reopened = Journal::open(&dir).unwrap();
+        assert_eq!(reopened.restaurant(), journal.restaurant());
+        assert_eq!(reopened.len(), 13);
+        assert!(matches!(
+            reopened.restaurant().order(order).unwrap().state,
+            OrderState::Paid(_)
+        ));
+        assert_eq!(reopened.restaurant().turns().len(), 1);
+        assert_eq!(reopened.restaurant().left().len(), 1);
+        assert!(!dir.join(SNAPSHOT).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn snapshots_plus_tail_match_a_full_replay() {
+        let dir = temp_dir("snapshot");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house())
+            .unwrap()
+            .snapshot_every(5);
+        service(&mut journal);
+        assert!(dir.join(SNAPSHOT).exists());
+
+        let text = fs::read_to_string(dir.join(SNAPSHOT)).unwrap();
+        let snapshot: Snapshot<Restaurant> = serde_json::from_str(&text).unwrap();
+        assert_eq!(snapshot.events, 10);
+
+        let recovered = Journal::open(&dir).unwrap();
+        let replayed = Restaurant::replay(&journal.events().unwrap()).unwrap();
+        assert_eq!(recovered.restaurant(), &replayed);
+        assert_eq!(recovered.restaurant(), journal.restaurant());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refused_events_are_not_logged() {
+        let dir = temp_dir("refused");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        assert!(matches!(
+            journal.add_to_waitlist("Big", 9, 0),
+            Err(JournalError::Service(ServiceError::Hosting(_)))
+        ));
+        let table = seat(&mut journal, "Ann", 0);
+        let order = journal
+            .take_order(table, vec![Appetizer::Salad.into()], 0)
+            .unwrap();
+        assert!(matches!(
+            journal.serve_order(order, 1),
+            Err(JournalError::Service(ServiceError::WrongState { .. }))
+        ));
+        journal.cook_order(order, 1).unwrap();
+        assert!(matches!(
+            journal.serve_order(order, 2),
+            Err(JournalError::Service(ServiceError::KitchenNotDone(_)))
+        ));
+        assert_eq!(journal.events().unwrap().len(), 5);
+        assert_eq!(
+            Journal::open(&dir).unwrap().restaurant(),
+            journal.restaurant()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_failed_write_leaves_the_state_alone() {
+        let dir = temp_dir("failed-write");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        // a read only handle refuses every append
+        journal.log = File::open(dir.join(LOG)).unwrap();
+        assert!(matches!(
+            journal.add_to_waitlist("Ann", 2, 0),
+            Err(JournalError::Io(_))
+        ));
+        assert_eq!(journal.restaurant().waitlist().waiting().count(), 0);
+        assert_eq!(journal.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_short_write_is_cut_off_the_log() {
+        let dir = temp_dir("short-write");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        let len = fs::metadata(dir.join(LOG)).unwrap().len();
+        // the disk fills up half way through the line
+        let full = append_line(
+            &journal.log,
+            journal.log_len,
+            b"{\"PartyLeft\":{}}\n",
+            |mut log, line| {
+                log.write_all(&line[..5])?;
+                Err(io::Error::other("no space left on device"))
+            },
+        );
+        assert!(full.is_err());
+        assert_eq!(fs::metadata(dir.join(LOG)).unwrap().len(), len);
+
+        // a leftover a failed cut didn't remove goes before the next event
+        OpenOptions::new()
+            .append(true)
+            .open(dir.join(LOG))
+            .unwrap()
+            .write_all(b"{\"Part")
+            .unwrap();
+        seat(&mut journal, "Ann", 0);
+        let reopened = Journal::open(&dir).unwrap();
+        assert_eq!(reopened.len(), journal.len());
+        assert_eq!(reopened.restaurant(), journal.restaurant());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_failed_snapshot_does_not_refuse_the_event() {
+        let dir = temp_dir("failed-snapshot");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house())
+            .unwrap()
+            .snapshot_every(1);
+        // a directory where the snapshot is written aside makes every snapshot fail
+        fs::create_dir(dir.join(format!("{}.tmp", SNAPSHOT))).unwrap();
+        journal.add_to_waitlist("Ann", 2, 0).unwrap();
+        assert_eq!(journal.restaurant().waitlist().waiting().count(), 1);
+        assert!(matches!(
+            journal.take_snapshot_error(),
+            Some(JournalError::Io(_))
+        ));
+        assert!(journal.take_snapshot_error().is_none());
+        assert_eq!(Journal::open(&dir).unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stock_levels_survive_a_restart() {
+        let dir = temp_dir("stock");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        let menu = Menu::house();
+        journal
+            .count_stock(Inventory::from_toml(&menu, "[soup]\non_hand = 1\n").unwrap())
+            .unwrap();
+        let (ann, bob) = (seat(&mut journal, "Ann", 0), seat(&mut journal, "Bob", 0));
+        let first = journal
+            .take_order(ann, vec![Appetizer::Soup.into()], 0)
+            .unwrap();
+        let second = journal
+            .take_order(bob, vec![Appetizer::Soup.into()], 1)
+            .unwrap();
+        journal.cook_order(first, 2).unwrap();
+        assert!(matches!(
+            journal.cook_order(second, 3),
+            Err(JournalError::Service(ServiceError::Inventory(_)))
+        ));
+        assert_eq!(
+            journal.restaurant().order(second).unwrap().state.name(),
+            "taken"
+        );
+
+        let mut reopened = Journal::open(&dir).unwrap();
+        assert_eq!(reopened.restaurant(), journal.restaurant());
+        assert!(!reopened.restaurant().inventory().is_available("soup"));
+        reopened.restock("soup", 4, 20).unwrap();
+        reopened.cook_order(second, 21).unwrap();
+        assert_eq!(reopened.restaurant().inventory().on_hand("soup"), Some(3));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn torn_last_line_is_dropped_on_recovery() {
+        let dir = temp_dir("torn");
+        let mut journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        journal.add_to_waitlist("Ann", 2, 0).unwrap();
+        let mut log = OpenOptions::new().append(true).open(dir.join(LOG)).unwrap();
+        log.write_all(b"{\"event\":\"party_added\",\"na").unwrap();
+
+        let mut recovered = Journal::open(&dir).unwrap();
+        assert_eq!(recovered.len(), 2);
+        recovered.add_to_waitlist("Bob", 2, 1).unwrap();
+        assert_eq!(Journal::open(&dir).unwrap().len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_lines_and_existing_journals_are_errors() {
+        let dir = temp_dir("corrupt");
+        Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        assert!(matches!(
+            Journal::create(&dir, tables(), 45, Menu::house()),
+            Err(JournalError::AlreadyExists(_))
+        ));
+        let mut log = OpenOptions::new().append(true).open(dir.join(LOG)).unwrap();
+        log.write_all(b"not json\n").unwrap();
+        assert!(matches!(
+            Journal::open(&dir),
+            Err(JournalError::Corrupt { line: 2, .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -13,59 +13,31 @@ mod tests {
     }
 }
 
-mod front_of_house {
-    pub mod hosting {
-        pub fn add_to_waitlist() {}
-
-        fn seat_at_table() {}
-    }
-
-    pub mod serving {
-        pub fn take_order() {}
-
-        fn serve_order() {}
-
-        fn take_payment() {}
-    }
-}
-mod back_of_house {
-    pub struct Breakfast {
-        pub toast: String,
-        seasonal_fruit: String,
-    }
-
-    impl Breakfast {
-        pub fn summer(toast: &str) -> Breakfast {
-            Breakfast {
-                toast: String::from(toast),
-                seasonal_fruit: String::from("peaches"),
-            }
-        }
-    }
-
-    // this makes all the enum variats public
-    pub enum Appetizer {
-        Soup,
-        Salad,
-    }
-
-    fn fix_incorrect_order() {
-        cook_order();
-        // call function in parent module of this module
-        super::deliver_order();
-    }
-
-    fn cook_order() {}
-}
+// front_of_house lives in src/front_of_house.rs, see the notes about moving modules to files at the bottom
+mod front_of_house;
+// back_of_house moved to src/back_of_house.rs the same way
+mod back_of_house;
+// the whole restaurant driven by events, and the log file those events are kept in
+pub mod journal;
+pub mod net;
+pub mod report;
+pub mod service;
 
 // we need to mark function as pub to use it here
 // inner modules can access outer module scope
 // but outer code can only access sibiling modules or inner codes that are marked with pub
 pub fn eat_at_restaurant() {
     // Absolute path
-    crate::front_of_house::hosting::add_to_waitlist();
+    let plan = crate::front_of_house::hosting::FloorPlan::new(vec![
+        crate::front_of_house::hosting::Table { number: 1, seats: 2 },
+        crate::front_of_house::hosting::Table { number: 2, seats: 4 },
+    ])
+    .unwrap();
     // Relative path
-    front_of_house::hosting::add_to_waitlist();
+    let mut waitlist = front_of_house::hosting::Waitlist::new(plan, 45);
+    let party = waitlist.add_to_waitlist("Ferris", 2, 0).unwrap();
+    let seating = waitlist.seat_at_table(party, 0).unwrap();
+    println!("{} is seated at table {}", seating.party.name, seating.table.number);
 
     // Order a breakfast in the summer with Rye toast
     let mut meal = back_of_house::Breakfast::summer("Rye");
@@ -88,14 +60,19 @@ fn deliver_order() {
 */
 mod server{
     // we bring parent module instead of the function itself so we always know that this function comes from another module
-    use crate::front_of_house::serving;
-    fn deliver_order() {
+    use crate::front_of_house::serving::{self, InKitchen, Order, Served};
+    pub(crate) fn deliver_order(order: Order<InKitchen>) -> Order<Served> {
         // we can shorten the path with the 'use' keyword
         // 'use' only creates a shortcut on that scope
-        serving::take_order();
+        serving::serve_order(order)
     }
 }
 
+// back_of_house calls super::deliver_order() so we bring it to the crate root
+use server::deliver_order;
+
+// the snippets below only show the 'use' syntax, they are commented out because they define the same names more than once
+/*
 // because both enums are Result we import the outer module so that rust can distinguish between them
 use std::fmt;
 use std::io;
@@ -115,15 +92,24 @@ fn function3() -> FMTResult {
 fn function4() -> IoResult<()> {
     // --snip--
 }
+*/
 
 // REEXPORTING
 // we can bring a module to the scope and then reexport it for the others to import it from our module
 pub use crate::front_of_house::hosting;
+pub use crate::front_of_house::reservations;
+pub use crate::front_of_house::serving;
+pub use crate::back_of_house::{inventory, kitchen, menu, Appetizer, Breakfast};
+pub use crate::back_of_house::{cook_order, deliver_when_ready, fix_incorrect_order};
+pub use crate::back_of_house::RemakeError;
+/*
 pub fn eat_at_restaurant() {
     hosting::add_to_waitlist();
 }
+*/
 // now users can import this code using restaurant::hosting::add_to_waitlist() instead of restaurant::front_of_house::hosting::add_to_waitlist()
 
+/*
 // std is a crate that is available y default and we dont need to import it
 use std::collections::HashMap;
 
@@ -139,9 +125,10 @@ use std::io::{self, Write};
 
 // use glob operator to bring all public items defined in std::collection
 use std::collections::*;
+*/
 
 // moving modules to seperate file
 // only load module to the module tree here
 // other files in the package need to import this module with "use"
 // in rus if we move modules to a file we need this nod declaration do that compiler know where to look for modules
-mod courier;
\ No newline at end of file
+pub mod courier;
\ No newline at end of file
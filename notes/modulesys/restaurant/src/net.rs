@@ -0,0 +1,786 @@
+// the tablets talk to the restaurant over the lan, one json object per line each way
+// every connection gets its own thread, the restaurant itself sits behind one mutex
+// so requests from different tablets are applied one at a time, in the order they get the lock
+// the server only turns requests into service events, all the rules stay in service.rs
+// events go through a Recorder, so behind a journal every request is on disk before it is answered
+use std::fmt;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::back_of_house::kitchen::TicketId;
+use crate::front_of_house::hosting::{Party, PartyId, Seating};
+use crate::front_of_house::serving::payment::{Payment, Receipt, Split, TaxPolicy, Tip};
+use crate::front_of_house::serving::{OrderId, OrderLine};
+use crate::service::{Applied, Event, OrderState, Recorder};
+
+/// Longest request line the server reads, in bytes.
+pub const MAX_LINE: usize = 64 * 1024;
+
+/// One line from a tablet, times are minutes since opening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    AddToWaitlist {
+        name: String,
+        size: u32,
+        at: u32,
+    },
+    LeaveWaitlist {
+        party: PartyId,
+        at: u32,
+    },
+    Waitlist,
+    /// Seats `party`, or the next party that fits a free table when it is `None`.
+    Seat {
+        party: Option<PartyId>,
+        at: u32,
+    },
+    ClearTable {
+        table: u32,
+        at: u32,
+    },
+    TakeOrder {
+        table: u32,
+        lines: Vec<OrderLine>,
+        at: u32,
+    },
+    FireOrder {
+        order: OrderId,
+        at: u32,
+    },
+    CompleteTicket {
+        ticket: TicketId,
+        at: u32,
+    },
+    ServeOrder {
+        order: OrderId,
+        at: u32,
+    },
+    Pay {
+        order: OrderId,
+        split: Split,
+        tip: Tip,
+        at: u32,
+    },
+    Order {
+        order: OrderId,
+    },
+}
+
+/// What the server answers to a request that worked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Done,
+    Party(PartyId),
+    Waiting(Vec<Party>),
+    Seated(Seating),
+    Order(OrderId),
+    Tickets(Vec<TicketId>),
+    Receipts(Vec<Receipt>),
+    OrderStatus {
+        table: u32,
+        state: String,
+        lines: Vec<OrderLine>,
+    },
+}
+
+/// Every request gets exactly one response line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Ok(Reply),
+    Error(String),
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(io::Error),
+    /// The server sent something that isn't a response, or hung up.
+    Protocol(String),
+    /// The server understood the request and refused it.
+    Refused(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Io(e) => write!(f, "connection failed: {}", e),
+            ClientError::Protocol(e) => write!(f, "bad response from server: {}", e),
+            ClientError::Refused(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> ClientError {
+        ClientError::Io(error)
+    }
+}
+
+struct Shared<R> {
+    recorder: Mutex<R>,
+    tax: TaxPolicy,
+}
+
+impl<R: Recorder> Shared<R> {
+    fn handle(&self, request: Request) -> Result<Reply, String> {
+        // a panicking connection can't leave half an event applied, record only swaps in a state it finished
+        let mut recorder = self
+            .recorder
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut apply = |event: Event| recorder.record(event).map_err(|e| e.to_string());
+        let reply = match request {
+            Request::AddToWaitlist { name, size, at } => {
+                applied(apply(Event::PartyAdded { name, size, at })?)
+            }
+            Request::LeaveWaitlist { party, at } => applied(apply(Event::PartyLeft { party, at })?),
+            Request::ClearTable { table, at } => {
+                apply(Event::TableCleared { table, at })?;
+                Reply::Done
+            }
+            Request::TakeOrder { table, lines, at } => {
+                applied(apply(Event::OrderTaken { table, lines, at })?)
+            }
+            Request::FireOrder { order, at } => applied(apply(Event::OrderFired { order, at })?),
+            Request::CompleteTicket { ticket, at } => {
+                applied(apply(Event::TicketCompleted { ticket, at })?)
+            }
+            Request::ServeOrder { order, at } => applied(apply(Event::OrderServed { order, at })?),
+            Request::Waitlist => Reply::Waiting(
+                recorder
+                    .restaurant()
+                    .waitlist()
+                    .waiting()
+                    .cloned()
+                    .collect(),
+            ),
+            Request::Seat { party, at } => {
+                let party = match party {
+                    Some(party) => party,
+                    None => recorder
+                        .restaurant()
+                        .waitlist()
+                        .next_seatable(at)
+                        .ok_or("no waiting party fits a free table")?,
+                };
+                applied(
+                    recorder
+                        .record(Event::PartySeated { party, at })
+                        .map_err(|e| e.to_string())?,
+                )
+            }
+            Request::Pay {
+                order,
+                split,
+                tip,
+                at,
+            } => {
+                let payment = Payment {
+                    split,
+                    tip,
+                    tax: self.tax,
+                };
+                apply(Event::OrderPaid { order, payment, at })?;
+                match &recorder
+                    .restaurant()
+                    .order(order)
+                    .map_err(|e| e.to_string())?
+                    .state
+                {
+                    OrderState::Paid(paid) => Reply::Receipts(paid.receipts().to_vec()),
+                    other => unreachable!("a paid order is {}", other.name()),
+                }
+            }
+            Request::Order { order } => {
+                let tracked = recorder
+                    .restaurant()
+                    .order(order)
+                    .map_err(|e| e.to_string())?;
+                Reply::OrderStatus {
+                    table: tracked.state.table(),
+                    state: tracked.state.name().to_string(),
+                    lines: tracked.state.lines().to_vec(),
+                }
+            }
+        };
+        Ok(reply)
+    }
+}
+
+fn applied(applied: Applied) -> Reply {
+    match applied {
+        Applied::Party(party) => Reply::Party(party),
+        Applied::Seated(seating) => Reply::Seated(seating),
+        Applied::Order(order) => Reply::Order(order),
+        Applied::Tickets(tickets) => Reply::Tickets(tickets),
+        _ => Reply::Done,
+    }
+}
+
+/// A bound listener that hasn't started taking connections yet.
+pub struct Server<R> {
+    listener: TcpListener,
+    shared: Arc<Shared<R>>,
+    max_connections: usize,
+    read_timeout: Duration,
+}
+
+impl<R: Recorder + Send + 'static> Server<R> {
+    /// Binds `addr`, port 0 picks a free port.
+    ///
+    /// Every request is recorded through `recorder`, pass a
+    /// [`Journal`](crate::journal::Journal) to keep the day on disk.
+    pub fn bind(addr: impl ToSocketAddrs, recorder: R, tax: TaxPolicy) -> io::Result<Server<R>> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            shared: Arc::new(Shared {
+                recorder: Mutex::new(recorder),
+                tax,
+            }),
+            max_connections: 64,
+            read_timeout: Duration::from_secs(10 * 60),
+        })
+    }
+
+    /// Connections over this many are told the server is busy and closed.
+    pub fn max_connections(mut self, max: usize) -> Server<R> {
+        self.max_connections = max;
+        self
+    }
+
+    /// A tablet that sends nothing for this long is disconnected.
+    pub fn read_timeout(mut self, timeout: Duration) -> Server<R> {
+        self.read_timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections on a background thread until the handle is shut down.
+    pub fn spawn(self) -> io::Result<ServerHandle<R>> {
+        let addr = self.local_addr()?;
+        let stopping = Arc::new(AtomicBool::new(false));
+        let shared = Arc::clone(&self.shared);
+        let stop = Arc::clone(&stopping);
+        let accept = thread::spawn(move || {
+            // each open connection with the thread answering it
+            let mut connections: Vec<(TcpStream, JoinHandle<()>)> = Vec::new();
+            for stream in self.listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                // a connection that failed to open only affects that tablet
+                let Ok(stream) = stream else { continue };
+                connections.retain(|(_, thread)| !thread.is_finished());
+                if connections.len() >= self.max_connections {
+                    let _ = refuse(stream, "the server is busy, try again later");
+                    continue;
+                }
+                let Ok(handle) = stream.try_clone() else {
+                    continue;
+                };
+                let shared = Arc::clone(&self.shared);
+                let timeout = self.read_timeout;
+                let thread = thread::spawn(move || {
+                    let connection = HangUp(stream);
+                    let _ = serve(&shared, &connection.0, timeout);
+                });
+                connections.push((handle, thread));
+            }
+            // closing the sockets ends every read, so the threads finish their last request and return
+            for (stream, thread) in connections {
+                let _ = stream.shutdown(Shutdown::Both);
+                let _ = thread.join();
+            }
+        });
+        Ok(ServerHandle {
+            addr,
+            shared,
+            stopping,
+            accept: Some(accept),
+        })
+    }
+}
+
+// the accept loop holds a clone of every stream, so dropping ours alone would leave the socket open
+// shutting it down on drop hangs up on the tablet even when answering it panicked
+struct HangUp(TcpStream);
+
+impl Drop for HangUp {
+    fn drop(&mut self) {
+        let _ = self.0.shutdown(Shutdown::Both);
+    }
+}
+
+fn refuse(mut stream: TcpStream, message: &str) -> io::Result<()> {
+    let mut line = serde_json::to_vec(&Response::Error(message.to_string()))?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+// answers one connection until the tablet hangs up, goes quiet or sends a line that is too long
+fn serve<R: Recorder>(shared: &Shared<R>, stream: &TcpStream, timeout: Duration) -> io::Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    let mut reader = BufReader::new(stream);
+    let mut writer = BufWriter::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        // one byte over the limit tells a line that is too long from one that just fits
+        if (&mut reader)
+            .take(MAX_LINE as u64 + 1)
+            .read_line(&mut line)?
+            == 0
+        {
+            return Ok(());
+        }
+        if line.len() > MAX_LINE {
+            // the rest of the line is still unread, there is no telling where the next one starts
+            let message = format!("requests are limited to {} bytes", MAX_LINE);
+            serde_json::to_writer(&mut writer, &Response::Error(message))?;
+            writer.write_all(b"\n")?;
+            return writer.flush();
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => match shared.handle(request) {
+                Ok(reply) => Response::Ok(reply),
+                Err(message) => Response::Error(message),
+            },
+            Err(e) => Response::Error(format!("bad request: {}", e)),
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+}
+
+/// A running server. Dropping it stops taking new connections and closes the open ones.
+pub struct ServerHandle<R> {
+    addr: SocketAddr,
+    shared: Arc<Shared<R>>,
+    stopping: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl<R> ServerHandle<R> {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Where the requests are recorded, held locked while the guard is alive.
+    pub fn recorder(&self) -> MutexGuard<'_, R> {
+        self.shared
+            .recorder
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Stops accepting connections and waits for the open ones to close.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if let Some(accept) = self.accept.take() {
+            self.stopping.store(true, Ordering::SeqCst);
+            // the accept loop is blocked in accept(), one last connection wakes it up
+            let _ = TcpStream::connect(self.addr);
+            let _ = accept.join();
+        }
+    }
+}
+
+impl<R> Drop for ServerHandle<R> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// One tablet's connection to the server.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Client, ClientError> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        })
+    }
+
+    /// Sends one request and waits for its response.
+    pub fn request(&mut self, request: &Request) -> Result<Reply, ClientError> {
+        serde_json::to_writer(&mut self.writer, request)
+            .map_err(|e| ClientError::Protocol(e.to_string()))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(ClientError::Protocol("connection closed".to_string()));
+        }
+        match serde_json::from_str(&line).map_err(|e| ClientError::Protocol(e.to_string()))? {
+            Response::Ok(reply) => Ok(reply),
+            Response::Error(message) => Err(ClientError::Refused(message)),
+        }
+    }
+
+    pub fn add_to_waitlist(
+        &mut self,
+        name: &str,
+        size: u32,
+        at: u32,
+    ) -> Result<PartyId, ClientError> {
+        match self.request(&Request::AddToWaitlist {
+            name: name.to_string(),
+            size,
+            at,
+        })? {
+            Reply::Party(party) => Ok(party),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn waitlist(&mut self) -> Result<Vec<Party>, ClientError> {
+        match self.request(&Request::Waitlist)? {
+            Reply::Waiting(parties) => Ok(parties),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn seat(&mut self, party: Option<PartyId>, at: u32) -> Result<Seating, ClientError> {
+        match self.request(&Request::Seat { party, at })? {
+            Reply::Seated(seating) => Ok(seating),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn take_order(
+        &mut self,
+        table: u32,
+        lines: Vec<OrderLine>,
+        at: u32,
+    ) -> Result<OrderId, ClientError> {
+        match self.request(&Request::TakeOrder { table, lines, at })? {
+            Reply::Order(order) => Ok(order),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn fire_order(&mut self, order: OrderId, at: u32) -> Result<Vec<TicketId>, ClientError> {
+        match self.request(&Request::FireOrder { order, at })? {
+            Reply::Tickets(tickets) => Ok(tickets),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn complete_ticket(&mut self, ticket: TicketId, at: u32) -> Result<(), ClientError> {
+        self.request(&Request::CompleteTicket { ticket, at })
+            .map(|_| ())
+    }
+
+    pub fn serve_order(&mut self, order: OrderId, at: u32) -> Result<(), ClientError> {
+        self.request(&Request::ServeOrder { order, at }).map(|_| ())
+    }
+
+    pub fn pay(
+        &mut self,
+        order: OrderId,
+        split: Split,
+        tip: Tip,
+        at: u32,
+    ) -> Result<Vec<Receipt>, ClientError> {
+        match self.request(&Request::Pay {
+            order,
+            split,
+            tip,
+            at,
+        })? {
+            Reply::Receipts(receipts) => Ok(receipts),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+fn unexpected(reply: Reply) -> ClientError {
+    ClientError::Protocol(format!("unexpected reply {:?}", reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::io::Read;
+
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::back_of_house::{Appetizer, Breakfast};
+    use crate::front_of_house::hosting::Table;
+    use crate::front_of_house::serving::payment::{Money, Rounding};
+    use crate::journal::Journal;
+    use crate::service::{Restaurant, ServiceError};
+
+    fn tables() -> Vec<Table> {
+        (1..=4).map(|number| Table { number, seats: 4 }).collect()
+    }
+
+    fn bind<R: Recorder + Send + 'static>(recorder: R) -> Server<R> {
+        Server::bind(
+            "127.0.0.1:0",
+            recorder,
+            TaxPolicy {
+                rate: 1000,
+                rounding: Rounding::HalfUp,
+            },
+        )
+        .unwrap()
+    }
+
+    fn server() -> ServerHandle<Restaurant> {
+        bind(Restaurant::open(tables(), 45, Menu::house()).unwrap())
+            .spawn()
+            .unwrap()
+    }
+
+    fn read_responses(stream: &mut TcpStream) -> Vec<Response> {
+        let mut text = String::new();
+        stream.read_to_string(&mut text).unwrap();
+        text.lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn an_order_goes_from_tablet_to_receipt() {
+        let server = server();
+        let mut host = Client::connect(server.local_addr()).unwrap();
+        let mut kitchen = Client::connect(server.local_addr()).unwrap();
+
+        let party = host.add_to_waitlist("Ann", 2, 0).unwrap();
+        let seating = host.seat(None, 1).unwrap();
+        assert_eq!(seating.party.id, party);
+
+        let order = host
+            .take_order(
+                seating.table.number,
+                vec![Breakfast::summer("Rye").into(), Appetizer::Salad.into()],
+                2,
+            )
+            .unwrap();
+        let tickets = kitchen.fire_order(order, 3).unwrap();
+        assert_eq!(tickets.len(), 2);
+        assert!(matches!(
+            host.serve_order(order, 4),
+            Err(ClientError::Refused(_))
+        ));
+        for ticket in tickets {
+            kitchen.complete_ticket(ticket, 15).unwrap();
+        }
+        host.serve_order(order, 16).unwrap();
+
+        let receipts = host.pay(order, Split::Even(2), Tip::None, 40).unwrap();
+        assert_eq!(receipts.len(), 2);
+        let total: Money = receipts.iter().map(|r| r.total).sum();
+        assert_eq!(total, Money(2000 + 200));
+        assert_eq!(
+            host.request(&Request::Order { order }).unwrap(),
+            Reply::OrderStatus {
+                table: seating.table.number,
+                state: "paid".to_string(),
+                lines: vec![Breakfast::summer("Rye").into(), Appetizer::Salad.into()],
+            }
+        );
+    }
+
+    #[test]
+    fn concurrent_tablets_share_one_waitlist() {
+        let server = server();
+        let addr = server.local_addr();
+        let workers: Vec<_> = (0..8)
+            .map(|tablet| {
+                thread::spawn(move || {
+                    let mut client = Client::connect(addr).unwrap();
+                    (0..25)
+                        .map(|i| {
+                            client
+                                .add_to_waitlist(&format!("party {}-{}", tablet, i), 2, i)
+                                .unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let ids: BTreeSet<PartyId> = workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 200);
+        assert_eq!(server.recorder().waitlist().waiting().count(), 200);
+        let mut client = Client::connect(addr).unwrap();
+        assert_eq!(client.waitlist().unwrap().len(), 200);
+    }
+
+    #[test]
+    fn bad_lines_get_an_error_and_the_connection_stays_open() {
+        let server = server();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream
+            .write_all(b"not json\n{\"op\":\"waitlist\"}\n")
+            .unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let lines = read_responses(&mut stream);
+        assert!(matches!(&lines[0], Response::Error(e) if e.starts_with("bad request")));
+        assert_eq!(lines[1], Response::Ok(Reply::Waiting(Vec::new())));
+    }
+
+    // records into a restaurant, but a party leaving crashes the connection before it counts
+    struct Flaky(Restaurant);
+
+    impl Recorder for Flaky {
+        type Error = ServiceError;
+
+        fn restaurant(&self) -> &Restaurant {
+            &self.0
+        }
+
+        fn record(&mut self, event: Event) -> Result<Applied, ServiceError> {
+            if matches!(event, Event::PartyLeft { .. }) {
+                panic!("the tablet crashed");
+            }
+            self.0.record(event)
+        }
+    }
+
+    #[test]
+    fn a_crashed_connection_does_not_stop_the_others() {
+        let flaky = Flaky(Restaurant::open(tables(), 45, Menu::house()).unwrap());
+        let server = bind(flaky).spawn().unwrap();
+        let mut crashing = Client::connect(server.local_addr()).unwrap();
+        let ann = crashing.add_to_waitlist("Ann", 2, 0).unwrap();
+        assert!(matches!(
+            crashing.request(&Request::LeaveWaitlist { party: ann, at: 1 }),
+            Err(ClientError::Io(_) | ClientError::Protocol(_))
+        ));
+
+        let mut other = Client::connect(server.local_addr()).unwrap();
+        other.add_to_waitlist("Bob", 2, 2).unwrap();
+        let waiting: Vec<String> = other
+            .waitlist()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(waiting, vec!["Ann".to_string(), "Bob".to_string()]);
+    }
+
+    #[test]
+    fn refusals_come_back_as_errors() {
+        let server = server();
+        let mut client = Client::connect(server.local_addr()).unwrap();
+        assert!(matches!(
+            client.add_to_waitlist("Huge", 9, 0),
+            Err(ClientError::Refused(e)) if e.contains("does not fit")
+        ));
+        assert!(matches!(
+            client.pay(OrderId(7), Split::Whole, Tip::None, 0),
+            Err(ClientError::Refused(e)) if e == "order 7 does not exist"
+        ));
+        server.shutdown();
+    }
+
+    #[test]
+    fn a_journal_behind_the_server_keeps_every_request() {
+        let dir = std::env::temp_dir().join(format!("restaurant-net-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let journal = Journal::create(&dir, tables(), 45, Menu::house()).unwrap();
+        let server = bind(journal).spawn().unwrap();
+        let mut client = Client::connect(server.local_addr()).unwrap();
+        client.add_to_waitlist("Ann", 2, 0).unwrap();
+        let seating = client.seat(None, 1).unwrap();
+        let recorded = server.recorder().len();
+        server.shutdown();
+
+        let journal = Journal::open(&dir).unwrap();
+        assert_eq!(journal.len(), recorded);
+        assert!(journal
+            .restaurant()
+            .waitlist()
+            .seated(seating.table.number)
+            .is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_line_over_the_limit_closes_the_connection() {
+        let server = server();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"{\"op\":\"waitlist\"}\n").unwrap();
+        stream.write_all(&vec![b' '; MAX_LINE + 1]).unwrap();
+        let lines = read_responses(&mut stream);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], Response::Ok(Reply::Waiting(Vec::new())));
+        assert!(matches!(&lines[1], Response::Error(e) if e.contains("limited")));
+    }
+
+    #[test]
+    fn connections_over_the_limit_are_turned_away() {
+        let server = bind(Restaurant::open(tables(), 45, Menu::house()).unwrap())
+            .max_connections(1)
+            .spawn()
+            .unwrap();
+        let mut first = Client::connect(server.local_addr()).unwrap();
+        assert!(first.waitlist().is_ok());
+        let mut second = TcpStream::connect(server.local_addr()).unwrap();
+        let lines = read_responses(&mut second);
+        assert!(matches!(&lines[..], [Response::Error(e)] if e.contains("busy")));
+        drop(first);
+        // the first connection's thread has to notice the hang-up before its slot frees
+        let mut retry = Client::connect(server.local_addr()).unwrap();
+        while retry.waitlist().is_err() {
+            thread::sleep(Duration::from_millis(10));
+            retry = Client::connect(server.local_addr()).unwrap();
+        }
+    }
+
+    #[test]
+    fn quiet_tablets_are_disconnected() {
+        let server = bind(Restaurant::open(tables(), 45, Menu::house()).unwrap())
+            .read_timeout(Duration::from_millis(50))
+            .spawn()
+            .unwrap();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        assert!(read_responses(&mut stream).is_empty());
+    }
+
+    #[test]
+    fn shutdown_closes_open_connections() {
+        let server = server();
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"{\"op\":\"waitlist\"}\n").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        // shutdown only returns once the connection's thread has been joined
+        server.shutdown();
+        line.clear();
+        assert_eq!(reader.read_line(&mut line).unwrap(), 0);
+    }
+}
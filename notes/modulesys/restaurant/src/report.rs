@@ -0,0 +1,571 @@
+// end of day numbers for the managers, built from the restaurant state after service
+// revenue per item comes from the restaurant's own menu, the one every bill of the day was priced from,
+// so a split bill still counts every dish once and the items add up to the subtotal
+// tax, tips and what was collected come from the receipts themselves
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::back_of_house::kitchen::RemakeReason;
+use crate::back_of_house::menu::{Category, MenuError};
+use crate::front_of_house::reservations::ReservationStatus;
+use crate::front_of_house::serving::payment::Money;
+use crate::service::{OrderState, Restaurant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemSales {
+    pub key: String,
+    pub name: String,
+    pub category: Category,
+    pub quantity: u32,
+    /// Before tax and tips.
+    pub revenue: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayReport {
+    /// Best selling first by revenue, then by name.
+    pub items: Vec<ItemSales>,
+    pub categories: BTreeMap<Category, Money>,
+    pub paid_orders: u32,
+    pub unpaid_orders: u32,
+    pub subtotal: Money,
+    pub tax: Money,
+    pub tips: Money,
+    pub collected: Money,
+    /// Cleared tables, the average is over these.
+    pub turns: u32,
+    /// Rounded to the nearest minute, `None` before the first table was cleared.
+    pub average_turn: Option<u32>,
+    pub parties_seated: u32,
+    pub parties_left: u32,
+    pub no_shows: u32,
+    pub remakes: BTreeMap<RemakeReason, u32>,
+}
+
+impl DayReport {
+    pub fn new(restaurant: &Restaurant) -> Result<DayReport, MenuError> {
+        let menu = restaurant.menu();
+        let mut items: BTreeMap<&str, ItemSales> = BTreeMap::new();
+        let mut report = DayReport {
+            items: Vec::new(),
+            categories: BTreeMap::new(),
+            paid_orders: 0,
+            unpaid_orders: 0,
+            subtotal: Money(0),
+            tax: Money(0),
+            tips: Money(0),
+            collected: Money(0),
+            turns: restaurant.turns().len() as u32,
+            average_turn: None,
+            parties_seated: restaurant.seated_count() as u32,
+            parties_left: restaurant.left().len() as u32,
+            no_shows: 0,
+            remakes: BTreeMap::new(),
+        };
+
+        for tracked in restaurant.orders().values() {
+            let paid = match &tracked.state {
+                OrderState::Paid(paid) => paid,
+                _ => {
+                    report.unpaid_orders += 1;
+                    continue;
+                }
+            };
+            report.paid_orders += 1;
+            for line in paid.lines() {
+                let item = menu.item(line.menu_key())?;
+                let price = Money(menu.price_of(line)?);
+                let sales = items.entry(line.menu_key()).or_insert_with(|| ItemSales {
+                    key: item.key.clone(),
+                    name: item.name.clone(),
+                    category: item.category,
+                    quantity: 0,
+                    revenue: Money(0),
+                });
+                sales.quantity += 1;
+                sales.revenue = sales.revenue + price;
+                let category = report.categories.entry(item.category).or_default();
+                *category = *category + price;
+            }
+            for receipt in paid.receipts() {
+                report.subtotal = report.subtotal + receipt.subtotal;
+                report.tax = report.tax + receipt.tax;
+                report.tips = report.tips + receipt.tip;
+                report.collected = report.collected + receipt.total;
+            }
+        }
+        report.items = items.into_values().collect();
+        report
+            .items
+            .sort_by(|a, b| b.revenue.cmp(&a.revenue).then_with(|| a.name.cmp(&b.name)));
+
+        let minutes: u32 = restaurant
+            .turns()
+            .iter()
+            .map(|t| t.cleared_at.saturating_sub(t.seating.seated_at))
+            .sum();
+        report.average_turn = (minutes + report.turns / 2).checked_div(report.turns);
+        report.no_shows = restaurant
+            .waitlist()
+            .reservations()
+            .reservations()
+            .filter(|r| r.status == ReservationStatus::NoShow)
+            .count() as u32;
+        for remake in restaurant.kitchen().remakes() {
+            *report.remakes.entry(remake.reason).or_default() += 1;
+        }
+        Ok(report)
+    }
+
+    /// Share of parties that gave up waiting, in basis points of everyone who
+    /// either got a table or left.
+    pub fn abandonment_rate(&self) -> Option<u32> {
+        let total = self.parties_seated + self.parties_left;
+        (total > 0).then(|| (self.parties_left * 10_000 + total / 2) / total)
+    }
+
+    pub fn total_remakes(&self) -> u32 {
+        self.remakes.values().sum()
+    }
+
+    /// One row per figure, `section,name,quantity,value`, amounts in currency units.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("section,name,quantity,value\n");
+        let mut row = |section: &str, name: &str, quantity: Option<u32>, value: String| {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                section,
+                csv_field(name),
+                quantity.map(|q| q.to_string()).unwrap_or_default(),
+                value
+            ));
+        };
+        for item in &self.items {
+            row(
+                "item",
+                &item.name,
+                Some(item.quantity),
+                item.revenue.to_string(),
+            );
+        }
+        for (category, revenue) in &self.categories {
+            let quantity = self
+                .items
+                .iter()
+                .filter(|i| i.category == *category)
+                .map(|i| i.quantity)
+                .sum();
+            row(
+                "category",
+                category_name(*category),
+                Some(quantity),
+                revenue.to_string(),
+            );
+        }
+        row(
+            "payments",
+            "subtotal",
+            Some(self.paid_orders),
+            self.subtotal.to_string(),
+        );
+        row("payments", "tax", None, self.tax.to_string());
+        row("payments", "tips", None, self.tips.to_string());
+        row("payments", "collected", None, self.collected.to_string());
+        row(
+            "payments",
+            "unpaid orders",
+            Some(self.unpaid_orders),
+            String::new(),
+        );
+        row(
+            "service",
+            "average turn minutes",
+            Some(self.turns),
+            self.average_turn.map(|m| m.to_string()).unwrap_or_default(),
+        );
+        row(
+            "service",
+            "abandonment rate",
+            Some(self.parties_left),
+            self.abandonment_rate().map(percent).unwrap_or_default(),
+        );
+        row("service", "no-shows", Some(self.no_shows), String::new());
+        for (reason, count) in &self.remakes {
+            row(
+                "remake",
+                &format!("{:?}", reason),
+                Some(*count),
+                String::new(),
+            );
+        }
+        csv
+    }
+
+    /// The same figures laid out for a terminal.
+    pub fn to_table(&self) -> String {
+        let mut rows = vec![Row::Header("Item", "Qty", "Revenue"), Row::Rule];
+        for item in &self.items {
+            rows.push(Row::Cells(
+                item.name.clone(),
+                item.quantity.to_string(),
+                item.revenue.to_string(),
+            ));
+        }
+        rows.push(Row::Rule);
+        for (category, revenue) in &self.categories {
+            rows.push(Row::Cells(
+                format!("All {}", category_name(*category)),
+                String::new(),
+                revenue.to_string(),
+            ));
+        }
+        rows.push(Row::Rule);
+        for (label, amount) in [
+            ("Subtotal", self.subtotal),
+            ("Tax", self.tax),
+            ("Tips", self.tips),
+            ("Collected", self.collected),
+        ] {
+            rows.push(Row::Cells(
+                label.to_string(),
+                String::new(),
+                amount.to_string(),
+            ));
+        }
+
+        let mut table = render(&rows);
+        table.push('\n');
+        let mut line = |label: &str, value: String| {
+            table.push_str(&format!("{:<22}{}\n", label, value));
+        };
+        line(
+            "Orders paid",
+            format!("{} ({} still open)", self.paid_orders, self.unpaid_orders),
+        );
+        line(
+            "Average turn",
+            match self.average_turn {
+                Some(minutes) => format!("{} min over {} tables", minutes, self.turns),
+                None => "no tables cleared".to_string(),
+            },
+        );
+        line(
+            "Waitlist abandonment",
+            match self.abandonment_rate() {
+                Some(rate) => format!("{} ({} left)", percent(rate), self.parties_left),
+                None => "no parties".to_string(),
+            },
+        );
+        line("No-shows", self.no_shows.to_string());
+        let remakes = self
+            .remakes
+            .iter()
+            .map(|(reason, count)| format!("{:?} {}", reason, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        line(
+            "Remakes",
+            if remakes.is_empty() {
+                "0".to_string()
+            } else {
+                format!("{} ({})", self.total_remakes(), remakes)
+            },
+        );
+        table
+    }
+}
+
+impl fmt::Display for DayReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_table())
+    }
+}
+
+enum Row {
+    Header(&'static str, &'static str, &'static str),
+    Cells(String, String, String),
+    Rule,
+}
+
+// first column left aligned, the numbers right aligned, every column as wide as its widest cell
+fn render(rows: &[Row]) -> String {
+    let mut widths = [0; 3];
+    for row in rows {
+        let cells = match row {
+            Row::Header(a, b, c) => [a.len(), b.len(), c.len()],
+            Row::Cells(a, b, c) => [a.chars().count(), b.len(), c.len()],
+            Row::Rule => continue,
+        };
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell);
+        }
+    }
+    let mut text = String::new();
+    for row in rows {
+        let (a, b, c) = match row {
+            Row::Header(a, b, c) => (a.to_string(), b.to_string(), c.to_string()),
+            Row::Cells(a, b, c) => (a.clone(), b.clone(), c.clone()),
+            Row::Rule => {
+                text.push_str(&"-".repeat(widths.iter().sum::<usize>() + 4));
+                text.push('\n');
+                continue;
+            }
+        };
+        text.push_str(&format!(
+            "{:<wa$}  {:>wb$}  {:>wc$}\n",
+            a,
+            b,
+            c,
+            wa = widths[0],
+            wb = widths[1],
+            wc = widths[2]
+        ));
+    }
+    text
+}
+
+fn category_name(category: Category) -> &'static str {
+    match category {
+        Category::Breakfast => "breakfast",
+        Category::Appetizer => "appetizer",
+    }
+}
+
+// 1250 basis points is 12.50%
+fn percent(basis_points: u32) -> String {
+    format!("{}.{:02}%", basis_points / 100, basis_points % 100)
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::menu::Menu;
+    use crate::back_of_house::{Appetizer, Breakfast};
+    use crate::front_of_house::hosting::{PartyId, Table};
+    use crate::front_of_house::serving::payment::{Payment, Rounding, Split, TaxPolicy, Tip};
+    use crate::front_of_house::serving::{OrderId, OrderLine};
+    use crate::service::{Applied, Event};
+
+    fn pay(restaurant: &mut Restaurant, order: OrderId, split: Split, at: u32) {
+        let payment = Payment {
+            split,
+            tip: Tip::Percent(1000),
+            tax: TaxPolicy {
+                rate: 1000,
+                rounding: Rounding::HalfUp,
+            },
+        };
+        restaurant
+            .apply(&Event::OrderPaid { order, payment, at })
+            .unwrap();
+    }
+
+    fn serve(restaurant: &mut Restaurant, table: u32, lines: Vec<OrderLine>, at: u32) -> OrderId {
+        let order = match restaurant
+            .apply(&Event::OrderTaken { table, lines, at })
+            .unwrap()
+        {
+            Applied::Order(order) => order,
+            other => panic!("{:?}", other),
+        };
+        let tickets = match restaurant.apply(&Event::OrderFired { order, at }).unwrap() {
+            Applied::Tickets(tickets) => tickets,
+            other => panic!("{:?}", other),
+        };
+        for ticket in tickets {
+            restaurant
+                .apply(&Event::TicketCompleted {
+                    ticket,
+                    at: at + 15,
+                })
+                .unwrap();
+        }
+        restaurant
+            .apply(&Event::OrderServed { order, at: at + 15 })
+            .unwrap();
+        order
+    }
+
+    // two tables, one split bill, one remake, one party that gave up and one still eating
+    fn day() -> Restaurant {
+        let mut restaurant = Restaurant::open(
+            vec![
+                Table {
+                    number: 1,
+                    seats: 2,
+                },
+                Table {
+                    number: 2,
+                    seats: 4,
+                },
+            ],
+            45,
+            Menu::house(),
+        )
+        .unwrap();
+        for (name, size) in [("Ann", 2), ("Bob", 4), ("Cat", 2), ("Dan", 2)] {
+            restaurant
+                .apply(&Event::PartyAdded {
+                    name: name.to_string(),
+                    size,
+                    at: 0,
+                })
+                .unwrap();
+        }
+        for party in [1, 2] {
+            restaurant
+                .apply(&Event::PartySeated {
+                    party: PartyId(party),
+                    at: 0,
+                })
+                .unwrap();
+        }
+        restaurant
+            .apply(&Event::PartyLeft {
+                party: PartyId(3),
+                at: 20,
+            })
+            .unwrap();
+
+        let first = serve(
+            &mut restaurant,
+            1,
+            vec![
+                Breakfast::summer("Sourdough").into(),
+                Appetizer::Soup.into(),
+            ],
+            5,
+        );
+        let cold = restaurant.kitchen().tickets()[0].id;
+        let remake = match restaurant
+            .apply(&Event::TicketRemade {
+                ticket: cold,
+                reason: RemakeReason::Cold,
+                at: 25,
+            })
+            .unwrap()
+        {
+            Applied::Ticket(remake) => remake,
+            other => panic!("{:?}", other),
+        };
+        restaurant
+            .apply(&Event::TicketCompleted {
+                ticket: remake,
+                at: 35,
+            })
+            .unwrap();
+        pay(&mut restaurant, first, Split::Even(2), 40);
+        let second = serve(
+            &mut restaurant,
+            2,
+            vec![
+                Breakfast::summer("Rye").into(),
+                Appetizer::Salad.into(),
+                Appetizer::Salad.into(),
+            ],
+            10,
+        );
+        pay(&mut restaurant, second, Split::Whole, 50);
+        serve(&mut restaurant, 1, vec![Appetizer::Soup.into()], 45);
+
+        restaurant
+            .apply(&Event::TableCleared { table: 1, at: 50 })
+            .unwrap();
+        restaurant
+            .apply(&Event::TableCleared { table: 2, at: 55 })
+            .unwrap();
+        restaurant
+    }
+
+    #[test]
+    fn revenue_is_counted_per_item_and_category() {
+        let restaurant = day();
+        let report = DayReport::new(&restaurant).unwrap();
+        let items: Vec<(&str, u32, Money)> = report
+            .items
+            .iter()
+            .map(|i| (i.key.as_str(), i.quantity, i.revenue))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("breakfast", 2, Money(1250 + 50 + 1250)),
+                ("salad", 2, Money(1500)),
+                ("soup", 1, Money(650)),
+            ]
+        );
+        assert_eq!(report.categories[&Category::Breakfast], Money(2550));
+        assert_eq!(report.categories[&Category::Appetizer], Money(2150));
+        assert_eq!(report.subtotal, Money(4700));
+        assert_eq!(report.tax, Money(470));
+        assert_eq!(report.collected, report.subtotal + report.tax + report.tips);
+        let revenue: Money = report.items.iter().map(|i| i.revenue).sum();
+        assert_eq!(revenue, report.subtotal);
+        assert_eq!((report.paid_orders, report.unpaid_orders), (2, 1));
+    }
+
+    #[test]
+    fn service_figures_cover_turns_waitlist_and_remakes() {
+        let restaurant = day();
+        let report = DayReport::new(&restaurant).unwrap();
+        assert_eq!(report.turns, 2);
+        // 50 and 55 minutes
+        assert_eq!(report.average_turn, Some(53));
+        // one of three parties that were dealt with left, Dan is still waiting
+        assert_eq!(report.abandonment_rate(), Some(3333));
+        assert_eq!(report.remakes[&RemakeReason::Cold], 1);
+        assert_eq!(report.total_remakes(), 1);
+    }
+
+    #[test]
+    fn an_empty_day_has_no_averages() {
+        let restaurant = Restaurant::open(
+            vec![Table {
+                number: 1,
+                seats: 2,
+            }],
+            45,
+            Menu::house(),
+        )
+        .unwrap();
+        let report = DayReport::new(&restaurant).unwrap();
+        assert_eq!(report.average_turn, None);
+        assert_eq!(report.abandonment_rate(), None);
+        assert!(report.to_table().contains("no tables cleared"));
+    }
+
+    #[test]
+    fn csv_has_one_row_per_figure() {
+        let restaurant = day();
+        let csv = DayReport::new(&restaurant).unwrap().to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "section,name,quantity,value");
+        assert_eq!(lines[1], "item,Seasonal breakfast,2,25.50");
+        assert!(lines.contains(&"category,appetizer,3,21.50"));
+        assert!(lines.contains(&"service,average turn minutes,2,53"));
+        assert!(lines.contains(&"service,abandonment rate,1,33.33%"));
+        assert!(lines.contains(&"remake,Cold,1,"));
+        assert!(lines.iter().all(|l| l.split(',').count() == 4));
+        assert_eq!(csv_field("Eggs, toast"), "\"Eggs, toast\"");
+    }
+
+    #[test]
+    fn table_lines_up_the_columns() {
+        let restaurant = day();
+        let table = DayReport::new(&restaurant).unwrap().to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Item                Qty  Revenue");
+        assert_eq!(lines[2], "Seasonal breakfast    2    25.50");
+        assert_eq!(lines[4], "Soup of the day       1     6.50");
+        assert!(table.contains("Average turn          53 min over 2 tables\n"));
+        assert!(table.contains("Remakes               1 (Cold 1)\n"));
+    }
+}
@@ -0,0 +1,791 @@
+// the whole restaurant as one value that only changes through events
+// front and back of house keep their own rules, this module just routes each event to them
+// replaying the same events always rebuilds the same state, which is what the journal relies on
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::back_of_house::inventory::{Inventory, InventoryError};
+use crate::back_of_house::kitchen::{Kitchen, KitchenError, RemakeReason, TicketId};
+use crate::back_of_house::menu::Menu;
+use crate::back_of_house::{cook_order, deliver_when_ready, fix_incorrect_order, RemakeError};
+use crate::front_of_house::hosting::{
+    FloorPlan, HostingError, Party, PartyId, Seating, Table, Waitlist,
+};
+use crate::front_of_house::reservations::{ReservationId, ReservationRules};
+use crate::front_of_house::serving::payment::{Payment, PaymentError};
+use crate::front_of_house::serving::{
+    self, InKitchen, Order, OrderId, OrderLine, Paid, Served, ServingError, Taken,
+};
+
+/// Something that happened during service, times are minutes since opening.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The menu is fixed for the day, every bill is priced from it.
+    Opened {
+        tables: Vec<Table>,
+        turn_time: u32,
+        menu: Menu,
+    },
+    /// The pantry was counted, replacing recipes and stock levels.
+    StockCounted {
+        inventory: Inventory,
+    },
+    Restocked {
+        ingredient: String,
+        quantity: u32,
+        at: u32,
+    },
+    ReservationRulesSet {
+        rules: ReservationRules,
+    },
+    ReservationMade {
+        name: String,
+        size: u32,
+        /// Start of the booked slot.
+        slot: u32,
+        table: Option<u32>,
+        at: u32,
+    },
+    ReservationCancelled {
+        reservation: ReservationId,
+        at: u32,
+    },
+    /// A party that booked checked in and joined the waitlist.
+    ReservationArrived {
+        reservation: ReservationId,
+        at: u32,
+    },
+    NoShowsMarked {
+        at: u32,
+    },
+    PartyAdded {
+        name: String,
+        size: u32,
+        at: u32,
+    },
+    PartyLeft {
+        party: PartyId,
+        at: u32,
+    },
+    PartySeated {
+        party: PartyId,
+        at: u32,
+    },
+    TableCleared {
+        table: u32,
+        at: u32,
+    },
+    OrderTaken {
+        table: u32,
+        lines: Vec<OrderLine>,
+        at: u32,
+    },
+    OrderFired {
+        order: OrderId,
+        at: u32,
+    },
+    TicketCompleted {
+        ticket: TicketId,
+        at: u32,
+    },
+    TicketRemade {
+        ticket: TicketId,
+        reason: RemakeReason,
+        at: u32,
+    },
+    OrderServed {
+        order: OrderId,
+        at: u32,
+    },
+    OrderPaid {
+        order: OrderId,
+        payment: Payment,
+        at: u32,
+    },
+}
+
+/// What applying an event produced.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Applied {
+    Reservation(ReservationId),
+    NoShows(Vec<ReservationId>),
+    Party(PartyId),
+    Seated(Seating),
+    Cleared(Seating),
+    Order(OrderId),
+    Tickets(Vec<TicketId>),
+    Ticket(TicketId),
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderState {
+    Taken(Order<Taken>),
+    InKitchen(Order<InKitchen>),
+    Served(Order<Served>),
+    // boxed, a paid order carries its receipts and would make every state that big
+    Paid(Box<Order<Paid>>),
+}
+
+impl OrderState {
+    pub fn table(&self) -> u32 {
+        match self {
+            OrderState::Taken(o) => o.table(),
+            OrderState::InKitchen(o) => o.table(),
+            OrderState::Served(o) => o.table(),
+            OrderState::Paid(o) => o.table(),
+        }
+    }
+
+    pub fn lines(&self) -> &[OrderLine] {
+        match self {
+            OrderState::Taken(o) => o.lines(),
+            OrderState::InKitchen(o) => o.lines(),
+            OrderState::Served(o) => o.lines(),
+            OrderState::Paid(o) => o.lines(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrderState::Taken(_) => "taken",
+            OrderState::InKitchen(_) => "in kitchen",
+            OrderState::Served(_) => "served",
+            OrderState::Paid(_) => "paid",
+        }
+    }
+}
+
+/// An order with the minutes at which it moved along.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub state: OrderState,
+    pub taken_at: u32,
+    pub served_at: Option<u32>,
+    pub paid_at: Option<u32>,
+}
+
+/// A table from the moment a party sat down until it was cleared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableTurn {
+    pub seating: Seating,
+    pub cleared_at: u32,
+}
+
+#[derive(Debug)]
+pub enum ServiceError {
+    Hosting(HostingError),
+    Serving(ServingError),
+    Payment(PaymentError),
+    Kitchen(KitchenError),
+    Inventory(InventoryError),
+    NotOpened,
+    AlreadyOpened,
+    UnknownOrder(OrderId),
+    WrongState { order: OrderId, state: &'static str },
+    KitchenNotDone(OrderId),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceError::Hosting(e) => write!(f, "{}", e),
+            ServiceError::Serving(e) => write!(f, "{}", e),
+            ServiceError::Payment(e) => write!(f, "{}", e),
+            ServiceError::Kitchen(e) => write!(f, "{}", e),
+            ServiceError::Inventory(e) => write!(f, "{}", e),
+            ServiceError::NotOpened => write!(f, "service must start with an opened event"),
+            ServiceError::AlreadyOpened => write!(f, "the restaurant is already open"),
+            ServiceError::UnknownOrder(id) => write!(f, "order {} does not exist", id.0),
+            ServiceError::WrongState { order, state } => {
+                write!(f, "order {} is {}", order.0, state)
+            }
+            ServiceError::KitchenNotDone(id) => {
+                write!(f, "the kitchen is still cooking for order {}", id.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<HostingError> for ServiceError {
+    fn from(error: HostingError) -> ServiceError {
+        ServiceError::Hosting(error)
+    }
+}
+
+impl From<ServingError> for ServiceError {
+    fn from(error: ServingError) -> ServiceError {
+        ServiceError::Serving(error)
+    }
+}
+
+impl From<PaymentError> for ServiceError {
+    fn from(error: PaymentError) -> ServiceError {
+        ServiceError::Payment(error)
+    }
+}
+
+impl From<KitchenError> for ServiceError {
+    fn from(error: KitchenError) -> ServiceError {
+        ServiceError::Kitchen(error)
+    }
+}
+
+impl From<InventoryError> for ServiceError {
+    fn from(error: InventoryError) -> ServiceError {
+        ServiceError::Inventory(error)
+    }
+}
+
+impl From<RemakeError> for ServiceError {
+    fn from(error: RemakeError) -> ServiceError {
+        match error {
+            RemakeError::Kitchen(e) => ServiceError::Kitchen(e),
+            RemakeError::Inventory(e) => ServiceError::Inventory(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "RestaurantRecord", try_from = "RestaurantRecord")]
+pub struct Restaurant {
+    menu: Menu,
+    waitlist: Waitlist,
+    kitchen: Kitchen,
+    #[serde(default)]
+    inventory: Inventory,
+    orders: BTreeMap<OrderId, TrackedOrder>,
+    next_order: u64,
+    turns: Vec<TableTurn>,
+    left: Vec<Party>,
+    seated: usize,
+}
+
+impl Restaurant {
+    pub fn open(
+        tables: Vec<Table>,
+        turn_time: u32,
+        menu: Menu,
+    ) -> Result<Restaurant, ServiceError> {
+        Ok(Restaurant {
+            menu,
+            waitlist: Waitlist::new(FloorPlan::new(tables)?, turn_time),
+            kitchen: Kitchen::new(),
+            inventory: Inventory::default(),
+            orders: BTreeMap::new(),
+            next_order: 0,
+            turns: Vec::new(),
+            left: Vec::new(),
+            seated: 0,
+        })
+    }
+
+    /// Rebuilds the state from a full event history starting with `Opened`.
+    pub fn replay<'a>(
+        events: impl IntoIterator<Item = &'a Event>,
+    ) -> Result<Restaurant, ServiceError> {
+        let mut events = events.into_iter();
+        let mut restaurant = match events.next() {
+            Some(Event::Opened {
+                tables,
+                turn_time,
+                menu,
+            }) => Restaurant::open(tables.clone(), *turn_time, menu.clone())?,
+            _ => return Err(ServiceError::NotOpened),
+        };
+        for event in events {
+            restaurant.apply(event)?;
+        }
+        Ok(restaurant)
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    pub fn waitlist(&self) -> &Waitlist {
+        &self.waitlist
+    }
+
+    pub fn kitchen(&self) -> &Kitchen {
+        &self.kitchen
+    }
+
+    /// Untracked until a `StockCounted` event, then nothing cooks without ingredients.
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn orders(&self) -> &BTreeMap<OrderId, TrackedOrder> {
+        &self.orders
+    }
+
+    pub fn order(&self, id: OrderId) -> Result<&TrackedOrder, ServiceError> {
+        self.orders.get(&id).ok_or(ServiceError::UnknownOrder(id))
+    }
+
+    /// Tables that have been seated and cleared again.
+    pub fn turns(&self) -> &[TableTurn] {
+        &self.turns
+    }
+
+    /// Parties that left the waitlist without a table.
+    pub fn left(&self) -> &[Party] {
+        &self.left
+    }
+
+    /// How many parties were given a table.
+    pub fn seated_count(&self) -> usize {
+        self.seated
+    }
+
+    /// Checks `event` against the current state and applies it.
+    ///
+    /// A rejected event leaves the state untouched.
+    pub fn apply(&mut self, event: &Event) -> Result<Applied, ServiceError> {
+        match event {
+            Event::Opened { .. } => Err(ServiceError::AlreadyOpened),
+            Event::StockCounted { inventory } => {
+                self.inventory = inventory.clone();
+                Ok(Applied::Done)
+            }
+            Event::Restocked {
+                ingredient,
+                quantity,
+                ..
+            } => {
+                self.inventory.restock(ingredient, *quantity)?;
+                Ok(Applied::Done)
+            }
+            Event::ReservationRulesSet { rules } => {
+                self.waitlist.set_reservation_rules(*rules);
+                Ok(Applied::Done)
+            }
+            Event::ReservationMade {
+                name,
+                size,
+                slot,
+                table,
+                at,
+            } => Ok(Applied::Reservation(
+                self.waitlist.reserve(name, *size, *slot, *table, *at)?,
+            )),
+            Event::ReservationCancelled { reservation, .. } => {
+                self.waitlist.cancel_reservation(*reservation)?;
+                Ok(Applied::Done)
+            }
+            Event::ReservationArrived { reservation, at } => {
+                Ok(Applied::Party(self.waitlist.check_in(*reservation, *at)?))
+            }
+            Event::NoShowsMarked { at } => Ok(Applied::NoShows(self.waitlist.mark_no_shows(*at))),
+            Event::PartyAdded { name, size, at } => Ok(Applied::Party(
+                self.waitlist.add_to_waitlist(name, *size, *at)?,
+            )),
+            Event::PartyLeft { party, .. } => {
+                self.left.push(self.waitlist.remove(*party)?);
+                Ok(Applied::Done)
+            }
+            Event::PartySeated { party, at } => {
+                let seating = self.waitlist.seat_at_table(*party, *at)?;
+                self.seated += 1;
+                Ok(Applied::Seated(seating))
+            }
+            Event::TableCleared { table, at } => {
+                let seating = self.waitlist.clear_table(*table)?;
+                self.turns.push(TableTurn {
+                    seating: seating.clone(),
+                    cleared_at: *at,
+                });
+                Ok(Applied::Cleared(seating))
+            }
+            Event::OrderTaken { table, lines, at } => {
+                if self.waitlist.floor_plan().table(*table).is_none() {
+                    return Err(HostingError::UnknownTable(*table).into());
+                }
+                if self.waitlist.seated(*table).is_none() {
+                    return Err(HostingError::TableNotOccupied(*table).into());
+                }
+                let order = serving::take_order(*table, lines.clone())?;
+                self.next_order += 1;
+                let id = OrderId(self.next_order);
+                self.orders.insert(
+                    id,
+                    TrackedOrder {
+                        state: OrderState::Taken(order),
+                        taken_at: *at,
+                        served_at: None,
+                        paid_at: None,
+                    },
+                );
+                Ok(Applied::Order(id))
+            }
+            Event::OrderFired { order, at } => {
+                self.advance(*order, |state, kitchen, inventory, _| match state {
+                    OrderState::Taken(taken) => {
+                        let in_kitchen = serving::send_to_kitchen(taken.clone());
+                        match cook_order(kitchen, inventory, &in_kitchen, *at) {
+                            Ok(tickets) => {
+                                Ok((OrderState::InKitchen(in_kitchen), Applied::Tickets(tickets)))
+                            }
+                            // nothing left the pantry, so the order simply stays taken
+                            Err(e) => Err((OrderState::Taken(taken), e.into())),
+                        }
+                    }
+                    other => Err(wrong_state(*order, other)),
+                })
+            }
+            Event::TicketCompleted { ticket, at } => {
+                self.kitchen.complete(*ticket, *at)?;
+                Ok(Applied::Done)
+            }
+            Event::TicketRemade { ticket, reason, at } => Ok(Applied::Ticket(fix_incorrect_order(
+                &mut self.kitchen,
+                &mut self.inventory,
+                *ticket,
+                *reason,
+                *at,
+            )?)),
+            Event::OrderServed { order, at } => {
+                let applied = self.advance(*order, |state, kitchen, _, _| match state {
+                    OrderState::InKitchen(in_kitchen) => {
+                        match deliver_when_ready(kitchen, in_kitchen) {
+                            Ok(served) => Ok((OrderState::Served(served), Applied::Done)),
+                            Err(in_kitchen) => Err((
+                                OrderState::InKitchen(in_kitchen),
+                                ServiceError::KitchenNotDone(*order),
+                            )),
+                        }
+                    }
+                    other => Err(wrong_state(*order, other)),
+                })?;
+                self.orders.get_mut(order).unwrap().served_at = Some(*at);
+                Ok(applied)
+            }
+            Event::OrderPaid { order, payment, at } => {
+                let applied = self.advance(*order, |state, _, _, menu| match state {
+                    OrderState::Served(served) => {
+                        match serving::take_payment(served, menu, payment.clone()) {
+                            Ok(paid) => Ok((OrderState::Paid(Box::new(paid)), Applied::Done)),
+                            Err((served, e)) => Err((OrderState::Served(served), e.into())),
+                        }
+                    }
+                    other => Err(wrong_state(*order, other)),
+                })?;
+                self.orders.get_mut(order).unwrap().paid_at = Some(*at);
+                Ok(applied)
+            }
+        }
+    }
+
+    // typestate orders only move forward by value, so the order is taken out of
+    // the map, moved along and put back; a refused step hands back the old state
+    fn advance(
+        &mut self,
+        id: OrderId,
+        step: impl FnOnce(
+            OrderState,
+            &mut Kitchen,
+            &mut Inventory,
+            &Menu,
+        ) -> Result<(OrderState, Applied), (OrderState, ServiceError)>,
+    ) -> Result<Applied, ServiceError> {
+        let mut tracked = self
+            .orders
+            .remove(&id)
+            .ok_or(ServiceError::UnknownOrder(id))?;
+        let result = match step(
+            tracked.state,
+            &mut self.kitchen,
+            &mut self.inventory,
+            &self.menu,
+        ) {
+            Ok((state, applied)) => {
+                tracked.state = state;
+                Ok(applied)
+            }
+            Err((state, error)) => {
+                tracked.state = state;
+                Err(error)
+            }
+        };
+        self.orders.insert(id, tracked);
+        result
+    }
+}
+
+/// Somewhere events can be recorded: the restaurant itself, or a
+/// [`Journal`](crate::journal::Journal) that also writes them to disk.
+pub trait Recorder {
+    type Error: fmt::Display;
+
+    fn restaurant(&self) -> &Restaurant;
+
+    /// Applies `event`, a refused event leaves the state untouched.
+    ///
+    /// So does a panic while it is applied, the shared server keeps serving
+    /// the other tablets from the same state afterwards.
+    fn record(&mut self, event: Event) -> Result<Applied, Self::Error>;
+}
+
+impl Recorder for Restaurant {
+    type Error = ServiceError;
+
+    fn restaurant(&self) -> &Restaurant {
+        self
+    }
+
+    // apply takes an order out of the map while it moves it on, so it works on a copy
+    // that only replaces the state once the event went through, like Journal::record
+    fn record(&mut self, event: Event) -> Result<Applied, ServiceError> {
+        let mut restaurant = self.clone();
+        let applied = restaurant.apply(&event)?;
+        *self = restaurant;
+        Ok(applied)
+    }
+}
+
+fn wrong_state(order: OrderId, state: OrderState) -> (OrderState, ServiceError) {
+    let error = ServiceError::WrongState {
+        order,
+        state: state.name(),
+    };
+    (state, error)
+}
+
+// what a snapshot holds, everything but the orders is stored as is
+#[derive(Serialize, Deserialize)]
+struct RestaurantRecord {
+    menu: Menu,
+    waitlist: Waitlist,
+    kitchen: Kitchen,
+    #[serde(default)]
+    inventory: Inventory,
+    orders: BTreeMap<OrderId, OrderRecord>,
+    next_order: u64,
+    turns: Vec<TableTurn>,
+    left: Vec<Party>,
+    seated: usize,
+}
+
+// an order is stored as its lines and how far it got, loading walks it through the
+// serving steps again so a snapshot can't put an order in a state it never reached
+#[derive(Serialize, Deserialize)]
+struct OrderRecord {
+    table: u32,
+    lines: Vec<OrderLine>,
+    stage: Stage,
+    taken_at: u32,
+    served_at: Option<u32>,
+    paid_at: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Stage {
+    Taken,
+    InKitchen,
+    Served,
+    Paid(Payment),
+}
+
+impl From<Restaurant> for RestaurantRecord {
+    fn from(restaurant: Restaurant) -> RestaurantRecord {
+        let orders = restaurant
+            .orders
+            .into_iter()
+            .map(|(id, tracked)| {
+                let stage = match &tracked.state {
+                    OrderState::Taken(_) => Stage::Taken,
+                    OrderState::InKitchen(_) => Stage::InKitchen,
+                    OrderState::Served(_) => Stage::Served,
+                    OrderState::Paid(paid) => Stage::Paid(paid.payment().clone()),
+                };
+                let record = OrderRecord {
+                    table: tracked.state.table(),
+                    lines: tracked.state.lines().to_vec(),
+                    stage,
+                    taken_at: tracked.taken_at,
+                    served_at: tracked.served_at,
+                    paid_at: tracked.paid_at,
+                };
+                (id, record)
+            })
+            .collect();
+        RestaurantRecord {
+            menu: restaurant.menu,
+            waitlist: restaurant.waitlist,
+            kitchen: restaurant.kitchen,
+            inventory: restaurant.inventory,
+            orders,
+            next_order: restaurant.next_order,
+            turns: restaurant.turns,
+            left: restaurant.left,
+            seated: restaurant.seated,
+        }
+    }
+}
+
+impl TryFrom<RestaurantRecord> for Restaurant {
+    type Error = ServiceError;
+
+    fn try_from(record: RestaurantRecord) -> Result<Restaurant, ServiceError> {
+        let mut orders = BTreeMap::new();
+        for (id, order) in record.orders {
+            let taken = serving::take_order(order.table, order.lines)?;
+            let state = match order.stage {
+                Stage::Taken => OrderState::Taken(taken),
+                Stage::InKitchen => OrderState::InKitchen(serving::send_to_kitchen(taken)),
+                Stage::Served => {
+                    OrderState::Served(serving::serve_order(serving::send_to_kitchen(taken)))
+                }
+                Stage::Paid(payment) => {
+                    let served = serving::serve_order(serving::send_to_kitchen(taken));
+                    let paid =
+                        serving::take_payment(served, &record.menu, payment).map_err(|(_, e)| e)?;
+                    OrderState::Paid(Box::new(paid))
+                }
+            };
+            let tracked = TrackedOrder {
+                state,
+                taken_at: order.taken_at,
+                served_at: order.served_at,
+                paid_at: order.paid_at,
+            };
+            orders.insert(id, tracked);
+        }
+        Ok(Restaurant {
+            menu: record.menu,
+            waitlist: record.waitlist,
+            kitchen: record.kitchen,
+            inventory: record.inventory,
+            orders,
+            next_order: record.next_order,
+            turns: record.turns,
+            left: record.left,
+            seated: record.seated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::back_of_house::Appetizer;
+    use crate::front_of_house::serving::payment::{Rounding, Split, TaxPolicy, Tip};
+
+    fn restaurant() -> Restaurant {
+        let tables = vec![Table {
+            number: 1,
+            seats: 2,
+        }];
+        Restaurant::open(tables, 45, Menu::house()).unwrap()
+    }
+
+    fn take_order(restaurant: &mut Restaurant, table: u32) -> Result<Applied, ServiceError> {
+        restaurant.apply(&Event::OrderTaken {
+            table,
+            lines: vec![Appetizer::Soup.into()],
+            at: 0,
+        })
+    }
+
+    #[test]
+    fn orders_are_only_taken_at_a_seated_table() {
+        let mut restaurant = restaurant();
+        assert!(matches!(
+            take_order(&mut restaurant, 7),
+            Err(ServiceError::Hosting(HostingError::UnknownTable(7)))
+        ));
+        assert!(matches!(
+            take_order(&mut restaurant, 1),
+            Err(ServiceError::Hosting(HostingError::TableNotOccupied(1)))
+        ));
+        assert!(restaurant.orders().is_empty());
+
+        let party = match restaurant
+            .apply(&Event::PartyAdded {
+                name: "Ann".to_string(),
+                size: 2,
+                at: 0,
+            })
+            .unwrap()
+        {
+            Applied::Party(party) => party,
+            other => panic!("{:?}", other),
+        };
+        restaurant
+            .apply(&Event::PartySeated { party, at: 1 })
+            .unwrap();
+        assert!(matches!(
+            take_order(&mut restaurant, 1),
+            Ok(Applied::Order(_))
+        ));
+    }
+
+    #[test]
+    fn orders_survive_a_snapshot_in_every_state() {
+        let mut restaurant = restaurant();
+        restaurant
+            .apply(&Event::PartyAdded {
+                name: "Ann".to_string(),
+                size: 2,
+                at: 0,
+            })
+            .unwrap();
+        restaurant
+            .apply(&Event::PartySeated {
+                party: PartyId(1),
+                at: 0,
+            })
+            .unwrap();
+        let ids: Vec<OrderId> = (0..3)
+            .map(|_| match take_order(&mut restaurant, 1).unwrap() {
+                Applied::Order(order) => order,
+                other => panic!("{:?}", other),
+            })
+            .collect();
+        for &order in &ids[1..] {
+            let tickets = match restaurant.apply(&Event::OrderFired { order, at: 1 }) {
+                Ok(Applied::Tickets(tickets)) => tickets,
+                other => panic!("{:?}", other),
+            };
+            for ticket in tickets {
+                restaurant
+                    .apply(&Event::TicketCompleted { ticket, at: 10 })
+                    .unwrap();
+            }
+        }
+        restaurant
+            .apply(&Event::OrderServed {
+                order: ids[2],
+                at: 11,
+            })
+            .unwrap();
+        let payment = Payment {
+            split: Split::Even(2),
+            tip: Tip::None,
+            tax: TaxPolicy {
+                rate: 1000,
+                rounding: Rounding::HalfUp,
+            },
+        };
+        restaurant
+            .apply(&Event::OrderPaid {
+                order: ids[2],
+                payment,
+                at: 30,
+            })
+            .unwrap();
+
+        let json = serde_json::to_string(&restaurant).unwrap();
+        let loaded: Restaurant = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, restaurant);
+        let states: Vec<&str> = loaded.orders().values().map(|o| o.state.name()).collect();
+        assert_eq!(states, ["taken", "in kitchen", "paid"]);
+    }
+}
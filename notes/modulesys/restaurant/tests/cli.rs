@@ -0,0 +1,217 @@
+// runs the restaurant binary on scripts, the way a demo at the front desk would
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("restaurant-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_script(dir: &Path, name: &str, script: &str, extra: &[&str]) -> Output {
+    let path = dir.join(name);
+    fs::write(&path, script).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_restaurant"))
+        .args(["--date", "2024-07-01", "--script"])
+        .arg(&path)
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn a_scripted_lunch() {
+    let dir = scratch("lunch");
+    let output = run_script(
+        &dir,
+        "lunch.txt",
+        "\
+# two parties, one order
+waitlist add Ann 2
+waitlist add Bob 6
+waitlist add Cat 6
+seat next
+seat next
+order 1 salad
+kitchen fire 1
+time 5
+kitchen done 1
+serve 1
+pay 1 tip 2.00
+report
+report csv
+",
+        &[],
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "\
+> waitlist add Ann 2
+party 1: Ann (2), estimated wait 0 min
+> waitlist add Bob 6
+party 2: Bob (6), estimated wait 0 min
+> waitlist add Cat 6
+party 3: Cat (6), estimated wait 45 min
+> seat next
+party 1 (Ann) seated at table 1 (2 seats)
+> seat next
+party 2 (Bob) seated at table 5 (6 seats)
+> order 1 salad
+order 1 for table 1: House salad
+> kitchen fire 1
+order 1 fired as ticket 1
+> time 5
+the time is now 5
+> kitchen done 1
+ticket 1 done
+> serve 1
+order 1 served
+> pay 1 tip 2.00
+order 1 paid
+Table 1 - guest 1 of 1
+House salad                 7.50
+--------------------------------
+Subtotal                    7.50
+Tax (8.25%)                 0.62
+Tip                         2.00
+Total                      10.12
+> report
+parties seated: 2
+parties waiting: 1
+parties that left: 0
+orders taken: 0, in kitchen: 0, served: 0, paid: 1
+tickets pending: 0, late: 0, remakes: 0
+revenue: 10.12
+> report csv
+section,name,quantity,value
+item,House salad,1,7.50
+category,appetizer,1,7.50
+payments,subtotal,1,7.50
+payments,tax,,0.62
+payments,tips,,2.00
+payments,collected,,10.12
+payments,unpaid orders,0,
+service,average turn minutes,0,
+service,abandonment rate,0,0.00%
+service,no-shows,0,
+"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn errors_are_reported_and_fail_the_script() {
+    let dir = scratch("errors");
+    let output = run_script(&dir, "bad.txt", "seat 9\nserve 1\nfly\nwaitlist\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let errors: Vec<&str> = stdout.lines().filter(|l| l.starts_with("error:")).collect();
+    assert_eq!(errors.len(), 3);
+    assert!(stdout.ends_with("> waitlist\nnobody is waiting\n"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_journal_carries_the_day_over() {
+    let dir = scratch("journal");
+    let journal = dir.join("day");
+    let journal = journal.to_str().unwrap();
+    let first = run_script(
+        &dir,
+        "morning.txt",
+        "waitlist add Ann 2\nseat 1\norder 1 soup\n",
+        &["--journal", journal],
+    );
+    assert!(first.status.success());
+
+    let second = run_script(
+        &dir,
+        "afternoon.txt",
+        "kitchen fire 1\nkitchen\n",
+        &["--journal", journal],
+    );
+    assert!(second.status.success());
+    let stdout = String::from_utf8(second.stdout).unwrap();
+    assert!(stdout.contains("order 1 fired as ticket 1"));
+    assert!(stdout.contains("ticket 1: table 1, grill"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn dishes_sell_out_when_the_pantry_runs_dry() {
+    let dir = scratch("stock");
+    let stock = dir.join("stock.toml");
+    fs::write(&stock, "[soup]\non_hand = 3\nlow_at = 1\n").unwrap();
+    let output = run_script(
+        &dir,
+        "soup.txt",
+        "waitlist add Ann 2\nwaitlist add Bob 2\nwaitlist add Cat 2\nseat 1\nseat 2\nseat 3\n\
+         order 1 soup soup\nkitchen fire 1\norder 2 soup\nkitchen fire 2\norder 3 soup\nrestock soup 2\nstock\n",
+        &["--stock", stock.to_str().unwrap()],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("order 1 fired as ticket 1\nalert: soup is running low, 1 left\n"));
+    assert!(stdout.contains("alert: soup is no longer available\n"));
+    assert!(stdout.contains("> order 3 soup\nerror: soup is sold out\n"));
+    assert!(stdout.ends_with("> restock soup 2\n2 soup on hand\n> stock\nsoup: 2\n"));
+    assert_eq!(output.status.code(), Some(1));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn bookings_hold_tables_and_no_shows_free_them() {
+    let dir = scratch("bookings");
+    let output = run_script(
+        &dir,
+        "bookings.txt",
+        "\
+reserve Ann 2 60
+reserve Bob 2 60 1
+reserve Cat 2 60 2
+time 30
+reservations
+waitlist add Dan 2
+seat next
+arrive 1
+seat next
+time 76
+noshows
+",
+        &["--tables", "2,2"],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "\
+> reserve Ann 2 60
+reservation 1: Ann (2) at 60, table 1
+> reserve Bob 2 60 1
+error: table 1 is already booked by reservation 1
+> reserve Cat 2 60 2
+reservation 2: Cat (2) at 60, table 2
+> time 30
+the time is now 30
+> reservations
+reservation 1: Ann (2) at 60, table 1, held
+reservation 2: Cat (2) at 60, table 2, held
+> waitlist add Dan 2
+party 1: Dan (2), estimated wait 75 min
+> seat next
+error: no waiting party fits a free table
+> arrive 1
+reservation 1 checked in as party 2
+> seat next
+party 2 (Ann) seated at table 1 (2 seats)
+> time 76
+the time is now 76
+> noshows
+reservation 2 is a no-show
+"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -1,38 +1,23 @@
-#[derive(Debug)]
-struct Rectangle {
-    width: u32,
-    height: u32,
-}
+// Rectangle with area, can_hold and square is shared with the adder crate, which also has positioned rectangles
+// #[path] reads the module from another file, relative to this one
+#[allow(dead_code)]
+#[path = "../testing/adder/src/geometry.rs"]
+mod geometry;
+
+use geometry::{Point, Rect, Rectangle};
 
 // each struct can have multiple impl blocks if necessary
+// the module is part of this crate so we can still add our own methods to its Rectangle
 impl Rectangle {
-    // &self is short for self:&Self
     // self means the type this method is implemented on
     // we must have exacly a first parameter name self
     // Having a method that takes ownership of the instance by using just self as the first parameter is rare; this technique is usually used when the method transforms self into something else and you want to prevent the caller from using the original instance after the transformation.
-    // methods help with organizing the code also we don't need to pass instance everytime to function
-    fn area(&self) -> u32 {
-        self.width * self.height
-    }
 
     // method can have the same name as fields
     // this is usefull to create getter methods
     fn width(&self) -> bool {
         self.width > 0
     }
-
-    // here we dont take ownership of the self and other and borrow them
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        self.width > other.width && self.height > other.height
-    }
-
-    // associated functions don't use self as first parameter because they don't rely on struct type to work
-    fn square(size: u32) -> Self {
-        Self {
-            width: size,
-            height: size,
-        }
-    }
 }
 
 fn main() {
@@ -41,8 +26,28 @@ fn main() {
         height: 50,
     };
 
+    // area takes &self, which is short for self: &Self
+    // methods help with organizing the code also we don't need to pass instance everytime to function
     println!(
         "The area of the rectangle is {} square pixels.",
         rect1.area()
     );
-}
\ No newline at end of file
+
+    // rect1.width() is the method, rect1.width the field
+    if rect1.width() {
+        println!("The rectangle has a nonzero width; it is {}", rect1.width);
+    }
+
+    // can_hold doesn't take ownership of self and other, it borrows them
+    // associated functions like square don't use self as first parameter because they don't rely on an instance to work
+    let rect2 = Rectangle::square(10);
+    println!("Can rect1 hold rect2? {}", rect1.can_hold(&rect2));
+
+    // a Rect is a size with a position, so it can answer where questions a Rectangle can't
+    let placed = Rect::new(10, 20, rect1.width, rect1.height);
+    println!(
+        "Placed at {:?} it covers (15, 25): {}",
+        placed.origin(),
+        placed.contains_point(Point::new(15, 25))
+    );
+}
@@ -0,0 +1,167 @@
+// `a + b` panics on overflow in a debug build but silently wraps around in a release build
+// the standard library has three explicit ways to say what should happen instead:
+// checked_add gives None, saturating_add sticks at MIN/MAX and wrapping_add wraps on purpose
+// the Integer trait lets us write each of them once for every integer primitive
+use std::fmt::Debug;
+
+pub trait Integer: Copy + Debug + PartialOrd {
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn saturating_add(self, other: Self) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+}
+
+// the inherent methods already exist on every primitive, the macro only forwards to them
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn saturating_add(self, other: Self) -> Self {
+                    <$t>::saturating_add(self, other)
+                }
+
+                fn wrapping_add(self, other: Self) -> Self {
+                    <$t>::wrapping_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// `None` when the sum doesn't fit in `T`.
+pub fn checked_add<T: Integer>(left: T, right: T) -> Option<T> {
+    left.checked_add(right)
+}
+
+/// Clamps to `T::MIN` or `T::MAX` instead of overflowing.
+pub fn saturating_add<T: Integer>(left: T, right: T) -> T {
+    left.saturating_add(right)
+}
+
+/// Wraps around like a release build would, but on purpose.
+pub fn wrapping_add<T: Integer>(left: T, right: T) -> T {
+    left.wrapping_add(right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prop::{check, Arbitrary};
+
+    // every property is written once and run for each primitive by the macro at the bottom
+    fn commutative<T: Integer + Arbitrary>() {
+        check("commutative", |(a, b): (T, T)| {
+            checked_add(a, b) == checked_add(b, a)
+                && saturating_add(a, b) == saturating_add(b, a)
+                && wrapping_add(a, b) == wrapping_add(b, a)
+        });
+    }
+
+    fn zero_is_the_identity<T: Integer + Arbitrary>() {
+        check("zero is the identity", |a: T| {
+            checked_add(a, T::ZERO) == Some(a)
+                && saturating_add(a, T::ZERO) == a
+                && wrapping_add(T::ZERO, a) == a
+        });
+    }
+
+    // when the sum fits all three agree, when it doesn't checked gives up and
+    // saturating sticks at the end it overflowed past, which wrapping never lands on
+    fn variants_agree_unless_they_overflow<T: Integer + Arbitrary>() {
+        check("variants agree unless they overflow", |(a, b): (T, T)| {
+            let saturated = saturating_add(a, b);
+            let wrapped = wrapping_add(a, b);
+            match checked_add(a, b) {
+                Some(sum) => sum == saturated && sum == wrapped,
+                None => {
+                    let end = if b > T::ZERO { T::MAX } else { T::MIN };
+                    saturated == end && wrapped != end
+                }
+            }
+        });
+    }
+
+    fn max_plus_one_overflows<T: Integer + Arbitrary>() {
+        assert_eq!(checked_add(T::MAX, T::ONE), None);
+        assert_eq!(saturating_add(T::MAX, T::ONE), T::MAX);
+        assert_eq!(wrapping_add(T::MAX, T::ONE), T::MIN);
+    }
+
+    macro_rules! for_every_integer {
+        ($($t:ident),*) => {
+            $(
+                mod $t {
+                    #[test]
+                    fn commutative() {
+                        super::commutative::<$t>();
+                    }
+
+                    #[test]
+                    fn zero_is_the_identity() {
+                        super::zero_is_the_identity::<$t>();
+                    }
+
+                    #[test]
+                    fn variants_agree_unless_they_overflow() {
+                        super::variants_agree_unless_they_overflow::<$t>();
+                    }
+
+                    #[test]
+                    fn max_plus_one_overflows() {
+                        super::max_plus_one_overflows::<$t>();
+                    }
+                }
+            )*
+        };
+    }
+
+    for_every_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+    // for types narrower than i128 the exact sum can be worked out, so overflow is checked against it
+    // these live in their own module because the ones above named i8, u8, ... hide the primitive types here
+    mod exact {
+        use crate::arith::checked_add;
+        use crate::prop::check;
+
+        macro_rules! against_i128 {
+            ($($name:ident: $t:ty),*) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        check("overflow matches the exact sum", |(a, b): ($t, $t)| {
+                            let exact = a as i128 + b as i128;
+                            let fits = (<$t>::MIN as i128..=<$t>::MAX as i128).contains(&exact);
+                            checked_add(a, b).map(|sum| sum as i128) == fits.then_some(exact)
+                        });
+                    }
+                )*
+            };
+        }
+
+        against_i128!(
+            i8: i8,
+            i16: i16,
+            i32: i32,
+            i64: i64,
+            u8: u8,
+            u16: u16,
+            u32: u32,
+            u64: u64
+        );
+    }
+}
@@ -0,0 +1,460 @@
+// the one Rectangle shared by the notes crates, notes/struct/rectangle_method.rs pulls this file in with #[path]
+// Rectangle is only a size, Rect puts a size somewhere on a grid so rectangles can be compared by position
+// a Rect covers x..x+width and y..y+height, the right and bottom edges are not part of it
+// so two rects that share an edge touch but don't overlap, and splitting never loses or duplicates a cell
+// y grows downwards like on a screen, "top" is the smaller y
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rectangle {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rectangle {
+    pub fn area(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Whether `other` fits inside with room to spare on both sides.
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+
+    pub fn square(size: u32) -> Self {
+        Self {
+            width: size,
+            height: size,
+        }
+    }
+
+    /// The same rectangle turned by 90°.
+    pub fn rotated(&self) -> Rectangle {
+        Rectangle {
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    /// Like `can_hold`, but `other` may be turned by 90° to fit.
+    pub fn can_hold_rotated(&self, other: &Rectangle) -> bool {
+        self.can_hold(other) || self.can_hold(&other.rotated())
+    }
+
+    /// Puts this size at `origin`, panics like `Rect::new` if it runs off the grid.
+    pub fn at(&self, origin: Point) -> Rect {
+        Rect::new(origin.x, origin.y, self.width, self.height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+}
+
+/// A rectangle with a position, its top left corner is `origin`.
+///
+/// Every cell of it has an `i32` position, so the far edges are at most `i32::MAX + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    origin: Point,
+    size: Rectangle,
+}
+
+// the far edges are worked out in i64, the last cell can be at i32::MAX and the edge is one past it
+// keeping every cell on the grid means any piece cut out of a rect has an origin that fits in an i32
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        let edge = i32::MAX as i64 + 1;
+        if x as i64 + width as i64 > edge || y as i64 + height as i64 > edge {
+            panic!(
+                "Rect at ({}, {}) sized {}x{} runs past i32::MAX.",
+                x, y, width, height
+            );
+        }
+        Rect {
+            origin: Point { x, y },
+            size: Rectangle { width, height },
+        }
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    pub fn size(&self) -> Rectangle {
+        self.size
+    }
+
+    // the smallest rect covering x0..x1 and y0..y1, callers make sure the far edges fit
+    fn from_edges(x0: i64, y0: i64, x1: i64, y1: i64) -> Rect {
+        Rect::new(x0 as i32, y0 as i32, (x1 - x0) as u32, (y1 - y0) as u32)
+    }
+
+    pub fn left(&self) -> i64 {
+        self.origin.x as i64
+    }
+
+    pub fn top(&self) -> i64 {
+        self.origin.y as i64
+    }
+
+    /// First column past the rect.
+    pub fn right(&self) -> i64 {
+        self.left() + self.size.width as i64
+    }
+
+    /// First row past the rect.
+    pub fn bottom(&self) -> i64 {
+        self.top() + self.size.height as i64
+    }
+
+    pub fn area(&self) -> u64 {
+        self.size.width as u64 * self.size.height as u64
+    }
+
+    /// Covers no cells at all.
+    pub fn is_empty(&self) -> bool {
+        self.size.width == 0 || self.size.height == 0
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        let (x, y) = (point.x as i64, point.y as i64);
+        self.left() <= x && x < self.right() && self.top() <= y && y < self.bottom()
+    }
+
+    /// Every cell of `other` is inside, edges may coincide. An empty rect fits anywhere inside.
+    pub fn contains(&self, other: &Rect) -> bool {
+        self.left() <= other.left()
+            && other.right() <= self.right()
+            && self.top() <= other.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Where `size` goes if it is placed in the top left corner, turned by 90° if it only fits that way.
+    pub fn place(&self, size: &Rectangle) -> Option<Rect> {
+        [*size, size.rotated()]
+            .into_iter()
+            .map(|size| size.at(self.origin))
+            .find(|placed| self.contains(placed))
+    }
+
+    /// Shares at least one cell, rects that only touch along an edge don't overlap.
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Shares an edge or a corner without overlapping.
+    pub fn touches(&self, other: &Rect) -> bool {
+        if self.is_empty() || other.is_empty() || self.overlaps(other) {
+            return false;
+        }
+        let columns = self.left() <= other.right() && other.left() <= self.right();
+        let rows = self.top() <= other.bottom() && other.top() <= self.bottom();
+        columns && rows
+    }
+
+    /// The cells both rects cover, `None` if there aren't any.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if left < right && top < bottom {
+            Some(Rect::from_edges(left, top, right, bottom))
+        } else {
+            None
+        }
+    }
+
+    /// The smallest rect covering both, `None` if it would be wider or taller than `u32::MAX`.
+    ///
+    /// Empty rects still count, their origin is a point the box has to reach.
+    pub fn union(&self, other: &Rect) -> Option<Rect> {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        if right - left > u32::MAX as i64 || bottom - top > u32::MAX as i64 {
+            return None;
+        }
+        Some(Rect::from_edges(left, top, right, bottom))
+    }
+
+    /// Cuts into a left part `width` wide and whatever is to its right.
+    ///
+    /// `None` unless both parts are at least one column wide.
+    pub fn split_vertical(&self, width: u32) -> Option<(Rect, Rect)> {
+        if width == 0 || width >= self.size.width {
+            return None;
+        }
+        let x = self.left() + width as i64;
+        Some((
+            Rect::from_edges(self.left(), self.top(), x, self.bottom()),
+            Rect::from_edges(x, self.top(), self.right(), self.bottom()),
+        ))
+    }
+
+    /// Cuts into a top part `height` tall and whatever is below it.
+    ///
+    /// `None` unless both parts are at least one row tall.
+    pub fn split_horizontal(&self, height: u32) -> Option<(Rect, Rect)> {
+        if height == 0 || height >= self.size.height {
+            return None;
+        }
+        let y = self.top() + height as i64;
+        Some((
+            Rect::from_edges(self.left(), self.top(), self.right(), y),
+            Rect::from_edges(self.left(), y, self.right(), self.bottom()),
+        ))
+    }
+
+    /// The parts of `self` not covered by `other`, at most four, together covering exactly what is left.
+    pub fn subtract(&self, other: &Rect) -> Vec<Rect> {
+        let Some(cut) = self.intersection(other) else {
+            return if self.is_empty() {
+                Vec::new()
+            } else {
+                vec![*self]
+            };
+        };
+        // a band above the cut and one below it, full width, then what is left beside it
+        let pieces = [
+            (self.left(), self.top(), self.right(), cut.top()),
+            (self.left(), cut.bottom(), self.right(), self.bottom()),
+            (self.left(), cut.top(), cut.left(), cut.bottom()),
+            (cut.right(), cut.top(), self.right(), cut.bottom()),
+        ];
+        pieces
+            .into_iter()
+            .filter(|&(x0, y0, x1, y1)| x0 < x1 && y0 < y1)
+            .map(|(x0, y0, x1, y1)| Rect::from_edges(x0, y0, x1, y1))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect::new(x, y, width, height)
+    }
+
+    // every cell a rect covers, small rects only
+    fn cells(rect: &Rect) -> Vec<(i64, i64)> {
+        (rect.top()..rect.bottom())
+            .flat_map(|y| (rect.left()..rect.right()).map(move |x| (x, y)))
+            .collect()
+    }
+
+    // rects on a small grid, big enough for every way two rects can sit relative to each other
+    fn grid() -> Vec<Rect> {
+        let mut rects = Vec::new();
+        for x in -1..3 {
+            for y in -1..3 {
+                for width in 0..4 {
+                    for height in 0..3 {
+                        rects.push(r(x, y, width, height));
+                    }
+                }
+            }
+        }
+        rects
+    }
+
+    #[test]
+    fn sizes() {
+        let larger = Rectangle {
+            width: 8,
+            height: 7,
+        };
+        let tall = Rectangle {
+            width: 2,
+            height: 7,
+        };
+        assert_eq!(larger.area(), 56);
+        assert_eq!(Rectangle::square(3).area(), 9);
+        // strictly bigger on both sides, so equal heights don't fit
+        assert!(!larger.can_hold(&tall));
+        assert!(!Rectangle::square(7).can_hold_rotated(&tall));
+        assert!(Rectangle::square(8).can_hold_rotated(&Rectangle {
+            width: 7,
+            height: 2
+        }));
+        let wide = Rectangle {
+            width: 10,
+            height: 3,
+        };
+        assert!(!wide.can_hold(&tall.rotated().rotated()));
+        assert!(wide.can_hold_rotated(&Rectangle {
+            width: 2,
+            height: 9
+        }));
+    }
+
+    #[test]
+    fn edges_and_points() {
+        let rect = r(-2, 3, 4, 2);
+        assert_eq!(
+            (rect.left(), rect.top(), rect.right(), rect.bottom()),
+            (-2, 3, 2, 5)
+        );
+        assert_eq!(rect.area(), 8);
+        assert!(rect.contains_point(Point::new(-2, 3)));
+        assert!(rect.contains_point(Point::new(1, 4)));
+        // the far edges belong to the neighbours
+        assert!(!rect.contains_point(Point::new(2, 4)));
+        assert!(!rect.contains_point(Point::new(1, 5)));
+        assert!(!r(0, 0, 0, 5).contains_point(Point::new(0, 0)));
+
+        let far = r(i32::MIN, i32::MAX, u32::MAX, 1);
+        assert_eq!(far.right(), i32::MAX as i64);
+        assert!(far.contains_point(Point::new(i32::MAX - 1, i32::MAX)));
+        assert!(!far.contains_point(Point::new(i32::MAX, i32::MAX)));
+    }
+
+    #[test]
+    fn intersection_matches_the_shared_cells() {
+        for a in grid() {
+            for b in grid() {
+                let shared: Vec<_> = cells(&a)
+                    .into_iter()
+                    .filter(|cell| cells(&b).contains(cell))
+                    .collect();
+                let cut = a.intersection(&b);
+                assert_eq!(cut.map(|c| cells(&c)).unwrap_or_default(), shared);
+                assert_eq!(a.overlaps(&b), !shared.is_empty(), "{:?} {:?}", a, b);
+                assert_eq!(cut, b.intersection(&a));
+            }
+        }
+    }
+
+    #[test]
+    fn edge_touching() {
+        let a = r(0, 0, 2, 2);
+        let right = r(2, 0, 2, 2);
+        let below = r(0, 2, 2, 2);
+        let corner = r(2, 2, 1, 1);
+        let apart = r(3, 0, 1, 1);
+        for other in [right, below, corner] {
+            assert!(!a.overlaps(&other));
+            assert!(a.touches(&other) && other.touches(&a));
+            assert_eq!(a.intersection(&other), None);
+        }
+        assert!(!a.touches(&apart));
+        assert!(!a.touches(&r(1, 1, 2, 2)), "overlapping isn't touching");
+        assert!(!a.touches(&r(2, 0, 0, 2)), "empty rects touch nothing");
+        assert_eq!(a.union(&right), Some(r(0, 0, 4, 2)));
+        assert_eq!(a.union(&corner), Some(r(0, 0, 3, 3)));
+    }
+
+    #[test]
+    fn union_is_the_bounding_box() {
+        for a in grid() {
+            for b in grid() {
+                let union = a.union(&b).unwrap();
+                assert!(union.contains(&a) && union.contains(&b));
+                assert_eq!(union, b.union(&a).unwrap());
+                // nothing smaller would do, every edge belongs to one of the two
+                assert!([a.left(), b.left()].contains(&union.left()));
+                assert!([a.right(), b.right()].contains(&union.right()));
+                assert!([a.top(), b.top()].contains(&union.top()));
+                assert!([a.bottom(), b.bottom()].contains(&union.bottom()));
+            }
+        }
+        // the last column is i32::MAX, one more than u32::MAX columns from i32::MIN
+        let wide = r(i32::MIN, 0, u32::MAX, 1);
+        assert_eq!(wide.union(&r(i32::MAX - 1, 0, 1, 1)), Some(wide));
+        assert_eq!(wide.union(&r(i32::MAX, 0, 1, 1)), None);
+    }
+
+    #[test]
+    fn containment() {
+        let outer = r(0, 0, 4, 3);
+        assert!(outer.contains(&outer));
+        assert!(
+            outer.contains(&r(2, 1, 2, 2)),
+            "sharing the far edges is fine"
+        );
+        assert!(!outer.contains(&r(3, 0, 2, 1)));
+        assert!(!outer.contains(&r(-1, 0, 1, 1)));
+        for a in grid() {
+            for b in grid() {
+                if !b.is_empty() {
+                    let covered = cells(&b).iter().all(|cell| cells(&a).contains(cell));
+                    assert_eq!(a.contains(&b), covered, "{:?} {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "runs past i32::MAX")]
+    fn rects_stay_on_the_grid() {
+        r(i32::MAX, 0, 2, 1);
+    }
+
+    #[test]
+    fn placing_may_rotate() {
+        let shelf = r(5, 5, 4, 2);
+        let tall = Rectangle {
+            width: 2,
+            height: 4,
+        };
+        assert_eq!(shelf.place(&tall), Some(r(5, 5, 4, 2)));
+        assert_eq!(shelf.place(&tall.rotated()), Some(r(5, 5, 4, 2)));
+        assert_eq!(shelf.place(&Rectangle::square(2)), Some(r(5, 5, 2, 2)));
+        assert_eq!(shelf.place(&Rectangle::square(3)), None);
+    }
+
+    #[test]
+    fn splitting_keeps_every_cell_once() {
+        let rect = r(-1, 2, 5, 3);
+        assert_eq!(
+            rect.split_vertical(2),
+            Some((r(-1, 2, 2, 3), r(1, 2, 3, 3)))
+        );
+        assert_eq!(
+            rect.split_horizontal(1),
+            Some((r(-1, 2, 5, 1), r(-1, 3, 5, 2)))
+        );
+        for at in [0, 5, 6] {
+            assert_eq!(rect.split_vertical(at), None);
+        }
+        for at in [0, 3] {
+            assert_eq!(rect.split_horizontal(at), None);
+        }
+        for at in 1..5 {
+            let (a, b) = rect.split_vertical(at).unwrap();
+            assert!(a.touches(&b) && !a.overlaps(&b));
+            assert_eq!(a.union(&b), Some(rect));
+            assert_eq!(a.area() + b.area(), rect.area());
+        }
+    }
+
+    #[test]
+    fn subtracting_leaves_exactly_the_rest() {
+        for a in grid() {
+            for b in grid() {
+                let pieces = a.subtract(&b);
+                let mut left: Vec<_> = pieces.iter().flat_map(cells).collect();
+                left.sort();
+                let mut expected: Vec<_> = cells(&a)
+                    .into_iter()
+                    .filter(|cell| !cells(&b).contains(cell))
+                    .collect();
+                expected.sort();
+                assert_eq!(left, expected, "{:?} - {:?}", a, b);
+                assert!(pieces.len() <= 4);
+                assert!(pieces.iter().all(|p| !p.is_empty() && !p.overlaps(&b)));
+            }
+        }
+    }
+}
@@ -1,26 +1,118 @@
+pub mod arith;
+pub mod geometry;
+pub mod po;
+#[cfg(test)]
+mod prop;
+
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
 
+// add overflows like any `+`, these say what should happen when the sum doesn't fit
+pub fn checked_add(left: usize, right: usize) -> Option<usize> {
+    arith::checked_add(left, right)
+}
+
+pub fn saturating_add(left: usize, right: usize) -> usize {
+    arith::saturating_add(left, right)
+}
+
+pub fn wrapping_add(left: usize, right: usize) -> usize {
+    arith::wrapping_add(left, right)
+}
+
 pub fn add_two(a: i32) -> i32 {
     a + 2
 }
 
-#[derive(Debug)]
-struct Rectangle {
-    width: u32,
-    height: u32,
+pub fn checked_add_two(a: i32) -> Option<i32> {
+    checked_internal_adder(a, 2)
 }
-impl Rectangle {
-    fn can_hold(&self, other: &Rectangle) -> bool {
-        self.width > other.width && self.height > other.height
-    }
+
+pub fn saturating_add_two(a: i32) -> i32 {
+    saturating_internal_adder(a, 2)
+}
+
+pub fn wrapping_add_two(a: i32) -> i32 {
+    wrapping_internal_adder(a, 2)
 }
 
+// Rectangle and can_hold now live in the geometry module, shared with notes/struct/rectangle_method.rs
+pub use geometry::Rectangle;
+
 pub fn greeting(name: &str) -> String {
     format!("Hello {}!", name)
 }
 
+// the english strings double as msgids, a catalog that doesn't have them leaves them in english
+const HELLO: &str = "Hello {}!";
+const HELLO_GUEST: &str = "Hello to {} guest!";
+const HELLO_GUESTS: &str = "Hello to {} guests!";
+
+// the catalogs the book is translated with, parsed the first time a locale asks for one
+// a catalog that doesn't parse is left out, its locale gets english like any untranslated one
+static BUNDLED: [(&str, &str); 1] = [("ja", include_str!("../../../../po/ja.po"))];
+
+fn bundled(locale: &str) -> Option<&'static po::Catalog> {
+    static CATALOGS: std::sync::OnceLock<Vec<(&str, po::Catalog)>> = std::sync::OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| {
+        BUNDLED
+            .iter()
+            .filter_map(|&(language, text)| Some((language, po::Catalog::parse(text).ok()?)))
+            .collect()
+    });
+    // ja_JP.UTF-8, ja-JP and ja all mean the ja catalog
+    let locale = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or("")
+        .replace('-', "_");
+    let language = locale.split('_').next().unwrap_or("");
+    [locale.as_str(), language]
+        .iter()
+        .find_map(|wanted| catalogs.iter().find(|(l, _)| l == wanted))
+        .map(|(_, catalog)| catalog)
+}
+
+/// `greeting` in the language of `locale`, in english when there is no translation for it.
+pub fn greeting_in(locale: &str, name: &str) -> String {
+    match bundled(locale) {
+        Some(catalog) => greeting_with(catalog, name),
+        None => greeting(name),
+    }
+}
+
+pub fn greeting_with(catalog: &po::Catalog, name: &str) -> String {
+    catalog
+        .gettext(HELLO)
+        .unwrap_or(HELLO)
+        .replacen("{}", name, 1)
+}
+
+/// Greets `count` guests, with the plural form the locale's grammar wants.
+pub fn guests_greeting_in(locale: &str, count: u64) -> String {
+    match bundled(locale) {
+        Some(catalog) => guests_greeting_with(catalog, count),
+        None => english_guests(count),
+    }
+}
+
+pub fn guests_greeting_with(catalog: &po::Catalog, count: u64) -> String {
+    match catalog.ngettext(HELLO_GUEST, count) {
+        Some(template) => template.replacen("{}", &count.to_string(), 1),
+        None => english_guests(count),
+    }
+}
+
+fn english_guests(count: u64) -> String {
+    let template = if count == 1 {
+        HELLO_GUEST
+    } else {
+        HELLO_GUESTS
+    };
+    template.replacen("{}", &count.to_string(), 1)
+}
+
 pub struct Guess {
     value: i32,
 }
@@ -43,6 +135,18 @@ fn internal_adder(a: i32, b: i32) -> i32 {
     a + b
 }
 
+fn checked_internal_adder(a: i32, b: i32) -> Option<i32> {
+    arith::checked_add(a, b)
+}
+
+fn saturating_internal_adder(a: i32, b: i32) -> i32 {
+    arith::saturating_add(a, b)
+}
+
+fn wrapping_internal_adder(a: i32, b: i32) -> i32 {
+    arith::wrapping_add(a, b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,6 +212,55 @@ mod tests {
         );
     }
 
+    // the book's ja.po has no entry for the greeting yet, so japanese falls back to english
+    #[test]
+    fn greeting_falls_back_to_english() {
+        assert_eq!(greeting_in("ja_JP.UTF-8", "Carol"), "Hello Carol!");
+        assert_eq!(greeting_in("fr", "Carol"), "Hello Carol!");
+        assert_eq!(guests_greeting_in("ja", 1), "Hello to 1 guest!");
+        assert_eq!(guests_greeting_in("de_DE", 3), "Hello to 3 guests!");
+        // but the catalog itself is there
+        let ja = bundled("ja-JP").unwrap();
+        assert_eq!(ja.language(), Some("ja"));
+        assert_eq!(ja.gettext("Introduction"), Some("はじめに"));
+    }
+
+    // bundled skips a broken catalog, so this is the test that notices one
+    #[test]
+    fn bundled_catalogs_parse() {
+        for (language, text) in BUNDLED {
+            if let Err(e) = po::Catalog::parse(text) {
+                panic!("po/{}.po does not parse: {}", language, e);
+            }
+        }
+    }
+
+    #[test]
+    fn greeting_uses_translations_and_plurals() {
+        let ru = po::Catalog::parse(
+            r#"msgid ""
+msgstr "Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\n"
+
+msgid "Hello {}!"
+msgstr "Привет, {}!"
+
+msgid "Hello to {} guest!"
+msgid_plural "Hello to {} guests!"
+msgstr[0] "Привет {} гостю!"
+msgstr[1] "Привет {} гостям!"
+msgstr[2] "Привет {} гостям!"
+"#,
+        )
+        .unwrap();
+        assert_eq!(greeting_with(&ru, "Carol"), "Привет, Carol!");
+        assert_eq!(guests_greeting_with(&ru, 21), "Привет 21 гостю!");
+        assert_eq!(guests_greeting_with(&ru, 11), "Привет 11 гостям!");
+
+        let empty = po::Catalog::parse("").unwrap();
+        assert_eq!(greeting_with(&empty, "Carol"), greeting("Carol"));
+        assert_eq!(guests_greeting_with(&empty, 0), "Hello to 0 guests!");
+    }
+
     // The test passes if the code inside the function panics; the test fails if the code inside the function doesn’t panic.
     #[test]
     #[should_panic]
@@ -146,6 +299,22 @@ mod tests {
         assert_eq!(5, value);
     }
 
+    // the harness keeps what a test prints to itself, so run the passing one again in a child and read its stdout
+    #[test]
+    fn printed_output_can_be_captured() {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["tests::this_test_will_pass", "--exact", "--nocapture"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains("I got the value 4\n"),
+            "stdout was `{}`",
+            stdout
+        );
+    }
+
     // you can ignore time consuming tests like this
     #[test]
     #[ignore]
@@ -157,5 +326,31 @@ mod tests {
     #[test]
     fn internal() {
         assert_eq!(4, internal_adder(2, 2));
+        assert_eq!(checked_internal_adder(i32::MAX, 1), None);
+        assert_eq!(saturating_internal_adder(i32::MIN, -1), i32::MIN);
+        assert_eq!(wrapping_internal_adder(i32::MAX, 1), i32::MIN);
+    }
+
+    // in a release build add(usize::MAX, 1) would quietly give 0, the variants make the choice explicit
+    #[test]
+    fn overflow_variants() {
+        assert_eq!(checked_add(2, 2), Some(4));
+        assert_eq!(checked_add(usize::MAX, 1), None);
+        assert_eq!(saturating_add(usize::MAX, 1), usize::MAX);
+        assert_eq!(wrapping_add(usize::MAX, 1), 0);
+
+        assert_eq!(checked_add_two(i32::MAX - 1), None);
+        assert_eq!(saturating_add_two(i32::MAX), i32::MAX);
+        assert_eq!(wrapping_add_two(i32::MAX), i32::MIN + 1);
+        assert_eq!(arith::checked_add(i32::MIN, -1), None);
+    }
+
+    // add_two is just a checked add of 2, for every i32 the property framework throws at it
+    #[test]
+    fn add_two_matches_the_generic_version() {
+        prop::check("add_two agrees with checked_add", |a: i32| {
+            checked_add_two(a) == a.checked_add(2)
+                && checked_add_two(a).is_none_or(|sum| sum == add_two(a))
+        });
     }
 }
@@ -0,0 +1,638 @@
+// a small reader for gettext .po catalogs, the same files the book is translated with (po/ja.po)
+// an entry is a msgid and its msgstr, or a msgid/msgid_plural pair with one msgstr[n] per plural form
+// which msgstr[n] to use for a count comes from the Plural-Forms line in the header entry,
+// a C expression in n such as `n != 1` that is evaluated here with a tiny parser
+// fuzzy entries and empty translations are treated as missing, like gettext does at runtime
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for PoError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Translation {
+    Singular(String),
+    /// One string per plural form, in the order of the catalog's plural rule.
+    Plural(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catalog {
+    language: Option<String>,
+    plural: Plural,
+    // keyed by msgctxt and msgid
+    messages: HashMap<(Option<String>, String), Translation>,
+}
+
+impl Catalog {
+    pub fn parse(text: &str) -> Result<Catalog, PoError> {
+        let mut catalog = Catalog {
+            language: None,
+            plural: Plural::germanic(),
+            messages: HashMap::new(),
+        };
+        let mut entry = Entry::default();
+        for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+            let error = |message: String| PoError {
+                line: number,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                catalog.finish(std::mem::take(&mut entry), number)?;
+                continue;
+            }
+            if let Some(flags) = line.strip_prefix("#,") {
+                // flags come before the entry they belong to, so a new entry starts here
+                catalog.finish(std::mem::take(&mut entry), number)?;
+                entry.fuzzy = flags.split(',').any(|flag| flag.trim() == "fuzzy");
+                continue;
+            }
+            if line.starts_with('#') {
+                // translator and reference comments, and obsolete #~ entries
+                continue;
+            }
+            if line.starts_with('"') {
+                // a continuation line adds to whatever the last keyword started
+                let text = unquote(line).map_err(error)?;
+                let field = entry
+                    .reading
+                    .ok_or_else(|| error("string without a keyword".to_string()))?;
+                entry.field(field).push_str(&text);
+                continue;
+            }
+
+            let (keyword, rest) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("expected a keyword and a string".to_string()))?;
+            let text = unquote(rest.trim()).map_err(error)?;
+            let field = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgid_plural" => Field::IdPlural,
+                "msgstr" => Field::Str,
+                _ => keyword
+                    .strip_prefix("msgstr[")
+                    .and_then(|k| k.strip_suffix(']'))
+                    .and_then(|n| n.parse().ok())
+                    .map(Field::Form)
+                    .ok_or_else(|| error(format!("unknown keyword {}", keyword)))?,
+            };
+            // entries don't have to be separated by blank lines, a msgctxt or msgid after a msgstr starts the next one
+            let starts_entry = matches!(field, Field::Context | Field::Id);
+            if starts_entry && (entry.msgstr.is_some() || !entry.forms.is_empty()) {
+                catalog.finish(std::mem::take(&mut entry), number)?;
+            }
+            if let Field::Form(form) = field {
+                if form != entry.forms.len() {
+                    return Err(error(format!("expected msgstr[{}]", entry.forms.len())));
+                }
+                entry.forms.push(String::new());
+            }
+            *entry.field(field) = text;
+            entry.reading = Some(field);
+        }
+        catalog.finish(entry, text.lines().count() + 1)?;
+        Ok(catalog)
+    }
+
+    // files all the strings of one entry, the header entry sets up the catalog instead
+    fn finish(&mut self, entry: Entry, line: usize) -> Result<(), PoError> {
+        let Some(msgid) = entry.msgid else {
+            if entry.msgstr.is_some() || !entry.forms.is_empty() {
+                return Err(PoError {
+                    line,
+                    message: "msgstr without a msgid".to_string(),
+                });
+            }
+            return Ok(());
+        };
+        if msgid.is_empty() && entry.context.is_none() {
+            return self.header(&entry.msgstr.unwrap_or_default(), line);
+        }
+        if entry.fuzzy {
+            return Ok(());
+        }
+        let translation = match entry.msgid_plural {
+            Some(_) => {
+                if entry.forms.iter().all(|form| form.is_empty()) {
+                    return Ok(());
+                }
+                Translation::Plural(entry.forms)
+            }
+            None => match entry.msgstr {
+                Some(text) if !text.is_empty() => Translation::Singular(text),
+                _ => return Ok(()),
+            },
+        };
+        self.messages.insert((entry.context, msgid), translation);
+        Ok(())
+    }
+
+    fn header(&mut self, header: &str, line: usize) -> Result<(), PoError> {
+        for field in header.lines() {
+            let Some((name, value)) = field.split_once(':') else {
+                continue;
+            };
+            match name.trim() {
+                "Language" => self.language = Some(value.trim().to_string()),
+                "Plural-Forms" => {
+                    self.plural = Plural::parse(value).map_err(|message| PoError {
+                        line,
+                        message: format!("bad Plural-Forms: {}", message),
+                    })?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The `Language` from the header, if it has one.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Translated messages, not counting the header.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// The translation of `msgid`, `None` if it is missing, fuzzy or empty.
+    pub fn gettext(&self, msgid: &str) -> Option<&str> {
+        self.pgettext(None, msgid)
+    }
+
+    /// Like `gettext` for an entry with a `msgctxt`.
+    pub fn pgettext(&self, context: Option<&str>, msgid: &str) -> Option<&str> {
+        match self
+            .messages
+            .get(&(context.map(str::to_string), msgid.to_string()))?
+        {
+            Translation::Singular(text) => Some(text),
+            // a plural entry looked up without a count gets its first form
+            Translation::Plural(forms) => forms.first().map(String::as_str),
+        }
+    }
+
+    /// The form of `msgid` for `n`, or `None` so the caller can fall back to the English pair.
+    pub fn ngettext(&self, msgid: &str, n: u64) -> Option<&str> {
+        match self.messages.get(&(None, msgid.to_string()))? {
+            Translation::Plural(forms) => forms
+                .get(self.plural.form(n))
+                .map(String::as_str)
+                .filter(|form| !form.is_empty()),
+            Translation::Singular(text) => Some(text),
+        }
+    }
+
+    /// Which `msgstr[i]` this catalog uses for `n`.
+    pub fn plural_form(&self, n: u64) -> usize {
+        self.plural.form(n)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    IdPlural,
+    Str,
+    Form(usize),
+}
+
+// one entry while it is being read
+#[derive(Default)]
+struct Entry {
+    fuzzy: bool,
+    context: Option<String>,
+    msgid: Option<String>,
+    msgid_plural: Option<String>,
+    msgstr: Option<String>,
+    forms: Vec<String>,
+    // where continuation lines go
+    reading: Option<Field>,
+}
+
+impl Entry {
+    fn field(&mut self, field: Field) -> &mut String {
+        let slot = match field {
+            Field::Context => &mut self.context,
+            Field::Id => &mut self.msgid,
+            Field::IdPlural => &mut self.msgid_plural,
+            Field::Str => &mut self.msgstr,
+            Field::Form(form) => return &mut self.forms[form],
+        };
+        slot.get_or_insert_with(String::new)
+    }
+}
+
+// "a \"quoted\" string\n" to its text
+fn unquote(quoted: &str) -> Result<String, String> {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|q| q.strip_suffix('"'))
+        .filter(|_| quoted.len() >= 2)
+        .ok_or_else(|| format!("expected a quoted string, got {}", quoted))?;
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some('"') => text.push('"'),
+            Some('\\') => text.push('\\'),
+            other => return Err(format!("unknown escape \\{}", other.unwrap_or(' '))),
+        }
+    }
+    Ok(text)
+}
+
+// the plural rule, e.g. `nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 ? 1 : 2);`
+#[derive(Debug, Clone, PartialEq)]
+struct Plural {
+    count: usize,
+    rule: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    N,
+    Number(u64),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Plural {
+    // English and most of western europe: one, and everything else
+    fn germanic() -> Plural {
+        Plural {
+            count: 2,
+            rule: Expr::Binary(Box::new(Expr::N), Op::Ne, Box::new(Expr::Number(1))),
+        }
+    }
+
+    fn parse(value: &str) -> Result<Plural, String> {
+        let mut count = None;
+        let mut rule = None;
+        for part in value.split(';') {
+            match part.split_once('=') {
+                Some((name, value)) if name.trim() == "nplurals" => {
+                    count = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|_| format!("bad nplurals {}", value))?,
+                    )
+                }
+                Some((name, value)) if name.trim() == "plural" => {
+                    let mut parser = Parser::new(value)?;
+                    let expr = parser.ternary()?;
+                    parser.end()?;
+                    rule = Some(expr);
+                }
+                _ => {}
+            }
+        }
+        Ok(Plural {
+            count: count.filter(|&n| n > 0).ok_or("missing nplurals")?,
+            rule: rule.ok_or("missing plural")?,
+        })
+    }
+
+    // a rule that points past the last form gets the last form, rather than nothing
+    fn form(&self, n: u64) -> usize {
+        (self.rule.eval(n) as usize).min(self.count - 1)
+    }
+}
+
+impl Expr {
+    // C semantics: comparisons give 0 or 1, dividing by zero gives 0 instead of crashing
+    fn eval(&self, n: u64) -> u64 {
+        match self {
+            Expr::N => n,
+            Expr::Number(value) => *value,
+            Expr::Not(e) => (e.eval(n) == 0) as u64,
+            Expr::Ternary(c, a, b) => {
+                if c.eval(n) != 0 {
+                    a.eval(n)
+                } else {
+                    b.eval(n)
+                }
+            }
+            Expr::Binary(a, op, b) => {
+                let (a, b) = (a.eval(n), b.eval(n));
+                match op {
+                    Op::Or => (a != 0 || b != 0) as u64,
+                    Op::And => (a != 0 && b != 0) as u64,
+                    Op::Eq => (a == b) as u64,
+                    Op::Ne => (a != b) as u64,
+                    Op::Lt => (a < b) as u64,
+                    Op::Le => (a <= b) as u64,
+                    Op::Gt => (a > b) as u64,
+                    Op::Ge => (a >= b) as u64,
+                    Op::Add => a.wrapping_add(b),
+                    Op::Sub => a.wrapping_sub(b),
+                    Op::Mul => a.wrapping_mul(b),
+                    Op::Div => a.checked_div(b).unwrap_or(0),
+                    Op::Rem => a.checked_rem(b).unwrap_or(0),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    N,
+    Number(u64),
+    Op(&'static str),
+}
+
+// recursive descent over the C operators a plural rule can use, lowest precedence first
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+}
+
+const OPERATORS: [&str; 19] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "?", ":", "(", ")",
+    ";",
+];
+
+impl Parser {
+    fn new(text: &str) -> Result<Parser, String> {
+        let mut tokens = Vec::new();
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('n') {
+                tokens.push(Token::N);
+                rest = after;
+            } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let value = rest[..end]
+                    .parse()
+                    .map_err(|_| format!("number too big {}", &rest[..end]))?;
+                tokens.push(Token::Number(value));
+                rest = &rest[end..];
+            } else {
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| rest.starts_with(*op))
+                    .ok_or_else(|| format!("unexpected {}", rest))?;
+                tokens.push(Token::Op(op));
+                rest = &rest[op.len()..];
+            }
+            rest = rest.trim_start();
+        }
+        Ok(Parser { tokens, at: 0 })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn eat(&mut self, op: &'static str) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.at += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        self.eat(";");
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    fn ternary(&mut self) -> Result<Expr, String> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.ternary()?;
+        if !self.eat(":") {
+            return Err("expected :".to_string());
+        }
+        let otherwise = self.ternary()?;
+        Ok(Expr::Ternary(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    // precedence climbing, each level binds tighter than the one before it
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[(&str, Op)]; 6] = [
+            &[("||", Op::Or)],
+            &[("&&", Op::And)],
+            &[("==", Op::Eq), ("!=", Op::Ne)],
+            &[("<=", Op::Le), (">=", Op::Ge), ("<", Op::Lt), (">", Op::Gt)],
+            &[("+", Op::Add), ("-", Op::Sub)],
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        'operators: loop {
+            for &(symbol, op) in LEVELS[level] {
+                if self.eat(symbol) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(Box::new(left), op, Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let inner = self.ternary()?;
+            if !self.eat(")") {
+                return Err("expected )".to_string());
+            }
+            return Ok(inner);
+        }
+        let token = self.peek().cloned();
+        self.at += 1;
+        match token {
+            Some(Token::N) => Ok(Expr::N),
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("rule ends too early".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLISH: &str = r#"
+# header first, like msginit writes it
+msgid ""
+msgstr ""
+"Language: pl\n"
+"Plural-Forms: nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\n"
+
+#: src/lib.rs:1
+msgid "Hello {}!"
+msgstr "Cześć {}!"
+
+msgid "{} apple"
+msgid_plural "{} apples"
+msgstr[0] "{} jabłko"
+msgstr[1] "{} jabłka"
+msgstr[2] "{} jabłek"
+
+#, fuzzy
+msgid "Goodbye"
+msgstr "Do widzenia"
+
+msgid "Untranslated"
+msgstr ""
+
+msgctxt "menu"
+msgid "Open"
+msgstr "Otwórz"
+
+msgid ""
+"split over "
+"three \"lines\"\n"
+msgstr "po"
+"dzielone\n"
+"#;
+
+    #[test]
+    fn entries_are_read() {
+        let catalog = Catalog::parse(POLISH).unwrap();
+        assert_eq!(catalog.language(), Some("pl"));
+        assert_eq!(catalog.gettext("Hello {}!"), Some("Cześć {}!"));
+        assert_eq!(
+            catalog.gettext("split over three \"lines\"\n"),
+            Some("podzielone\n")
+        );
+        assert_eq!(catalog.pgettext(Some("menu"), "Open"), Some("Otwórz"));
+        assert_eq!(catalog.gettext("Open"), None);
+        assert_eq!(catalog.len(), 4);
+    }
+
+    #[test]
+    fn fuzzy_and_empty_translations_are_missing() {
+        let catalog = Catalog::parse(POLISH).unwrap();
+        assert_eq!(catalog.gettext("Goodbye"), None);
+        assert_eq!(catalog.gettext("Untranslated"), None);
+        assert_eq!(catalog.gettext("Never heard of it"), None);
+    }
+
+    #[test]
+    fn plural_forms_follow_the_header() {
+        let catalog = Catalog::parse(POLISH).unwrap();
+        let forms: Vec<usize> = [0, 1, 2, 4, 5, 12, 21, 22, 25, 112, 122]
+            .iter()
+            .map(|&n| catalog.plural_form(n))
+            .collect();
+        assert_eq!(forms, [2, 0, 1, 1, 2, 2, 2, 1, 2, 2, 1]);
+        assert_eq!(catalog.ngettext("{} apple", 22), Some("{} jabłka"));
+        assert_eq!(catalog.ngettext("{} apple", 5), Some("{} jabłek"));
+    }
+
+    #[test]
+    fn without_a_header_plurals_are_english() {
+        let catalog = Catalog::parse("msgid \"a\"\nmsgstr \"b\"\n").unwrap();
+        assert_eq!(catalog.plural_form(1), 0);
+        assert_eq!(catalog.plural_form(0), 1);
+        assert_eq!(catalog.plural_form(2), 1);
+    }
+
+    #[test]
+    fn plural_rules() {
+        let form = |rule: &str, n: u64| Plural::parse(rule).unwrap().form(n);
+        assert_eq!(form("nplurals=1; plural=0;", 5), 0);
+        assert_eq!(form("nplurals=2; plural=(n > 1);", 0), 0);
+        assert_eq!(form("nplurals=2; plural=(n > 1);", 2), 1);
+        assert_eq!(form("nplurals=2; plural=!(n == 1) ;", 1), 0);
+        // out of range answers land on the last form
+        assert_eq!(form("nplurals=2; plural=n;", 7), 1);
+        assert_eq!(form("nplurals=2; plural=n % 0;", 7), 0);
+        assert_eq!(form("nplurals=3; plural=n-1*2+3;", 0), 1);
+        assert!(Plural::parse("nplurals=2; plural=(n > 1;").is_err());
+        assert!(Plural::parse("nplurals=2; plural=n $ 1;").is_err());
+        assert!(Plural::parse("plural=n;").is_err());
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        assert_eq!(
+            Catalog::parse("msgid \"a\"\nmsgstr \"unterminated\n").unwrap_err(),
+            PoError {
+                line: 2,
+                message: "expected a quoted string, got \"unterminated".to_string()
+            }
+        );
+        assert_eq!(Catalog::parse("\"stray\"\n").unwrap_err().line, 1);
+        assert_eq!(
+            Catalog::parse("msgid \"a\"\nmsgstr[1] \"b\"\n")
+                .unwrap_err()
+                .line,
+            2
+        );
+        assert_eq!(
+            Catalog::parse("msgid \"a\"\nmsgwhat \"b\"\n")
+                .unwrap_err()
+                .line,
+            2
+        );
+    }
+}
@@ -0,0 +1,167 @@
+// a tiny property testing framework, so the crate doesn't need quickcheck or proptest
+// a property is a function from a random input to bool, check() runs it on many inputs
+// when one fails it is shrunk towards zero so the panic shows the smallest input that still fails
+// the generator is seeded, so a failure happens again on every run until it is fixed
+use std::fmt::Debug;
+
+/// How many random inputs each property gets.
+pub const CASES: u32 = 512;
+
+/// Seed used unless `PROP_SEED` is set, change it to explore different inputs.
+pub const SEED: u64 = 0x5eed_ada2_2024_0001;
+
+/// A xorshift random number generator, good enough to pick test inputs.
+pub struct Gen {
+    state: u64,
+}
+
+impl Gen {
+    pub fn new(seed: u64) -> Gen {
+        // xorshift gets stuck at zero
+        Gen { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn next_u128(&mut self) -> u128 {
+        (self.next_u64() as u128) << 64 | self.next_u64() as u128
+    }
+
+    /// True roughly once in `n` calls.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.next_u64().is_multiple_of(n)
+    }
+}
+
+pub trait Arbitrary: Clone + Debug {
+    fn arbitrary(g: &mut Gen) -> Self;
+
+    /// Simpler values to try when `self` fails a property, most promising first.
+    fn shrink(&self) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+// random bits almost never hit the values where integer bugs live, so those are picked on purpose
+macro_rules! arbitrary_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Arbitrary for $t {
+                fn arbitrary(g: &mut Gen) -> $t {
+                    if g.one_in(4) {
+                        let edges = [
+                            0,
+                            1,
+                            <$t>::MIN,
+                            <$t>::MAX,
+                            <$t>::MIN.wrapping_add(1),
+                            <$t>::MAX.wrapping_sub(1),
+                            (0 as $t).wrapping_sub(1),
+                        ];
+                        edges[(g.next_u64() % edges.len() as u64) as usize]
+                    } else if g.one_in(3) {
+                        // small numbers, where off by one mistakes show up
+                        (g.next_u64() % 201) as $t
+                    } else {
+                        g.next_u128() as $t
+                    }
+                }
+
+                fn shrink(&self) -> Vec<$t> {
+                    let value = *self;
+                    if value == 0 {
+                        return Vec::new();
+                    }
+                    let mut smaller = vec![0, value / 2];
+                    // one step closer to zero from either side
+                    if value > 0 as $t {
+                        smaller.push(value - 1);
+                    } else {
+                        smaller.push(value.wrapping_add(1));
+                    }
+                    smaller.dedup();
+                    smaller.retain(|&s| s != value);
+                    smaller
+                }
+            }
+        )*
+    };
+}
+
+arbitrary_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<A: Arbitrary, B: Arbitrary> Arbitrary for (A, B) {
+    fn arbitrary(g: &mut Gen) -> (A, B) {
+        (A::arbitrary(g), B::arbitrary(g))
+    }
+
+    fn shrink(&self) -> Vec<(A, B)> {
+        let (a, b) = self;
+        let mut smaller: Vec<(A, B)> = a.shrink().into_iter().map(|a| (a, b.clone())).collect();
+        smaller.extend(b.shrink().into_iter().map(|b| (a.clone(), b)));
+        smaller
+    }
+}
+
+// keeps taking the first simpler input that still fails, until none does
+fn shrink<T: Arbitrary>(mut failing: T, property: &impl Fn(T) -> bool) -> T {
+    // a bound, in case shrink() ever goes round in circles
+    for _ in 0..10_000 {
+        match failing.shrink().into_iter().find(|s| !property(s.clone())) {
+            Some(simpler) => failing = simpler,
+            None => break,
+        }
+    }
+    failing
+}
+
+/// Runs `property` on `CASES` random inputs and panics with the smallest failing one.
+pub fn check<T: Arbitrary>(name: &str, property: impl Fn(T) -> bool) {
+    let seed = std::env::var("PROP_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(SEED);
+    let mut g = Gen::new(seed);
+    for case in 0..CASES {
+        let input = T::arbitrary(&mut g);
+        if !property(input.clone()) {
+            let smallest = shrink(input.clone(), &property);
+            panic!(
+                "property \"{}\" failed on case {} with seed {}\n  input: {:?}\n  shrunk to: {:?}",
+                name, case, seed, input, smallest
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_values_come_up() {
+        let mut g = Gen::new(SEED);
+        let values: Vec<i8> = (0..CASES).map(|_| i8::arbitrary(&mut g)).collect();
+        for edge in [0, 1, -1, i8::MIN, i8::MAX] {
+            assert!(values.contains(&edge), "{} never generated", edge);
+        }
+    }
+
+    #[test]
+    fn failures_shrink_to_the_smallest_input() {
+        let property = |(a, b): (u32, u32)| a < 1000 || b < 10;
+        assert_eq!(shrink((u32::MAX, 123_456), &property), (1000, 10));
+        assert_eq!(shrink(-77i64, &|n: i64| n > -5), -5);
+    }
+
+    #[test]
+    #[should_panic(expected = "shrunk to: 100")]
+    fn check_reports_the_shrunk_input() {
+        check("below 100", |n: u16| n < 100);
+    }
+}
@@ -1,4 +1,123 @@
 // Files in subdirectories of the tests directory don’t get compiled as separate crates or have sections in the test output.
+// every test binary that says `mod common;` gets its own copy, so not all of it is used everywhere
+#![allow(dead_code)]
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once, OnceLock};
+
+// scratch space for every test binary, one subdirectory per process
+const ROOT: &str = "adder-tests";
+
+static SETUP: Once = Once::new();
+static SETUP_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs the setup shared by every test in this binary, only the first call does any work.
 pub fn setup() {
-    // setup code specific to your library's tests would go here
-}
\ No newline at end of file
+    SETUP.call_once(|| {
+        SETUP_RUNS.fetch_add(1, Ordering::SeqCst);
+        let root = root();
+        fs::create_dir_all(&root).expect("could not create the test scratch directory");
+        sweep(&root);
+    });
+}
+
+/// How many times `setup` did its work, which should never be more than once.
+pub fn setup_runs() -> usize {
+    SETUP_RUNS.load(Ordering::SeqCst)
+}
+
+fn root() -> PathBuf {
+    std::env::temp_dir().join(ROOT)
+}
+
+// a run that crashed never dropped its directories, clear out the ones whose process is gone
+#[cfg(target_os = "linux")]
+fn sweep(root: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let pid = name.to_str().and_then(|name| name.split('-').next());
+        if let Some(pid) = pid.and_then(|pid| pid.parse::<u32>().ok()) {
+            if pid != std::process::id() && !Path::new("/proc").join(pid.to_string()).exists() {
+                let _ = fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sweep(_root: &Path) {}
+
+/// A directory of its own for one test, removed with everything in it when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(label: &str) -> TempDir {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        setup();
+        let n = NEXT.fetch_add(1, Ordering::SeqCst);
+        let path = root().join(format!("{}-{}-{}", std::process::id(), n, label));
+        fs::create_dir_all(&path).expect("could not create a temp directory");
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Writes `contents` to `name` inside the directory and returns its path.
+    pub fn write(&self, name: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("could not create a temp subdirectory");
+        }
+        fs::write(&path, contents).expect("could not write a temp file");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // a failing test still gets here while unwinding, there is nothing better to do with an error
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+type Slot = Arc<OnceLock<Arc<dyn Any + Send + Sync>>>;
+
+/// The value of the fixture called `name`, built by `init` the first time any test in this binary asks for it.
+///
+/// Tests asking at the same time wait for the one building it. Asking for the same name with a
+/// different type is a bug in the test and panics.
+pub fn fixture<T: Any + Send + Sync>(name: &str, init: impl FnOnce() -> T) -> Arc<T> {
+    static FIXTURES: OnceLock<Mutex<HashMap<String, Slot>>> = OnceLock::new();
+    setup();
+    // only hold the map long enough to find the slot, so a fixture can be built from other fixtures
+    let slot = FIXTURES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(name.to_string())
+        .or_default()
+        .clone();
+    let value = slot.get_or_init(|| Arc::new(init())).clone();
+    value.downcast().unwrap_or_else(|_| {
+        panic!(
+            "fixture {} was already built as a different type than {}",
+            name,
+            std::any::type_name::<T>()
+        )
+    })
+}
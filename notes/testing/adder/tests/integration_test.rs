@@ -8,4 +8,48 @@ mod common;
 fn it_adds_two_integration() {
     common::setup();
     assert_eq!(4, adder::add_two(2));
-}
\ No newline at end of file
+}
+
+#[test]
+fn temp_dirs_are_removed_on_drop() {
+    let dir = common::TempDir::new("drop");
+    let file = dir.write("nested/sum.txt", adder::add(2, 2).to_string());
+    assert_eq!("4", std::fs::read_to_string(&file).unwrap());
+
+    let other = common::TempDir::new("drop");
+    assert_ne!(dir.path(), other.path());
+
+    let path = dir.path().to_path_buf();
+    drop(dir);
+    assert!(!path.exists());
+    assert!(other.path().exists());
+}
+
+#[test]
+fn fixtures_are_built_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+    let sums = || {
+        common::fixture("sums", || {
+            BUILT.fetch_add(1, Ordering::SeqCst);
+            (0..100).map(adder::add_two).collect::<Vec<i32>>()
+        })
+    };
+
+    let threads: Vec<_> = (0..4).map(|_| std::thread::spawn(sums)).collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap()[40], 42);
+    }
+    assert_eq!(sums().len(), 100);
+    assert_eq!(BUILT.load(Ordering::SeqCst), 1);
+
+    common::setup();
+    assert_eq!(common::setup_runs(), 1);
+}
+
+#[test]
+#[should_panic(expected = "different type")]
+fn fixtures_keep_their_type() {
+    common::fixture("answer", || 42_u32);
+    common::fixture("answer", || "forty two");
+}